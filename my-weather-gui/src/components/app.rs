@@ -6,7 +6,13 @@ use super::{
 };
 use crate::TempUnit;
 use chrono::Local;
-use my_weather::{get_weather, types::to_forecast, ApiError, ForeCast};
+use my_weather::{
+  alerts::{AlertEngine, Rules},
+  climate::Normals,
+  get_weather,
+  types::to_forecast,
+  ApiError, ForeCast, Language,
+};
 use relm4::{
   factory::FactoryVecDeque, gtk::prelude::*, Component, ComponentController, ComponentParts,
   Controller, RelmWidgetExt,
@@ -15,9 +21,12 @@ use relm4::{
 /// Application state.
 pub struct AppModel {
   forecast: FactoryVecDeque<ForecastEntryAndTempUnit>,
+  language: Language,
   fetching: bool,
   status_message: String,
   status_dialog: Controller<ErrorDialogModel>,
+  alerts: AlertEngine,
+  normals: Normals,
   header: Controller<HeaderModel>,
   refresh_timer: Controller<RefreshWorker>,
 }
@@ -29,6 +38,8 @@ pub enum AppMsg {
   Error(String),
   /// Clear weather forecasts entries.
   ChangeUnit(TempUnit),
+  /// Change the display language.
+  ChangeLanguage(Language),
   /// Request fetch new forecast data
   Fetch,
 }
@@ -109,19 +120,31 @@ impl Component for AppModel {
   ) -> relm4::ComponentParts<Self> {
     let mut model = AppModel {
       forecast: FactoryVecDeque::new(gtk::Box::default(), sender.input_sender()),
+      language: Language::default(),
       fetching: false,
       status_dialog: ErrorDialogModel::builder().launch(()).detach(),
       status_message: String::new(),
+      alerts: AlertEngine::default(),
+      normals: Normals::default(),
       header: HeaderModel::builder()
         .launch(())
         .forward(sender.input_sender(), |msg| match msg {
           HeaderMsg::ChangeUnit(unit) => AppMsg::ChangeUnit(unit),
+          HeaderMsg::ChangeLanguage(language) => AppMsg::ChangeLanguage(language),
         }),
       refresh_timer: RefreshWorker::builder()
         .launch(())
         .forward(sender.input_sender(), |_| AppMsg::Fetch),
     };
     model.status_dialog.widget().set_transient_for(Some(root));
+    match Rules::from_env() {
+      Ok(rules) => model.alerts = AlertEngine::new(rules),
+      Err(err) => model.status_dialog.emit(DialogMsg::Open(format!("{err}"))),
+    }
+    match Normals::from_env() {
+      Ok(normals) => model.normals = normals,
+      Err(err) => model.status_dialog.emit(DialogMsg::Open(format!("{err}"))),
+    }
     model.handle_api_result(forecast);
     let forecast_factory = model.forecast.widget();
     let header = model.header.widget();
@@ -155,6 +178,14 @@ impl Component for AppModel {
       ChangeUnit(unit) => {
         self.forecast.guard().iter_mut().for_each(|fc| fc.1 = unit);
       }
+      ChangeLanguage(language) => {
+        self.language = language;
+        self
+          .forecast
+          .guard()
+          .iter_mut()
+          .for_each(|fc| fc.2 = language);
+      }
       Fetch => {
         self.fetching = true;
         sender.oneshot_command(async { FetchWeather::Fetched(get_weather().await) });
@@ -188,11 +219,28 @@ impl AppModel {
   fn handle_api_result(&mut self, result: Result<ForeCast, ApiError>) {
     match result {
       Ok(forecast) => {
+        let warnings = forecast.warnings().len();
+        let mut entries = to_forecast(forecast.entries());
+        self.normals.annotate(&mut entries, &Local);
+        let alerts = self.alerts.evaluate(&entries);
         self.forecast.guard().clear();
-        for fc in to_forecast(forecast.entries()) {
-          self.forecast.guard().push_back((fc, TempUnit::Celsius));
+        for fc in entries {
+          self
+            .forecast
+            .guard()
+            .push_back((fc, TempUnit::Celsius, self.language));
         }
         self.status_message = format!("Loaded weather at {}", Local::now().format("%v %r"));
+        if warnings > 0 {
+          self.status_message += &format!(" ({warnings} feed warnings)");
+        }
+        // Only the first new alert fits, the rest are counted.
+        if let Some(alert) = alerts.first() {
+          self.status_message += &format!(" — {alert}");
+          if alerts.len() > 1 {
+            self.status_message += &format!(" (+{} more)", alerts.len() - 1);
+          }
+        }
       }
       Err(err) => self.status_dialog.emit(DialogMsg::Open(format!("{err}"))),
     }
@@ -1,16 +1,22 @@
 use super::app::AppMsg;
 use crate::TempUnit;
 use gtk::pango::EllipsizeMode;
-use my_weather::types::{
-  CurrentForecastWithEntry, DayNight, Forecast, ForecastEntry, ForecastWithEntry, Temperature,
+use my_weather::{
+  l10n::{not_reported, Localize},
+  types::{
+    CurrentForecastWithEntry, DayNight, Forecast, ForecastEntry, ForecastWithEntry, Temperature,
+  },
+  Language,
 };
 use relm4::{gtk::prelude::*, prelude::FactoryComponent};
 
 #[derive(Debug)]
-pub struct ForecastEntryAndTempUnit(pub ForecastEntry, pub TempUnit);
+pub struct ForecastEntryAndTempUnit(pub ForecastEntry, pub TempUnit, pub Language);
 
 pub enum ForecastWidgets {
   Future {
+    row_container: gtk::Box,
+    day_of_week_label: Option<gtk::Label>,
     day_temp_label: Option<gtk::Label>,
     night_temp_label: Option<gtk::Label>,
   },
@@ -21,7 +27,7 @@ pub enum ForecastWidgets {
 }
 
 impl FactoryComponent for ForecastEntryAndTempUnit {
-  type Init = (ForecastEntry, TempUnit);
+  type Init = (ForecastEntry, TempUnit, Language);
   type Input = ();
   type Output = ();
   type ParentWidget = gtk::Box;
@@ -69,11 +75,11 @@ impl FactoryComponent for ForecastEntryAndTempUnit {
   }
 
   fn init_model(
-    (entry, unit): Self::Init,
+    (entry, unit, language): Self::Init,
     _index: &relm4::prelude::DynamicIndex,
     _sender: relm4::FactorySender<Self>,
   ) -> Self {
-    Self(entry, unit)
+    Self(entry, unit, language)
   }
 
   fn output_to_parent_input(_output: Self::Output) -> Option<Self::ParentInput> {
@@ -83,6 +89,8 @@ impl FactoryComponent for ForecastEntryAndTempUnit {
   fn update_view(&self, widgets: &mut Self::Widgets, _sender: relm4::FactorySender<Self>) {
     match widgets {
       ForecastWidgets::Future {
+        row_container,
+        day_of_week_label,
         day_temp_label: day_label,
         night_temp_label: night_label,
       } => {
@@ -90,24 +98,22 @@ impl FactoryComponent for ForecastEntryAndTempUnit {
           let update_units =
             |(dn, dn_label): (Option<&ForecastWithEntry>, Option<&mut gtk::Label>)| {
               if let (Some(dn), Some(dn_label)) = (dn, dn_label) {
-                let temp_string = match self.1 {
-                  TempUnit::Celsius => format!("{}", &dn.forecast.celsius),
-                  TempUnit::Fahrenheit => format!("{}", &dn.forecast.fahrenheit),
-                };
-                dn_label.set_label(&temp_string);
+                dn_label.set_label(&self.temperature_string(&dn.forecast));
+                dn_label.set_tooltip_text(self.temperature_tooltip(&dn.forecast).as_deref());
               }
             };
           update_units((day.as_ref(), day_label.as_mut()));
           update_units((night.as_ref(), night_label.as_mut()));
+
+          if let (Some(label), Some(fc)) = (day_of_week_label, day.as_ref().or(night.as_ref())) {
+            label.set_label(&fc.forecast.day_of_week.localize(self.2));
+          }
+          row_container.set_tooltip_markup(Some(&self.0.localized_summary(self.2)));
         }
       }
       ForecastWidgets::Current { temperature_label } => {
         if let ForecastEntry::Current(forecast) = &self.0 {
-          temperature_label.set_label(&if self.1 == TempUnit::Celsius {
-            format!("{}", forecast.current.celsius)
-          } else {
-            format!("{}", forecast.current.fahrenheit)
-          })
+          temperature_label.set_label(&self.current_temperature_string(forecast))
         }
       }
       ForecastWidgets::None => (),
@@ -127,7 +133,7 @@ impl ForecastEntryAndTempUnit {
         row_container.append(
           &gtk::Label::builder()
             .halign(gtk::Align::Center)
-            .tooltip_markup(&self.0.summary())
+            .tooltip_markup(&self.0.localized_summary(self.2))
             .label(&entry.title)
             .build(),
         );
@@ -142,7 +148,7 @@ impl ForecastEntryAndTempUnit {
     forecast: &CurrentForecastWithEntry,
     row_container: &gtk::Box,
   ) -> ForecastWidgets {
-    row_container.set_tooltip_markup(Some(&self.0.summary()));
+    row_container.set_tooltip_markup(Some(&self.0.localized_summary(self.2)));
 
     let info_container = gtk::Box::builder()
       .orientation(gtk::Orientation::Horizontal)
@@ -153,20 +159,28 @@ impl ForecastEntryAndTempUnit {
     let temperature_label = gtk::Label::builder()
       .css_name("temperature")
       .css_classes(vec!["current".into()])
-      .label(&if self.1 == TempUnit::Celsius {
-        format!("{}", forecast.current.celsius)
-      } else {
-        format!("{}", forecast.current.fahrenheit)
-      })
+      .label(&self.current_temperature_string(forecast))
       .build();
 
+    let description_label = gtk::Label::builder()
+      .css_name("description")
+      .label(
+        forecast
+          .current
+          .description
+          .as_deref()
+          .unwrap_or_else(|| not_reported(self.2)),
+      )
+      .build();
+    if forecast.current.celsius.is_none() {
+      temperature_label.add_css_class("unreported");
+    }
+    if forecast.current.description.is_none() {
+      description_label.add_css_class("unreported");
+    }
+
     info_container.append(&temperature_label);
-    info_container.append(
-      &gtk::Label::builder()
-        .css_name("description")
-        .label(&forecast.current.description)
-        .build(),
-    );
+    info_container.append(&description_label);
     row_container.append(&info_container);
 
     ForecastWidgets::Current { temperature_label }
@@ -179,21 +193,19 @@ impl ForecastEntryAndTempUnit {
     night: Option<&ForecastWithEntry>,
     row_container: &gtk::Box,
   ) -> ForecastWidgets {
-    row_container.set_tooltip_markup(Some(&self.0.summary()));
-    if let Some(day) = day
+    row_container.set_tooltip_markup(Some(&self.0.localized_summary(self.2)));
+    let day_of_week_label = day
       .or(night)
-      .iter()
-      .next()
-      .map(|fc| fc.forecast.day_of_week.as_str())
-    {
-      row_container.append(
-        &gtk::Label::builder()
-          .label(day)
+      .map(|fc| fc.forecast.day_of_week.localize(self.2))
+      .map(|day| {
+        let label = gtk::Label::builder()
+          .label(&day)
           .halign(gtk::Align::Center)
           .css_classes(vec!["dayofweek".into()])
-          .build(),
-      );
-    }
+          .build();
+        row_container.append(&label);
+        label
+      });
     let day_night_container = gtk::Box::builder()
       .orientation(gtk::Orientation::Horizontal)
       .spacing(5)
@@ -221,6 +233,8 @@ impl ForecastEntryAndTempUnit {
     row_container.append(&day_night_container);
 
     ForecastWidgets::Future {
+      row_container: row_container.clone(),
+      day_of_week_label,
       day_temp_label,
       night_temp_label,
     }
@@ -234,10 +248,7 @@ impl ForecastEntryAndTempUnit {
       .css_classes(vec!["tempAndDesc".into()])
       .build();
 
-    let temp_string = match self.1 {
-      TempUnit::Celsius => format!("{}", &forecast.celsius),
-      TempUnit::Fahrenheit => format!("{}", &forecast.fahrenheit),
-    };
+    let temp_string = self.temperature_string(forecast);
 
     let high_low_label = gtk::Label::builder()
       .css_name("temperature")
@@ -245,15 +256,20 @@ impl ForecastEntryAndTempUnit {
         Temperature::High(..) => "high".into(),
         Temperature::Low(..) => "low".into(),
         Temperature::Current(..) => "current".into(),
+        Temperature::Rising(..) => "rising".into(),
+        Temperature::Falling(..) => "falling".into(),
+        Temperature::Steady(..) => "steady".into(),
       }])
       .label(&temp_string)
       .justify(gtk::Justification::Right)
       .build();
 
+    high_low_label.set_tooltip_text(self.temperature_tooltip(forecast).as_deref());
+
     let mut day_night_label = gtk::Label::builder()
       .css_name("description")
       .ellipsize(EllipsizeMode::End)
-      .tooltip_text(&forecast.description)
+      .tooltip_text(&self.timeline_string(forecast))
       .label(&forecast.description);
 
     match forecast.day {
@@ -270,4 +286,43 @@ impl ForecastEntryAndTempUnit {
 
     (temp_label_container, high_low_label)
   }
+
+  /// When a temperature trend is reached and how far the temperature is
+  /// from normal in the selected unit, e.g. "by morning, 6° above normal".
+  fn temperature_tooltip(&self, forecast: &Forecast) -> Option<String> {
+    let parts = [
+      forecast.celsius.qualifier().map(|q| q.localize(self.2)),
+      forecast.normal.map(|normal| match self.1 {
+        TempUnit::Celsius => normal.localize(self.2),
+        TempUnit::Fahrenheit => normal.fahrenheit().localize(self.2),
+      }),
+    ];
+    let parts = parts.into_iter().flatten().collect::<Vec<_>>();
+    (!parts.is_empty()).then(|| parts.join(", "))
+  }
+
+  /// Conditions in order of occurrence, or the description when there is only one.
+  fn timeline_string(&self, forecast: &Forecast) -> String {
+    if forecast.timeline.len() < 2 {
+      return forecast.description.clone();
+    }
+    forecast.timeline.localize(self.2)
+  }
+
+  /// Current temperature in the selected unit and language, if it was reported.
+  fn current_temperature_string(&self, forecast: &CurrentForecastWithEntry) -> String {
+    let temperature = match self.1 {
+      TempUnit::Celsius => forecast.current.celsius.as_ref().map(|t| t.localize(self.2)),
+      TempUnit::Fahrenheit => forecast.current.fahrenheit.as_ref().map(|t| t.localize(self.2)),
+    };
+    temperature.unwrap_or_else(|| "—".into())
+  }
+
+  /// Temperature in the selected unit and language.
+  fn temperature_string(&self, forecast: &Forecast) -> String {
+    match self.1 {
+      TempUnit::Celsius => forecast.celsius.localize(self.2),
+      TempUnit::Fahrenheit => forecast.fahrenheit.localize(self.2),
+    }
+  }
 }
@@ -1,10 +1,12 @@
 //! A header component with title and menu button.
 use crate::TempUnit;
+use my_weather::Language;
 use relm4::{gtk::prelude::*, ComponentParts, SimpleComponent};
 
 #[derive(Debug)]
 pub enum HeaderMsg {
   ChangeUnit(TempUnit),
+  ChangeLanguage(Language),
 }
 
 pub struct HeaderModel;
@@ -55,6 +57,30 @@ impl SimpleComponent for HeaderModel {
                         button.popdown();
                     }
                 }
+              },
+              gtk::Separator {
+                set_orientation: gtk::Orientation::Horizontal,
+              },
+              #[name = "language_group"]
+              gtk::ToggleButton {
+                set_label: "English",
+                set_active: true,
+                connect_toggled[sender, button] => move |btn| {
+                    if btn.is_active() {
+                        sender.output(HeaderMsg::ChangeLanguage(Language::English)).unwrap();
+                        button.popdown();
+                    }
+                }
+              },
+              gtk::ToggleButton {
+                set_label: "Français",
+                set_group: Some(&language_group),
+                connect_toggled[sender, button] => move |btn| {
+                    if btn.is_active() {
+                        sender.output(HeaderMsg::ChangeLanguage(Language::French)).unwrap();
+                        button.popdown();
+                    }
+                }
               }
             }
           },
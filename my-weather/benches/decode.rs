@@ -0,0 +1,40 @@
+//! Compare the serde and streaming feed decoders on recorded feeds.
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use my_weather::{stream, types::to_forecast, ForeCast};
+
+const FEEDS: [(&str, &str); 2] = [
+  ("qc-58_e", include_str!("../fixtures/qc-58_e.xml")),
+  ("qc-58_f", include_str!("../fixtures/qc-58_f.xml")),
+];
+
+fn decode(c: &mut Criterion) {
+  let mut group = c.benchmark_group("decode");
+  for (name, feed) in FEEDS {
+    group.bench_function(format!("serde/{name}"), |b| {
+      b.iter(|| black_box(feed).parse::<ForeCast>().unwrap())
+    });
+    group.bench_function(format!("stream/{name}"), |b| {
+      b.iter(|| stream::decode(black_box(feed)).unwrap())
+    });
+  }
+  group.finish();
+}
+
+fn forecast(c: &mut Criterion) {
+  let mut group = c.benchmark_group("forecast");
+  for (name, feed) in FEEDS {
+    group.bench_function(format!("serde/{name}"), |b| {
+      b.iter(|| to_forecast(black_box(feed).parse::<ForeCast>().unwrap().entries()))
+    });
+    group.bench_function(format!("stream/{name}"), |b| {
+      b.iter(|| {
+        let feed = stream::decode(black_box(feed)).unwrap();
+        to_forecast(feed.entries.into_iter().map(|entry| entry.into_owned()))
+      })
+    });
+  }
+  group.finish();
+}
+
+criterion_group!(benches, decode, forecast);
+criterion_main!(benches);
@@ -0,0 +1,356 @@
+//! Forecast accuracy measured against the current conditions kept in an
+//! [`Archive`](crate::archive::Archive).
+//!
+//! Every forecast temperature of the city feeds in the archive is matched
+//! with the temperatures observed during its period, between 06:00 and 21:00
+//! for a day and from 18:00 until noon the next day for a night. Highs and
+//! rising temperatures are compared with the highest observed, lows and
+//! falling temperatures with the lowest, and steady temperatures with the
+//! mean. Observations are only as frequent as the refreshes that recorded
+//! them, so periods without any observation are left out, as are the feeds
+//! of other providers.
+use crate::{
+  archive::ArchivedFeed,
+  dates::{forecast_date, parse_time},
+  types::{Celsius, DayNight, ForecastEntry, Temperature},
+  Location,
+};
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Probability of precipitation from which precipitation counts as forecast.
+const POP_THRESHOLD: u8 = 50;
+
+/// Words in a condition or description that mean precipitation.
+const PRECIPITATION: [&str; 16] = [
+  "rain",
+  "snow",
+  "drizzle",
+  "flurries",
+  "showers",
+  "ice pellets",
+  "hail",
+  "thunderstorm",
+  "pluie",
+  "neige",
+  "bruine",
+  "averses",
+  "grésil",
+  "grêle",
+  "orage",
+  "précipitations",
+];
+
+fn mentions_precipitation(text: &str) -> bool {
+  let text = text.to_lowercase();
+  PRECIPITATION.iter().any(|word| text.contains(word))
+}
+
+/// Error of forecast temperatures against observed ones.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ErrorStats {
+  pub count: usize,
+  /// Mean of the absolute errors in degrees.
+  pub mean_absolute_error: f32,
+  /// Mean of forecast minus observed, positive when forecasts run warm.
+  pub bias: f32,
+}
+
+impl ErrorStats {
+  fn from_errors(errors: &[f32]) -> Self {
+    if errors.is_empty() {
+      return Self::default();
+    }
+    let count = errors.len();
+    Self {
+      count,
+      mean_absolute_error: errors.iter().map(|e| e.abs()).sum::<f32>() / count as f32,
+      bias: errors.iter().sum::<f32>() / count as f32,
+    }
+  }
+}
+
+/// Forecast against observed precipitation per period.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PrecipitationStats {
+  pub hits: usize,
+  pub misses: usize,
+  pub false_alarms: usize,
+  pub correct_negatives: usize,
+}
+
+impl PrecipitationStats {
+  /// Share of periods with precipitation that had it forecast.
+  pub fn hit_rate(&self) -> Option<f32> {
+    let observed = self.hits + self.misses;
+    (observed > 0).then(|| self.hits as f32 / observed as f32)
+  }
+
+  /// Share of periods with precipitation forecast that didn't get any.
+  pub fn false_alarm_ratio(&self) -> Option<f32> {
+    let forecast = self.hits + self.false_alarms;
+    (forecast > 0).then(|| self.false_alarms as f32 / forecast as f32)
+  }
+
+  fn add(&mut self, forecast: bool, observed: bool) {
+    match (forecast, observed) {
+      (true, true) => self.hits += 1,
+      (false, true) => self.misses += 1,
+      (true, false) => self.false_alarms += 1,
+      (false, false) => self.correct_negatives += 1,
+    }
+  }
+}
+
+/// Accuracy of the forecasts for one location.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AccuracyReport {
+  /// Temperature error by lead time in days from the issue date.
+  pub by_lead: BTreeMap<i64, ErrorStats>,
+  pub overall: ErrorStats,
+  pub precipitation: PrecipitationStats,
+}
+
+/// A forecast period resolved to a date.
+struct PeriodForecast {
+  date: NaiveDate,
+  day: DayNight,
+  lead: i64,
+  temperature: Temperature<Celsius>,
+  precipitation: bool,
+}
+
+struct Observation {
+  time: NaiveDateTime,
+  celsius: Option<f32>,
+  precipitation: bool,
+}
+
+/// Observation window of a period in local time.
+fn window(date: NaiveDate, day: DayNight) -> (NaiveDateTime, NaiveDateTime) {
+  let at = |date: NaiveDate, hour| date.and_time(NaiveTime::from_hms_opt(hour, 0, 0).unwrap());
+  match day {
+    DayNight::Day => (at(date, 6), at(date, 21)),
+    DayNight::Night => (at(date, 18), at(date + Duration::days(1), 12)),
+  }
+}
+
+/// Accuracy of the forecasts for `location` in `feeds`, with dates in the
+/// location's time zone `tz`.
+pub fn accuracy<Tz: TimeZone>(
+  feeds: impl IntoIterator<Item = ArchivedFeed>,
+  location: &Location,
+  tz: &Tz,
+) -> AccuracyReport {
+  let mut forecasts = vec![];
+  let mut observations = HashMap::new();
+  let mut seen = HashSet::new();
+
+  for feed in feeds {
+    if feed.location.city != location.city || feed.provider.is_some() {
+      continue;
+    }
+    let issued = feed
+      .issued
+      .map(|issued| issued.with_timezone(tz).date_naive());
+
+    for entry in crate::types::to_forecast(feed.entries.into_iter()) {
+      match entry {
+        ForecastEntry::Current(current) => {
+          let Some(time) = current.entry.updated.as_deref().and_then(parse_time) else {
+            continue;
+          };
+          observations.entry(time).or_insert_with(|| Observation {
+            time: time.with_timezone(tz).naive_local(),
+            celsius: current.current.celsius.map(|t| t.value()),
+            precipitation: current
+              .current
+              .description
+              .as_deref()
+              .map(mentions_precipitation)
+              .unwrap_or(false),
+          });
+        }
+        ForecastEntry::Future { day, night, .. } => {
+          let Some(issued) = issued else { continue };
+          for forecast in [day, night].into_iter().flatten() {
+            let forecast = forecast.forecast;
+            // A forecast fetched on several refreshes is only counted once.
+            if !seen.insert((feed.issued, forecast.day_of_week, forecast.day)) {
+              continue;
+            }
+            let date = forecast_date(issued, &forecast);
+            forecasts.push(PeriodForecast {
+              date,
+              day: forecast.day,
+              lead: (date - issued).num_days(),
+              temperature: forecast.celsius,
+              precipitation: forecast
+                .pop
+                .map(|pop| pop >= POP_THRESHOLD)
+                .unwrap_or_else(|| mentions_precipitation(&forecast.description)),
+            });
+          }
+        }
+        ForecastEntry::Warning(_) => (),
+      }
+    }
+  }
+
+  let mut errors: BTreeMap<i64, Vec<f32>> = BTreeMap::new();
+  let mut report = AccuracyReport::default();
+
+  for forecast in forecasts {
+    let (start, end) = window(forecast.date, forecast.day);
+    let observed = observations
+      .values()
+      .filter(|observation| observation.time >= start && observation.time < end)
+      .collect::<Vec<_>>();
+    if observed.is_empty() {
+      continue;
+    }
+
+    report.precipitation.add(
+      forecast.precipitation,
+      observed.iter().any(|observation| observation.precipitation),
+    );
+
+    let temperatures = observed
+      .iter()
+      .filter_map(|observation| observation.celsius);
+    let observed = match forecast.temperature {
+      Temperature::High(..) | Temperature::Rising(..) => temperatures.reduce(f32::max),
+      Temperature::Low(..) | Temperature::Falling(..) => temperatures.reduce(f32::min),
+      Temperature::Steady(..) => {
+        let temperatures = temperatures.collect::<Vec<_>>();
+        (!temperatures.is_empty())
+          .then(|| temperatures.iter().sum::<f32>() / temperatures.len() as f32)
+      }
+      Temperature::Current(..) => None,
+    };
+    if let Some(observed) = observed {
+      errors
+        .entry(forecast.lead)
+        .or_default()
+        .push(forecast.temperature.value() - observed);
+    }
+  }
+
+  report.overall = ErrorStats::from_errors(&errors.values().flatten().copied().collect::<Vec<_>>());
+  report.by_lead = errors
+    .into_iter()
+    .map(|(lead, errors)| (lead, ErrorStats::from_errors(&errors)))
+    .collect();
+  report
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::{Entry, ForeCast, Language};
+  use chrono::{DateTime, Utc};
+
+  fn feed(input: &str, fetched: DateTime<Utc>) -> ArchivedFeed {
+    let forecast = input.parse::<ForeCast>().unwrap();
+    ArchivedFeed {
+      location: Location::new("qc-58", Language::English),
+      fetched,
+      issued: forecast.updated().and_then(parse_time),
+      provider: None,
+      entries: forecast.entries().collect(),
+    }
+  }
+
+  /// A feed with only current conditions observed at `time`.
+  fn observation(time: DateTime<Utc>, condition: &str, celsius: f32) -> ArchivedFeed {
+    let mut feed = feed(include_str!("../fixtures/qc-58_e.xml"), time);
+    feed.issued = None;
+    feed.entries = vec![Entry {
+      title: format!("Current Conditions: {condition}, {celsius}°C"),
+      updated: Some(time.to_rfc3339()),
+      summary: String::new(),
+      ..feed.entries[0].clone()
+    }];
+    feed
+  }
+
+  #[test]
+  fn test_accuracy() {
+    let issued = Utc.with_ymd_and_hms(2023, 1, 7, 21, 0, 0).unwrap();
+    let mut feeds = vec![
+      feed(include_str!("../fixtures/qc-58_e.xml"), issued),
+      // The same forecast fetched again is not counted twice.
+      feed(include_str!("../fixtures/qc-58_e.xml"), issued),
+    ];
+    // Saturday night forecast low -5: observed -7 overnight with snow.
+    feeds.push(observation(
+      Utc.with_ymd_and_hms(2023, 1, 8, 3, 0, 0).unwrap(),
+      "Light Snow",
+      -7.,
+    ));
+    // Sunday forecast high -2, cloudy: observed -1 then -4, dry.
+    feeds.push(observation(
+      Utc.with_ymd_and_hms(2023, 1, 8, 18, 0, 0).unwrap(),
+      "Cloudy",
+      -1.,
+    ));
+    feeds.push(observation(
+      Utc.with_ymd_and_hms(2023, 1, 8, 20, 0, 0).unwrap(),
+      "Cloudy",
+      -4.,
+    ));
+    // Monday forecast high -3 with POP 60%: observed -3, dry.
+    feeds.push(observation(
+      Utc.with_ymd_and_hms(2023, 1, 9, 17, 0, 0).unwrap(),
+      "Mostly Cloudy",
+      -3.,
+    ));
+    // Monday night snow with temperature rising to zero by morning: observed
+    // plus 1 with snow.
+    feeds.push(observation(
+      Utc.with_ymd_and_hms(2023, 1, 10, 5, 0, 0).unwrap(),
+      "Light Snow",
+      1.,
+    ));
+    // Another provider's forecast for the same city is not scored.
+    let mut other = feed(
+      &include_str!("../fixtures/qc-58_e.xml").replace("Low minus 5.", "Low minus 30."),
+      issued,
+    );
+    other.provider = Some("open-meteo".into());
+    feeds.push(other);
+
+    let report = accuracy(feeds, &Location::new("qc-58", Language::English), &Utc);
+
+    assert_eq!(
+      report.by_lead.get(&0),
+      Some(&ErrorStats {
+        count: 1,
+        mean_absolute_error: 2.,
+        bias: 2.,
+      })
+    );
+    assert_eq!(report.by_lead.get(&1).map(|stats| stats.count), Some(2));
+    assert_eq!(
+      report.by_lead.get(&2),
+      Some(&ErrorStats {
+        count: 2,
+        mean_absolute_error: 0.5,
+        bias: -0.5,
+      })
+    );
+    assert_eq!(report.overall.count, 5);
+    assert_eq!(
+      report.precipitation,
+      PrecipitationStats {
+        hits: 2,
+        misses: 0,
+        false_alarms: 1,
+        correct_negatives: 2,
+      }
+    );
+    assert_eq!(report.precipitation.hit_rate(), Some(1.));
+    assert_eq!(report.precipitation.false_alarm_ratio(), Some(1. / 3.));
+  }
+}
@@ -0,0 +1,461 @@
+//! Air Quality Health Index observations and forecasts, published by
+//! Environment Canada as XML documents per region, e.g.
+//! `https://dd.weather.gc.ca/air_quality/aqhi/ont/observation/realtime/xml/AQ_OBS_FEVNT_CURRENT.xml`.
+//!
+//! AQHI regions don't match the city feeds, so an [`AirQuality`] is joined to
+//! the [`Location`] it was fetched for rather than derived from it.
+use crate::{
+  citypage::{strip_declaration, time_stamp},
+  l10n::Localize,
+  ApiError, Language, Location,
+};
+use serde::{Deserialize, Serialize};
+
+/// Datamart zones AQHI regions are grouped in.
+const ZONES: [&str; 5] = ["atl", "ont", "pnr", "pyr", "que"];
+
+/// Health risk category of an AQHI value.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Risk {
+  /// 1 to 3.
+  Low,
+  /// 4 to 6.
+  Moderate,
+  /// 7 to 10.
+  High,
+  /// Above 10.
+  VeryHigh,
+}
+
+impl Risk {
+  pub fn from_index(index: f32) -> Self {
+    match index.round() {
+      index if index <= 3. => Self::Low,
+      index if index <= 6. => Self::Moderate,
+      index if index <= 10. => Self::High,
+      _ => Self::VeryHigh,
+    }
+  }
+}
+
+impl std::fmt::Display for Risk {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.localize(Language::English))
+  }
+}
+
+impl Localize for Risk {
+  fn localize(&self, language: Language) -> String {
+    match (language, self) {
+      (Language::English, Self::Low) => "Low risk",
+      (Language::English, Self::Moderate) => "Moderate risk",
+      (Language::English, Self::High) => "High risk",
+      (Language::English, Self::VeryHigh) => "Very high risk",
+      (Language::French, Self::Low) => "Risque faible",
+      (Language::French, Self::Moderate) => "Risque modéré",
+      (Language::French, Self::High) => "Risque élevé",
+      (Language::French, Self::VeryHigh) => "Risque très élevé",
+    }
+    .into()
+  }
+}
+
+/// An AQHI region, its Datamart zone and code, e.g. `ont/FEVNT` for Ottawa.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Region {
+  pub zone: String,
+  pub code: String,
+}
+
+impl Region {
+  /// Path of the latest observation below the AQHI directory.
+  pub(crate) fn observation_path(&self) -> String {
+    format!(
+      "{}/observation/realtime/xml/AQ_OBS_{}_CURRENT.xml",
+      self.zone, self.code
+    )
+  }
+
+  /// Path of the latest forecast below the AQHI directory.
+  pub(crate) fn forecast_path(&self) -> String {
+    format!(
+      "{}/forecast/realtime/xml/AQ_FCST_{}_CURRENT.xml",
+      self.zone, self.code
+    )
+  }
+}
+
+impl std::str::FromStr for Region {
+  type Err = ApiError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let invalid = || ApiError::Region(s.into());
+    let (zone, code) = s.trim().split_once('/').ok_or_else(invalid)?;
+    let zone = zone.to_lowercase();
+    if !ZONES.contains(&zone.as_str())
+      || code.is_empty()
+      || !code.bytes().all(|b| b.is_ascii_alphanumeric())
+    {
+      return Err(invalid());
+    }
+    Ok(Self {
+      zone,
+      code: code.to_uppercase(),
+    })
+  }
+}
+
+impl std::fmt::Display for Region {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}/{}", self.zone, self.code)
+  }
+}
+
+/// The latest AQHI measured in a region.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Observed {
+  /// RFC 3339 UTC time of the observation.
+  pub time: Option<String>,
+  pub index: f32,
+  pub risk: Risk,
+}
+
+/// The AQHI forecast for a period, e.g. "Tonight".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AirQualityPeriod {
+  /// Period name in the location's language.
+  pub name: String,
+  pub index: f32,
+  pub risk: Risk,
+}
+
+/// Observed and forecast AQHI of a region, for a location.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AirQuality {
+  pub location: Location,
+  pub region: Region,
+  /// Region name in the location's language, e.g. "Ottawa".
+  pub region_name: Option<String>,
+  pub observed: Option<Observed>,
+  /// RFC 3339 UTC time the forecast was issued.
+  pub issued: Option<String>,
+  pub forecast: Vec<AirQualityPeriod>,
+}
+
+impl AirQuality {
+  /// Decode the observation and forecast documents of a region, either of
+  /// which may be missing. A forecast document without any period for the
+  /// location's language is an error.
+  pub fn decode(
+    location: Location,
+    region: Region,
+    observation: Option<&str>,
+    forecast: Option<&str>,
+  ) -> Result<Self, ApiError> {
+    let language = location.language;
+    let observation = observation
+      .map(|input| serde_xml_rs::from_str::<RawObservation>(strip_declaration(input)))
+      .transpose()?;
+    let forecast = forecast
+      .map(|input| serde_xml_rs::from_str::<RawForecastGroup>(strip_declaration(input)))
+      .transpose()?;
+
+    let region_name = observation
+      .as_ref()
+      .map(|raw| &raw.region)
+      .or(forecast.as_ref().map(|raw| &raw.region))
+      .and_then(|raw| raw.name(language));
+    let observed = observation.and_then(|raw| {
+      let index = index(&raw.air_quality_health_index)?;
+      Some(Observed {
+        time: raw.date_stamp.as_ref().and_then(RawDateStamp::time),
+        index,
+        risk: Risk::from_index(index),
+      })
+    });
+    let (issued, forecast) = match forecast {
+      Some(raw) => {
+        let periods = raw
+          .forecasts
+          .forecast
+          .iter()
+          .filter_map(|forecast| forecast.period(language))
+          .collect::<Vec<_>>();
+        if periods.is_empty() {
+          return Err(ApiError::NoForecast(region.to_string()));
+        }
+        (
+          raw.date_stamp.as_ref().and_then(RawDateStamp::time),
+          periods,
+        )
+      }
+      None => (None, vec![]),
+    };
+
+    Ok(Self {
+      location,
+      region,
+      region_name,
+      observed,
+      issued,
+      forecast,
+    })
+  }
+
+  /// Risk of the observed AQHI.
+  pub fn risk(&self) -> Option<Risk> {
+    self.observed.as_ref().map(|observed| observed.risk)
+  }
+
+  /// Highest risk of the observation and forecast.
+  pub fn highest_risk(&self) -> Option<Risk> {
+    self
+      .forecast
+      .iter()
+      .map(|period| period.risk)
+      .chain(self.risk())
+      .max()
+  }
+}
+
+impl std::fmt::Display for AirQuality {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let language = self.location.language;
+    let name = self.region_name.as_deref().unwrap_or(&self.region.code);
+    match &self.observed {
+      Some(observed) => writeln!(
+        f,
+        "AQHI {name}: {} ({})",
+        observed.index,
+        observed.risk.localize(language)
+      )?,
+      None => writeln!(f, "AQHI {name}")?,
+    }
+    for period in &self.forecast {
+      writeln!(
+        f,
+        "{}: {} ({})",
+        period.name,
+        period.index,
+        period.risk.localize(language)
+      )?;
+    }
+    Ok(())
+  }
+}
+
+fn index(value: &Option<String>) -> Option<f32> {
+  value.as_deref()?.trim().parse().ok()
+}
+
+#[derive(Debug, Deserialize)]
+struct RawRegion {
+  #[serde(rename = "nameEn")]
+  name_en: Option<String>,
+  #[serde(rename = "nameFr")]
+  name_fr: Option<String>,
+}
+
+impl RawRegion {
+  fn name(&self, language: Language) -> Option<String> {
+    match language {
+      Language::English => self.name_en.clone(),
+      Language::French => self.name_fr.clone(),
+    }
+  }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawDateStamp {
+  #[serde(rename = "UTCStamp")]
+  utc_stamp: Option<String>,
+}
+
+impl RawDateStamp {
+  fn time(&self) -> Option<String> {
+    time_stamp(self.utc_stamp.as_deref()?)
+  }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawObservation {
+  region: RawRegion,
+  date_stamp: Option<RawDateStamp>,
+  air_quality_health_index: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawForecastGroup {
+  region: RawRegion,
+  date_stamp: Option<RawDateStamp>,
+  #[serde(default)]
+  forecasts: RawForecasts,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RawForecasts {
+  #[serde(default)]
+  forecast: Vec<RawForecast>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawForecast {
+  #[serde(rename = "period", default)]
+  periods: Vec<RawPeriod>,
+  air_quality_health_index: Option<String>,
+}
+
+impl RawForecast {
+  fn period(&self, language: Language) -> Option<AirQualityPeriod> {
+    let lang = match language {
+      Language::English => "EN",
+      Language::French => "FR",
+    };
+    let period = self.periods.iter().find(|period| period.lang == lang)?;
+    let index = index(&self.air_quality_health_index)?;
+    Some(AirQualityPeriod {
+      name: period
+        .forecast_name
+        .clone()
+        .or_else(|| period.day.clone())?,
+      index,
+      risk: Risk::from_index(index),
+    })
+  }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawPeriod {
+  lang: String,
+  #[serde(rename = "forecastName")]
+  forecast_name: Option<String>,
+  #[serde(rename = "$value")]
+  day: Option<String>,
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn ottawa() -> Region {
+    "ont/FEVNT".parse().unwrap()
+  }
+
+  fn decode(language: Language) -> AirQuality {
+    AirQuality::decode(
+      Location::new("on-118", language),
+      ottawa(),
+      Some(include_str!("../fixtures/aqhi_obs_fevnt.xml")),
+      Some(include_str!("../fixtures/aqhi_fcst_fevnt.xml")),
+    )
+    .unwrap()
+  }
+
+  #[test]
+  fn test_risk() {
+    assert_eq!(Risk::from_index(1.), Risk::Low);
+    assert_eq!(Risk::from_index(3.4), Risk::Low);
+    assert_eq!(Risk::from_index(3.5), Risk::Moderate);
+    assert_eq!(Risk::from_index(7.), Risk::High);
+    assert_eq!(Risk::from_index(10.), Risk::High);
+    assert_eq!(Risk::from_index(11.), Risk::VeryHigh);
+    assert!(Risk::High > Risk::Moderate);
+  }
+
+  #[test]
+  fn test_region() {
+    assert_eq!(
+      " ONT/fevnt".parse::<Region>().unwrap(),
+      Region {
+        zone: "ont".into(),
+        code: "FEVNT".into(),
+      }
+    );
+    assert_eq!(
+      ottawa().observation_path(),
+      "ont/observation/realtime/xml/AQ_OBS_FEVNT_CURRENT.xml"
+    );
+    for invalid in ["FEVNT", "mars/FEVNT", "ont/", "ont/../x"] {
+      assert!(invalid.parse::<Region>().is_err(), "{invalid}");
+    }
+  }
+
+  #[test]
+  fn test_decode() {
+    let air = decode(Language::English);
+    assert_eq!(air.region_name.as_deref(), Some("Ottawa"));
+    assert_eq!(
+      air.observed,
+      Some(Observed {
+        time: Some("2023-01-07T20:00:00Z".into()),
+        index: 2.9,
+        risk: Risk::Low,
+      })
+    );
+    assert_eq!(air.issued.as_deref(), Some("2023-01-07T15:00:00Z"));
+    assert_eq!(
+      air
+        .forecast
+        .iter()
+        .map(|period| (period.name.as_str(), period.risk))
+        .collect::<Vec<_>>(),
+      vec![
+        ("Tonight", Risk::Low),
+        ("Tomorrow", Risk::Moderate),
+        ("Tomorrow Night", Risk::High),
+      ]
+    );
+    assert_eq!(air.risk(), Some(Risk::Low));
+    assert_eq!(air.highest_risk(), Some(Risk::High));
+    assert_eq!(
+      air.to_string(),
+      "AQHI Ottawa: 2.9 (Low risk)\nTonight: 3 (Low risk)\nTomorrow: 4 (Moderate risk)\nTomorrow Night: 7 (High risk)\n"
+    );
+
+    let french = decode(Language::French);
+    assert_eq!(french.forecast[0].name, "Ce soir et cette nuit");
+    assert!(french
+      .to_string()
+      .ends_with("Demain soir et nuit: 7 (Risque élevé)\n"));
+  }
+
+  #[test]
+  fn test_forecast_only() {
+    let air = AirQuality::decode(
+      Location::new("on-118", Language::English),
+      ottawa(),
+      None,
+      Some(include_str!("../fixtures/aqhi_fcst_fevnt.xml")),
+    )
+    .unwrap();
+    assert_eq!(air.region_name.as_deref(), Some("Ottawa"));
+    assert_eq!(air.observed, None);
+    assert_eq!(air.highest_risk(), Some(Risk::High));
+  }
+
+  #[test]
+  fn test_no_periods() {
+    let forecast = include_str!("../fixtures/aqhi_fcst_fevnt.xml");
+    let start = forecast.find("<forecasts>").unwrap();
+    let end = forecast.find("</forecasts>").unwrap() + "</forecasts>".len();
+    for document in [
+      format!("{}{}", &forecast[..start], &forecast[end..]),
+      forecast.replace("lang=\"FR\"", "lang=\"DE\""),
+    ] {
+      let result = AirQuality::decode(
+        Location::new("on-118", Language::French),
+        ottawa(),
+        None,
+        Some(&document),
+      );
+      assert!(
+        matches!(&result, Err(ApiError::NoForecast(region)) if region == "ont/FEVNT"),
+        "{result:?}"
+      );
+    }
+  }
+}
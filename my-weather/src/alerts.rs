@@ -0,0 +1,556 @@
+//! Threshold alert rules evaluated against each new forecast.
+//!
+//! Rules are read from TOML:
+//!
+//! ```toml
+//! [[rule]]
+//! name = "Extreme cold"
+//! when = "low_below"
+//! celsius = -25
+//! within_days = 3
+//!
+//! [[rule]]
+//! name = "Wet commute"
+//! when = "pop_at_least"
+//! percent = 70
+//! days = "weekdays"
+//!
+//! [[rule]]
+//! name = "Snowfall"
+//! when = "warning"
+//! kind = "Snowfall"
+//!
+//! [[rule]]
+//! name = "Poor air"
+//! when = "air_quality"
+//! risk = "high"
+//! ```
+//!
+//! An [`AlertEngine`] fires a rule once per matching period or warning, and
+//! fires it again only if the match goes away and comes back.
+use crate::{
+  air_quality::{AirQuality, Risk},
+  diff::Period,
+  types::{
+    DayNight, DayOfWeek, Forecast, ForecastEntry, ForecastWithEntry, WarningKind, WarningLevel,
+    WarningStatus, WeatherWarning,
+  },
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, path::Path};
+use thiserror::Error;
+
+/// Environment variable holding the path of the rules file used by the
+/// front ends.
+pub const RULES_ENV: &str = "MY_WEATHER_RULES";
+
+/// Rules loading errors.
+#[derive(Error, Debug)]
+pub enum RulesError {
+  #[error("Alert rules io error {0}")]
+  Io(#[from] std::io::Error),
+  #[error("Invalid alert rules {0}")]
+  Toml(#[from] toml::de::Error),
+}
+
+/// Named group of days a rule applies to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DaySet {
+  Weekdays,
+  Weekend,
+}
+
+/// Days of the week a rule applies to, either `"weekdays"`, `"weekend"` or a
+/// list such as `["Saturday", "Sunday"]`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Days {
+  Set(DaySet),
+  List(Vec<DayOfWeek>),
+}
+
+impl Days {
+  fn contains(&self, day: DayOfWeek) -> bool {
+    let weekend = matches!(day, DayOfWeek::Saturday | DayOfWeek::Sunday);
+    match self {
+      Self::Set(DaySet::Weekdays) => !weekend,
+      Self::Set(DaySet::Weekend) => weekend,
+      Self::List(days) => days.contains(&day),
+    }
+  }
+}
+
+/// What a rule matches.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "when", rename_all = "snake_case")]
+pub enum Condition {
+  /// Forecast low below `celsius`: the temperature of a night, whether a
+  /// low or a trend.
+  LowBelow {
+    celsius: f32,
+    #[serde(flatten)]
+    periods: Periods,
+  },
+  /// Forecast high above `celsius`: the temperature of a day, whether a
+  /// high or a trend.
+  HighAbove {
+    celsius: f32,
+    #[serde(flatten)]
+    periods: Periods,
+  },
+  /// Probability of precipitation of at least `percent`.
+  PopAtLeast {
+    percent: u8,
+    #[serde(flatten)]
+    periods: Periods,
+  },
+  /// A warning in effect, of any kind or level unless given.
+  Warning {
+    kind: Option<WarningKind>,
+    level: Option<WarningLevel>,
+  },
+  /// Air Quality Health Index of at least `risk`, in the current conditions
+  /// or an [`AirQuality`] observation or forecast.
+  AirQuality { risk: Risk },
+}
+
+/// Forecast periods a condition looks at.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Periods {
+  /// Only the first forecast days, counting tonight as the first one when
+  /// the forecast starts with it.
+  pub within_days: Option<usize>,
+  pub days: Option<Days>,
+}
+
+impl Periods {
+  fn contains(&self, index: usize, forecast: &Forecast) -> bool {
+    self.within_days.map(|days| index < days).unwrap_or(true)
+      && self
+        .days
+        .as_ref()
+        .map(|days| days.contains(forecast.day_of_week))
+        .unwrap_or(true)
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+  pub name: String,
+  #[serde(flatten)]
+  pub condition: Condition,
+}
+
+/// Rules file, a list of `[[rule]]` tables.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Rules {
+  #[serde(rename = "rule", default)]
+  pub rules: Vec<Rule>,
+}
+
+impl Rules {
+  pub fn load(path: impl AsRef<Path>) -> Result<Self, RulesError> {
+    std::fs::read_to_string(path)?.parse()
+  }
+
+  /// Rules from the file named by [`RULES_ENV`], or no rules when it isn't
+  /// set.
+  pub fn from_env() -> Result<Self, RulesError> {
+    match std::env::var_os(RULES_ENV) {
+      Some(path) => Self::load(path),
+      None => Ok(Self::default()),
+    }
+  }
+}
+
+impl std::str::FromStr for Rules {
+  type Err = RulesError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Ok(toml::from_str(s)?)
+  }
+}
+
+/// What made a rule fire.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "content")]
+pub enum Trigger {
+  Period {
+    period: Period,
+    forecast: Forecast,
+  },
+  Warning {
+    title: String,
+    warning: WeatherWarning,
+  },
+  AirQuality {
+    /// "Current" for what was observed, otherwise the forecast period name.
+    period: String,
+    index: f32,
+    risk: Risk,
+  },
+}
+
+/// A rule firing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertEvent {
+  pub rule: String,
+  pub trigger: Trigger,
+}
+
+impl std::fmt::Display for AlertEvent {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match &self.trigger {
+      Trigger::Period { period, forecast } => {
+        write!(f, "{}: {period} {}", self.rule, forecast.celsius)?;
+        if let Some(pop) = forecast.pop {
+          write!(f, ", POP {pop}%")?;
+        }
+        Ok(())
+      }
+      Trigger::Warning { title, .. } => write!(f, "{}: {title}", self.rule),
+      Trigger::AirQuality {
+        period,
+        index,
+        risk,
+      } => write!(f, "{}: {period} AQHI {index} ({risk})", self.rule),
+    }
+  }
+}
+
+/// Identifies a match so that it only fires once.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum MatchKey {
+  Period(Period),
+  Warning(String),
+  AirQuality(String),
+}
+
+impl Condition {
+  fn matches_forecast(&self, index: usize, forecast: &Forecast) -> bool {
+    match self {
+      Self::LowBelow { celsius, periods } => {
+        forecast.day == DayNight::Night
+          && forecast.celsius.value() < *celsius
+          && periods.contains(index, forecast)
+      }
+      Self::HighAbove { celsius, periods } => {
+        forecast.day == DayNight::Day
+          && forecast.celsius.value() > *celsius
+          && periods.contains(index, forecast)
+      }
+      Self::PopAtLeast { percent, periods } => {
+        forecast.pop.map(|pop| pop >= *percent).unwrap_or(false)
+          && periods.contains(index, forecast)
+      }
+      Self::Warning { .. } | Self::AirQuality { .. } => false,
+    }
+  }
+
+  fn matches_air_quality(&self, index: f32) -> bool {
+    match self {
+      Self::AirQuality { risk } => Risk::from_index(index) >= *risk,
+      _ => false,
+    }
+  }
+
+  fn matches_warning(&self, warning: &WeatherWarning) -> bool {
+    match self {
+      Self::Warning { kind, level } => {
+        warning.status == WarningStatus::InEffect
+          && kind.as_ref().map(|k| *k == warning.kind).unwrap_or(true)
+          && level.map(|l| l == warning.level).unwrap_or(true)
+      }
+      _ => false,
+    }
+  }
+}
+
+impl Rule {
+  fn matches(
+    &self,
+    entries: &[ForecastEntry],
+    air_quality: Option<&AirQuality>,
+  ) -> Vec<(MatchKey, Trigger)> {
+    let mut result = vec![];
+    let mut day_index = 0;
+    for entry in entries {
+      match entry {
+        ForecastEntry::Future { day, night, .. } => {
+          for ForecastWithEntry { forecast, .. } in [day, night].into_iter().flatten() {
+            if self.condition.matches_forecast(day_index, forecast) {
+              let period = Period {
+                day_of_week: forecast.day_of_week,
+                day: forecast.day,
+              };
+              result.push((
+                MatchKey::Period(period),
+                Trigger::Period {
+                  period,
+                  forecast: forecast.clone(),
+                },
+              ));
+            }
+          }
+          day_index += 1;
+        }
+        ForecastEntry::Warning(entry) => {
+          let Ok(warning) = entry.title.parse::<WeatherWarning>() else {
+            continue;
+          };
+          if self.condition.matches_warning(&warning) {
+            result.push((
+              MatchKey::Warning(entry.title.clone()),
+              Trigger::Warning {
+                title: entry.title.clone(),
+                warning,
+              },
+            ));
+          }
+        }
+        ForecastEntry::Current(_) => (),
+      }
+    }
+
+    let mut air_quality_match = |period: &str, index: f32| {
+      if self.condition.matches_air_quality(index) {
+        result.push((
+          MatchKey::AirQuality(period.into()),
+          Trigger::AirQuality {
+            period: period.into(),
+            index,
+            risk: Risk::from_index(index),
+          },
+        ));
+      }
+    };
+    let current = entries.iter().find_map(|entry| match entry {
+      ForecastEntry::Current(current) => current.current.observation.as_ref()?.aqhi,
+      _ => None,
+    });
+    // The AQHI feed's own observation takes precedence over the one in the
+    // current conditions.
+    let observed = air_quality
+      .and_then(|air_quality| air_quality.observed.as_ref())
+      .map(|observed| observed.index);
+    if let Some(index) = observed.or(current) {
+      air_quality_match("Current", index);
+    }
+    for period in air_quality
+      .iter()
+      .flat_map(|air_quality| &air_quality.forecast)
+    {
+      air_quality_match(&period.name, period.index);
+    }
+
+    result
+  }
+}
+
+/// Evaluates rules against successive forecasts.
+#[derive(Debug, Clone, Default)]
+pub struct AlertEngine {
+  rules: Vec<Rule>,
+  /// Matches from the previous evaluation, by rule index.
+  fired: HashSet<(usize, MatchKey)>,
+}
+
+impl AlertEngine {
+  pub fn new(rules: Rules) -> Self {
+    Self {
+      rules: rules.rules,
+      fired: HashSet::new(),
+    }
+  }
+
+  pub fn rules(&self) -> &[Rule] {
+    &self.rules
+  }
+
+  /// Alerts for a new [`to_forecast`](crate::types::to_forecast) output that
+  /// didn't already fire for the previous one.
+  pub fn evaluate(&mut self, entries: &[ForecastEntry]) -> Vec<AlertEvent> {
+    self.evaluate_with_air_quality(entries, None)
+  }
+
+  /// Like [`evaluate`](Self::evaluate), also checking the air quality of the
+  /// forecast's location.
+  pub fn evaluate_with_air_quality(
+    &mut self,
+    entries: &[ForecastEntry],
+    air_quality: Option<&AirQuality>,
+  ) -> Vec<AlertEvent> {
+    let mut fired = HashSet::new();
+    let mut events = vec![];
+
+    for (index, rule) in self.rules.iter().enumerate() {
+      for (key, trigger) in rule.matches(entries, air_quality) {
+        let key = (index, key);
+        if !self.fired.contains(&key) && !fired.contains(&key) {
+          events.push(AlertEvent {
+            rule: rule.name.clone(),
+            trigger,
+          });
+        }
+        fired.insert(key);
+      }
+    }
+
+    self.fired = fired;
+    events
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::types::feed_forecast;
+
+  const RULES: &str = r#"
+[[rule]]
+name = "Cold night"
+when = "low_below"
+celsius = -4
+within_days = 2
+
+[[rule]]
+name = "Wet weekday"
+when = "pop_at_least"
+percent = 60
+days = "weekdays"
+
+[[rule]]
+name = "Snowfall"
+when = "warning"
+kind = "Snowfall"
+"#;
+
+  #[test]
+  fn test_parse_rules() {
+    let rules = RULES.parse::<Rules>().unwrap();
+    assert_eq!(rules.rules.len(), 3);
+    assert_eq!(
+      rules.rules[1].condition,
+      Condition::PopAtLeast {
+        percent: 60,
+        periods: Periods {
+          within_days: None,
+          days: Some(Days::Set(DaySet::Weekdays)),
+        },
+      }
+    );
+    assert_eq!(
+      rules.rules[2].condition,
+      Condition::Warning {
+        kind: Some(WarningKind::Snowfall),
+        level: None,
+      }
+    );
+    assert!("[[rule]]\nname = \"x\"\nwhen = \"sometimes\""
+      .parse::<Rules>()
+      .is_err());
+  }
+
+  #[test]
+  fn test_fires_once() {
+    let mut engine = AlertEngine::new(RULES.parse().unwrap());
+    let feed = include_str!("../fixtures/qc-58_e.xml");
+
+    let events = engine.evaluate(&feed_forecast(feed));
+    assert_eq!(
+      events
+        .iter()
+        .map(|event| event.to_string())
+        .collect::<Vec<_>>(),
+      vec![
+        "Cold night: Saturday night -5°C",
+        "Cold night: Sunday night -9°C",
+        "Wet weekday: Monday -3°C, POP 60%",
+        "Wet weekday: Tuesday -3°C, POP 70%",
+      ]
+    );
+    assert!(engine.evaluate(&feed_forecast(feed)).is_empty());
+
+    let warned = feed.replace(
+      "No watches or warnings in effect, Gatineau",
+      "SNOWFALL WARNING IN EFFECT, Gatineau",
+    );
+    let events = engine.evaluate(&feed_forecast(&warned));
+    assert_eq!(events.len(), 1);
+    assert!(matches!(
+      &events[0].trigger,
+      Trigger::Warning { warning, .. } if warning.kind == WarningKind::Snowfall
+    ));
+
+    // Firing again once the match went away and came back.
+    engine.evaluate(&feed_forecast(
+      &warned.replace("Low minus 5.", "Low minus 2."),
+    ));
+    assert_eq!(engine.evaluate(&feed_forecast(&warned)).len(), 1);
+  }
+
+  #[test]
+  fn test_trends() {
+    let rules = r#"
+[[rule]]
+name = "Extreme cold"
+when = "low_below"
+celsius = -20
+
+[[rule]]
+name = "Mild"
+when = "high_above"
+celsius = -5
+"#;
+    let mut engine = AlertEngine::new(rules.parse().unwrap());
+    let feed = include_str!("../fixtures/qc-58_e.xml").replace(
+      "Saturday night: Periods of snow. Low minus 5.",
+      "Saturday night: Periods of snow. Temperature falling to minus 25 by morning.",
+    );
+    let events = engine.evaluate(&feed_forecast(&feed));
+    assert_eq!(
+      events
+        .iter()
+        .map(|event| event.to_string())
+        .collect::<Vec<_>>(),
+      vec![
+        "Extreme cold: Saturday night -25°C",
+        "Mild: Sunday -2°C",
+        "Mild: Monday -3°C, POP 60%",
+        "Mild: Tuesday -3°C, POP 70%",
+      ]
+    );
+  }
+
+  #[test]
+  fn test_air_quality() {
+    let rules = "[[rule]]\nname = \"Poor air\"\nwhen = \"air_quality\"\nrisk = \"moderate\"";
+    let mut engine = AlertEngine::new(rules.parse().unwrap());
+    let feed = feed_forecast(include_str!("../fixtures/qc-58_e.xml"));
+    // The AQHI of 3 in the current conditions is a low risk.
+    assert!(engine.evaluate(&feed).is_empty());
+
+    let air = AirQuality::decode(
+      crate::Location::new("on-118", crate::Language::English),
+      "ont/FEVNT".parse().unwrap(),
+      Some(include_str!("../fixtures/aqhi_obs_fevnt.xml")),
+      Some(include_str!("../fixtures/aqhi_fcst_fevnt.xml")),
+    )
+    .unwrap();
+    let events = engine.evaluate_with_air_quality(&feed, Some(&air));
+    assert_eq!(
+      events
+        .iter()
+        .map(|event| event.to_string())
+        .collect::<Vec<_>>(),
+      vec![
+        "Poor air: Tomorrow AQHI 4 (Moderate risk)",
+        "Poor air: Tomorrow Night AQHI 7 (High risk)",
+      ]
+    );
+    assert!(engine
+      .evaluate_with_air_quality(&feed, Some(&air))
+      .is_empty());
+  }
+}
@@ -0,0 +1,284 @@
+//! Append-only archive of fetched feeds, stored as JSON lines.
+//!
+//! Each line is one [`ArchivedFeed`]. Lines that can't be read back, such as
+//! a line cut short by a crash while writing, are skipped by queries.
+use crate::{
+  dates::parse_time,
+  types::{to_forecast, CurrentForecastWithEntry, ForecastEntry},
+  Entry, ForeCast, Location, Term,
+};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::HashSet,
+  fs::{File, OpenOptions},
+  io::{BufRead, BufReader, ErrorKind, Read, Seek, SeekFrom, Write},
+  path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+/// Archive read or write errors.
+#[derive(Error, Debug)]
+pub enum ArchiveError {
+  #[error("Archive io error {0}")]
+  Io(#[from] std::io::Error),
+  #[error("Archive encoding error {0}")]
+  Json(#[from] serde_json::Error),
+}
+
+/// A feed as it was fetched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedFeed {
+  pub location: Location,
+  pub fetched: DateTime<Utc>,
+  /// Time the feed was issued, when the feed reported it.
+  pub issued: Option<DateTime<Utc>>,
+  /// Provider of the feed, e.g. `open-meteo`, or none for a city feed.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub provider: Option<String>,
+  pub entries: Vec<Entry>,
+}
+
+/// Current conditions observed at a location.
+#[derive(Debug, Clone)]
+pub struct ArchivedCurrent {
+  pub location: Location,
+  pub observed: DateTime<Utc>,
+  pub current: CurrentForecastWithEntry,
+}
+
+/// JSON lines file of archived feeds.
+#[derive(Debug, Clone)]
+pub struct Archive {
+  path: PathBuf,
+}
+
+impl Archive {
+  /// Archive at `path`. The file is created on the first write.
+  pub fn open(path: impl AsRef<Path>) -> Self {
+    Self {
+      path: path.as_ref().into(),
+    }
+  }
+
+  /// Append a fetched city feed. A line left unfinished by an earlier write
+  /// is ended first, so that only it is lost.
+  pub fn record(
+    &self,
+    location: &Location,
+    forecast: &ForeCast,
+    fetched: DateTime<Utc>,
+  ) -> Result<ArchivedFeed, ArchiveError> {
+    self.record_from(None, location, forecast, fetched)
+  }
+
+  /// Append a feed fetched from `provider`, or a city feed without one.
+  pub fn record_from(
+    &self,
+    provider: Option<&str>,
+    location: &Location,
+    forecast: &ForeCast,
+    fetched: DateTime<Utc>,
+  ) -> Result<ArchivedFeed, ArchiveError> {
+    let feed = ArchivedFeed {
+      location: location.clone(),
+      fetched,
+      issued: forecast.updated().and_then(parse_time),
+      provider: provider.map(Into::into),
+      entries: forecast.0.entries.clone(),
+    };
+
+    let mut line = serde_json::to_string(&feed)?;
+    line.push('\n');
+    let mut file = OpenOptions::new()
+      .create(true)
+      .read(true)
+      .append(true)
+      .open(&self.path)?;
+    if file.metadata()?.len() > 0 {
+      let mut last = [0];
+      file.seek(SeekFrom::End(-1))?;
+      file.read_exact(&mut last)?;
+      if last != *b"\n" {
+        line.insert(0, '\n');
+      }
+    }
+    file.write_all(line.as_bytes())?;
+
+    Ok(feed)
+  }
+
+  /// Every archived feed in the order it was fetched.
+  pub fn feeds(&self) -> Result<impl Iterator<Item = ArchivedFeed>, ArchiveError> {
+    let lines = match File::open(&self.path) {
+      Ok(file) => Some(BufReader::new(file).split(b'\n')),
+      Err(e) if e.kind() == ErrorKind::NotFound => None,
+      Err(e) => return Err(e.into()),
+    };
+
+    Ok(
+      lines
+        .into_iter()
+        .flatten()
+        .map_while(Result::ok)
+        .filter_map(|line| String::from_utf8(line).ok())
+        .filter_map(|line| serde_json::from_str(&line).ok()),
+    )
+  }
+
+  /// Feeds issued on a date in the given time zone.
+  pub fn issued_on<Tz: TimeZone>(
+    &self,
+    date: NaiveDate,
+    tz: &Tz,
+  ) -> Result<Vec<ArchivedFeed>, ArchiveError> {
+    Ok(
+      self
+        .feeds()?
+        .filter(|feed| {
+          feed
+            .issued
+            .map(|issued| issued.with_timezone(tz).date_naive() == date)
+            .unwrap_or(false)
+        })
+        .collect(),
+    )
+  }
+
+  /// Current conditions observed between two times, inclusive. The same
+  /// observation fetched more than once is only returned once.
+  pub fn current_between(
+    &self,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+  ) -> Result<Vec<ArchivedCurrent>, ArchiveError> {
+    let mut seen = HashSet::new();
+    let mut result = vec![];
+
+    for feed in self.feeds()? {
+      for entry in feed.entries {
+        if entry.category.term != Term::Current {
+          continue;
+        }
+        let Some(observed) = entry.updated.as_deref().and_then(parse_time) else {
+          continue;
+        };
+        if observed < from || observed > to || !seen.insert((feed.location.clone(), observed)) {
+          continue;
+        }
+        if let Some(ForecastEntry::Current(current)) = to_forecast(std::iter::once(entry)).pop() {
+          result.push(ArchivedCurrent {
+            location: feed.location.clone(),
+            observed,
+            current,
+          });
+        }
+      }
+    }
+
+    Ok(result)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::Language;
+  use chrono::Duration;
+
+  fn archive(name: &str) -> Archive {
+    let path = std::env::temp_dir().join(format!("my-weather-{name}-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    Archive::open(path)
+  }
+
+  #[test]
+  fn test_record_and_query() {
+    let archive = archive("query");
+    let location = Location::new("qc-58", Language::English);
+    let forecast = include_str!("../fixtures/qc-58_e.xml")
+      .parse::<ForeCast>()
+      .unwrap();
+    let fetched = Utc.with_ymd_and_hms(2023, 1, 7, 21, 0, 0).unwrap();
+
+    let feed = archive.record(&location, &forecast, fetched).unwrap();
+    assert_eq!(
+      feed.issued,
+      Some(Utc.with_ymd_and_hms(2023, 1, 7, 20, 45, 0).unwrap())
+    );
+    archive
+      .record(&location, &forecast, fetched + Duration::minutes(15))
+      .unwrap();
+
+    let date = NaiveDate::from_ymd_opt(2023, 1, 7).unwrap();
+    assert_eq!(archive.issued_on(date, &Utc).unwrap().len(), 2);
+    assert!(archive
+      .issued_on(date.succ_opt().unwrap(), &Utc)
+      .unwrap()
+      .is_empty());
+
+    let current = archive
+      .current_between(
+        Utc.with_ymd_and_hms(2023, 1, 7, 19, 0, 0).unwrap(),
+        Utc.with_ymd_and_hms(2023, 1, 7, 21, 0, 0).unwrap(),
+      )
+      .unwrap();
+    assert_eq!(current.len(), 1);
+    assert_eq!(
+      current[0].current.current.description.as_deref(),
+      Some("Light Snow")
+    );
+
+    let _ = std::fs::remove_file(&archive.path);
+  }
+
+  #[test]
+  fn test_missing_and_truncated() {
+    let archive = archive("truncated");
+    assert_eq!(archive.feeds().unwrap().count(), 0);
+
+    let location = Location::new("qc-58", Language::French);
+    let forecast = include_str!("../fixtures/qc-58_f.xml")
+      .parse::<ForeCast>()
+      .unwrap();
+    archive.record(&location, &forecast, Utc::now()).unwrap();
+    OpenOptions::new()
+      .append(true)
+      .open(&archive.path)
+      .unwrap()
+      .write_all(b"{\"location\":")
+      .unwrap();
+
+    assert_eq!(archive.feeds().unwrap().count(), 1);
+
+    // The next feed starts on a line of its own.
+    let fetched = Utc.with_ymd_and_hms(2023, 1, 7, 21, 0, 0).unwrap();
+    archive.record(&location, &forecast, fetched).unwrap();
+    let feeds = archive.feeds().unwrap().collect::<Vec<_>>();
+    assert_eq!(feeds.len(), 2);
+    assert_eq!(feeds[1].fetched, fetched);
+    assert_eq!(feeds[1].location, location);
+    let _ = std::fs::remove_file(&archive.path);
+  }
+
+  #[test]
+  fn test_invalid_utf8() {
+    let archive = archive("utf8");
+    let location = Location::new("qc-58", Language::French);
+    let forecast = include_str!("../fixtures/qc-58_f.xml")
+      .parse::<ForeCast>()
+      .unwrap();
+    archive.record(&location, &forecast, Utc::now()).unwrap();
+    // A line cut within "é".
+    OpenOptions::new()
+      .append(true)
+      .open(&archive.path)
+      .unwrap()
+      .write_all(b"{\"location\":\"Gatineau - M\xc3\n")
+      .unwrap();
+    archive.record(&location, &forecast, Utc::now()).unwrap();
+
+    assert_eq!(archive.feeds().unwrap().count(), 2);
+    let _ = std::fs::remove_file(&archive.path);
+  }
+}
@@ -0,0 +1,313 @@
+//! Sunrise, sunset, civil twilight and moon phase computed offline from a
+//! location's coordinates.
+//!
+//! The sun follows the sunrise equation, good to a minute or two away from
+//! the poles, with the usual 0.833° allowance for refraction and the size of
+//! the sun's disk. The moon phase is its age in a mean lunar month, which
+//! can be up to a day off the actual phase.
+use crate::{
+  dates::{forecast_date, updated_on},
+  provider::Coordinates,
+  types::{Almanac, ForecastEntry, ForecastWithEntry, MoonPhase},
+};
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use serde::Serialize;
+use std::f64::consts::PI;
+
+/// Julian day of 2000-01-01 12:00.
+const J2000: f64 = 2_451_545.;
+/// Julian day of the Unix epoch.
+const UNIX_EPOCH: f64 = 2_440_587.5;
+/// Obliquity of the ecliptic, in degrees.
+const OBLIQUITY: f64 = 23.4397;
+/// Altitude of the sun's center at sunrise and sunset, in degrees.
+const SUNRISE_ALTITUDE: f64 = -0.833;
+/// Altitude of the sun's center at the start and end of civil twilight.
+const CIVIL_TWILIGHT_ALTITUDE: f64 = -6.;
+/// Mean length of a lunar month, in days.
+const SYNODIC_MONTH: f64 = 29.530_588_853;
+/// Julian day of the new moon of 2000-01-06 18:14 UTC.
+const NEW_MOON: f64 = 2_451_550.26;
+
+/// Whether the sun stays up or down all day.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub enum Polar {
+  MidnightSun,
+  PolarNight,
+}
+
+/// Sun times of a date at a location.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Sun {
+  /// When the sun is highest.
+  pub noon: DateTime<Utc>,
+  /// Start of civil twilight, when the sun reaches 6° below the horizon.
+  pub dawn: Option<DateTime<Utc>>,
+  pub sunrise: Option<DateTime<Utc>>,
+  pub sunset: Option<DateTime<Utc>>,
+  /// End of civil twilight.
+  pub dusk: Option<DateTime<Utc>>,
+  /// Set when the sun doesn't rise or set.
+  pub polar: Option<Polar>,
+}
+
+impl Sun {
+  /// Time between sunrise and sunset, a whole day under the midnight sun.
+  pub fn day_length(&self) -> Duration {
+    match (self.sunrise, self.sunset, self.polar) {
+      (Some(sunrise), Some(sunset), _) => sunset - sunrise,
+      (_, _, Some(Polar::MidnightSun)) => Duration::days(1),
+      _ => Duration::zero(),
+    }
+  }
+}
+
+/// Phase of the moon at a time.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Moon {
+  pub phase: MoonPhase,
+  /// Days since the new moon.
+  pub age: f64,
+  /// Fraction lit, from 0 to 1.
+  pub illumination: f64,
+}
+
+fn julian_day(time: DateTime<Utc>) -> f64 {
+  time.timestamp_millis() as f64 / 86_400_000. + UNIX_EPOCH
+}
+
+fn from_julian_day(day: f64) -> DateTime<Utc> {
+  let millis = ((day - UNIX_EPOCH) * 86_400_000.).round() as i64;
+  Utc
+    .timestamp_millis_opt(millis)
+    .single()
+    .unwrap_or_default()
+}
+
+/// Sun times of the local `date` at `coordinates`.
+pub fn sun(coordinates: &Coordinates, date: NaiveDate) -> Sun {
+  let days = (date - NaiveDate::from_ymd_opt(2000, 1, 1).unwrap_or_default()).num_days() as f64;
+  // Mean solar noon, later to the west.
+  let mean_noon = days - coordinates.longitude / 360.;
+  let anomaly = (357.5291 + 0.985_600_28 * mean_noon)
+    .rem_euclid(360.)
+    .to_radians();
+  let center = 1.9148 * anomaly.sin() + 0.02 * (2. * anomaly).sin() + 0.0003 * (3. * anomaly).sin();
+  let longitude = (anomaly.to_degrees() + center + 180. + 102.9372)
+    .rem_euclid(360.)
+    .to_radians();
+  let transit = J2000 + mean_noon + 0.0053 * anomaly.sin() - 0.0069 * (2. * longitude).sin();
+  let declination = (longitude.sin() * OBLIQUITY.to_radians().sin()).asin();
+  let latitude = coordinates.latitude.to_radians();
+
+  // Hour angle of the sun at an altitude, as a fraction of a day, or the
+  // cosine of the angle when the sun never gets there.
+  let hour_angle = |altitude: f64| {
+    let cos = (altitude.to_radians().sin() - latitude.sin() * declination.sin())
+      / (latitude.cos() * declination.cos());
+    if (-1. ..=1.).contains(&cos) {
+      Ok(cos.acos().to_degrees() / 360.)
+    } else {
+      Err(cos)
+    }
+  };
+  let times = |altitude: f64| match hour_angle(altitude) {
+    Ok(angle) => (
+      Some(from_julian_day(transit - angle)),
+      Some(from_julian_day(transit + angle)),
+    ),
+    Err(_) => (None, None),
+  };
+
+  let (sunrise, sunset) = times(SUNRISE_ALTITUDE);
+  let (dawn, dusk) = times(CIVIL_TWILIGHT_ALTITUDE);
+  Sun {
+    noon: from_julian_day(transit),
+    dawn,
+    sunrise,
+    sunset,
+    dusk,
+    polar: match hour_angle(SUNRISE_ALTITUDE) {
+      Err(cos) if cos < -1. => Some(Polar::MidnightSun),
+      Err(_) => Some(Polar::PolarNight),
+      Ok(_) => None,
+    },
+  }
+}
+
+/// Phase of the moon at `time`.
+pub fn moon(time: DateTime<Utc>) -> Moon {
+  let age = (julian_day(time) - NEW_MOON).rem_euclid(SYNODIC_MONTH);
+  let fraction = age / SYNODIC_MONTH;
+  Moon {
+    phase: MoonPhase::from_fraction(fraction),
+    age,
+    illumination: (1. - (2. * PI * fraction).cos()) / 2.,
+  }
+}
+
+/// Sun and moon of the local `date` at `coordinates`, with times in the
+/// location's time zone `tz`. The moon is taken at solar noon.
+pub fn almanac<Tz: TimeZone>(coordinates: &Coordinates, date: NaiveDate, tz: &Tz) -> Almanac
+where
+  Tz::Offset: std::fmt::Display,
+{
+  let sun = sun(coordinates, date);
+  let moon = moon(sun.noon);
+  let local = |time: Option<DateTime<Utc>>| time.map(|time| time.with_timezone(tz).to_rfc3339());
+  Almanac {
+    dawn: local(sun.dawn),
+    sunrise: local(sun.sunrise),
+    sunset: local(sun.sunset),
+    dusk: local(sun.dusk),
+    day_length: sun.day_length().num_minutes() as u32,
+    moon: moon.phase,
+    illumination: moon.illumination as f32,
+  }
+}
+
+/// Set the almanac of the current conditions and of every future forecast,
+/// dated from the time its entry was updated in the location's time zone
+/// `tz`. A night gets the almanac of the evening it starts.
+pub fn annotate<Tz: TimeZone>(entries: &mut [ForecastEntry], coordinates: &Coordinates, tz: &Tz)
+where
+  Tz::Offset: std::fmt::Display,
+{
+  for entry in entries {
+    match entry {
+      ForecastEntry::Current(current) => {
+        current.current.almanac =
+          updated_on(&current.entry, tz).map(|date| almanac(coordinates, date, tz));
+      }
+      ForecastEntry::Future { day, night, .. } => {
+        for ForecastWithEntry { forecast, entry } in day.iter_mut().chain(night.iter_mut()) {
+          forecast.almanac = updated_on(entry, tz)
+            .map(|issued| almanac(coordinates, forecast_date(issued, forecast), tz));
+        }
+      }
+      ForecastEntry::Warning(_) => (),
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::types::feed_forecast;
+  use chrono::FixedOffset;
+
+  fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+  }
+
+  fn at(time: &str) -> DateTime<Utc> {
+    time.parse().unwrap()
+  }
+
+  /// Whether a time is within two minutes of the expected one.
+  fn near(time: Option<DateTime<Utc>>, expected: &str) -> bool {
+    time.is_some_and(|time| (time - at(expected)).num_seconds().abs() <= 120)
+  }
+
+  const OTTAWA: Coordinates = Coordinates {
+    latitude: 45.42,
+    longitude: -75.70,
+  };
+  const TROMSO: Coordinates = Coordinates {
+    latitude: 69.65,
+    longitude: 18.96,
+  };
+
+  #[test]
+  fn test_equinox() {
+    let sun = sun(
+      &Coordinates {
+        latitude: 0.,
+        longitude: 0.,
+      },
+      date(2023, 3, 20),
+    );
+    assert!(near(Some(sun.noon), "2023-03-20T12:07:30Z"), "{sun:?}");
+    assert!(near(sun.sunrise, "2023-03-20T06:04:00Z"), "{sun:?}");
+    assert!(near(sun.sunset, "2023-03-20T18:11:00Z"), "{sun:?}");
+    assert!(near(sun.dawn, "2023-03-20T05:43:00Z"), "{sun:?}");
+    assert_eq!(sun.polar, None);
+    assert!((sun.day_length().num_minutes() - 727).abs() <= 2);
+  }
+
+  #[test]
+  fn test_ottawa() {
+    // 05:15 and 20:54 EDT on the longest day.
+    let summer = sun(&OTTAWA, date(2023, 6, 21));
+    assert!(near(summer.sunrise, "2023-06-21T09:15:00Z"), "{summer:?}");
+    assert!(near(summer.sunset, "2023-06-22T00:54:00Z"), "{summer:?}");
+    // 07:42 and 16:35 EST.
+    let winter = sun(&OTTAWA, date(2023, 1, 7));
+    assert!(near(winter.sunrise, "2023-01-07T12:42:00Z"), "{winter:?}");
+    assert!(near(winter.sunset, "2023-01-07T21:35:00Z"), "{winter:?}");
+  }
+
+  #[test]
+  fn test_polar() {
+    // The sun stays below the horizon but civil twilight still comes.
+    let winter = sun(&TROMSO, date(2023, 12, 21));
+    assert_eq!(winter.polar, Some(Polar::PolarNight));
+    assert_eq!((winter.sunrise, winter.sunset), (None, None));
+    assert!(winter.dawn.is_some() && winter.dusk.is_some());
+    assert_eq!(winter.day_length(), Duration::zero());
+
+    let summer = sun(&TROMSO, date(2023, 6, 21));
+    assert_eq!(summer.polar, Some(Polar::MidnightSun));
+    assert_eq!(summer.day_length(), Duration::days(1));
+  }
+
+  #[test]
+  fn test_moon() {
+    let full = moon(at("2023-01-06T23:08:00Z"));
+    assert_eq!(full.phase, MoonPhase::FullMoon);
+    assert!(full.illumination > 0.99, "{full:?}");
+
+    let new = moon(at("2023-01-21T20:53:00Z"));
+    assert_eq!(new.phase, MoonPhase::NewMoon);
+    assert!(new.illumination < 0.01, "{new:?}");
+
+    let first_quarter = moon(at("2023-01-28T15:19:00Z"));
+    assert_eq!(first_quarter.phase, MoonPhase::FirstQuarter);
+    assert!((first_quarter.illumination - 0.5).abs() < 0.1);
+  }
+
+  #[test]
+  fn test_annotate() {
+    let mut entries = feed_forecast(include_str!("../fixtures/qc-58_e.xml"));
+    let eastern = FixedOffset::west_opt(5 * 3600).unwrap();
+    annotate(&mut entries, &OTTAWA, &eastern);
+
+    let current = entries
+      .iter()
+      .find_map(|entry| match entry {
+        ForecastEntry::Current(current) => current.current.almanac.clone(),
+        _ => None,
+      })
+      .unwrap();
+    assert_eq!(
+      current.to_string(),
+      "Sunrise 07:42, sunset 16:35, 8 h 53 min of daylight, full moon (100% lit)"
+    );
+    assert!(current.sunrise.unwrap().starts_with("2023-01-07T07:42"));
+
+    // Friday is January 13.
+    let friday = entries
+      .iter()
+      .find_map(|entry| match entry {
+        ForecastEntry::Future { day: Some(day), .. }
+          if day.forecast.day_of_week == crate::types::DayOfWeek::Friday =>
+        {
+          day.forecast.almanac.clone()
+        }
+        _ => None,
+      })
+      .unwrap();
+    assert!(friday.sunrise.unwrap().starts_with("2023-01-13T07:"));
+    assert_eq!(friday.moon, MoonPhase::LastQuarter);
+  }
+}
@@ -0,0 +1,272 @@
+//! Common Alerting Protocol (CAP 1.2) alerts.
+//!
+//! The warnings entry of a city feed only has a title and a link, while the
+//! CAP alert behind it has the areas, timing and instructions. Alerts carry
+//! one `<info>` block per language.
+use crate::{
+  parsers::parse_warning,
+  types::{WarningStatus, WeatherWarning},
+  ApiError, Language,
+};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MessageType {
+  Alert,
+  Update,
+  Cancel,
+  Ack,
+  Error,
+  #[serde(other)]
+  Unknown,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Urgency {
+  Immediate,
+  Expected,
+  Future,
+  Past,
+  #[serde(other)]
+  Unknown,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Severity {
+  Extreme,
+  Severe,
+  Moderate,
+  Minor,
+  #[serde(other)]
+  Unknown,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Certainty {
+  Observed,
+  Likely,
+  Possible,
+  Unlikely,
+  #[serde(other)]
+  Unknown,
+}
+
+/// Closed ring of latitude, longitude pairs, from a `<polygon>` such as
+/// `45.45,-75.93 45.60,-75.67 45.45,-75.93`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Polygon(pub Vec<(f64, f64)>);
+
+impl TryFrom<String> for Polygon {
+  type Error = String;
+
+  fn try_from(polygon: String) -> Result<Self, Self::Error> {
+    polygon
+      .split_whitespace()
+      .map(|point| {
+        point
+          .split_once(',')
+          .and_then(|(lat, lon)| Some((lat.parse().ok()?, lon.parse().ok()?)))
+          .ok_or_else(|| format!("invalid polygon point \"{point}\""))
+      })
+      .collect::<Result<_, _>>()
+      .map(Self)
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapArea {
+  #[serde(rename = "areaDesc")]
+  pub description: String,
+  /// Polygons of the area, without those that have an invalid point.
+  #[serde(rename = "polygon", default, deserialize_with = "valid_polygons")]
+  pub polygons: Vec<Polygon>,
+}
+
+/// Polygons that can be read, so that a malformed one doesn't lose the
+/// whole alert.
+fn valid_polygons<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Polygon>, D::Error> {
+  Ok(
+    Vec::<String>::deserialize(deserializer)?
+      .into_iter()
+      .filter_map(|polygon| Polygon::try_from(polygon).ok())
+      .collect(),
+  )
+}
+
+/// Alert details in one language. Times are RFC 3339.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapInfo {
+  #[serde(default = "default_language")]
+  pub language: String,
+  pub event: String,
+  pub urgency: Urgency,
+  pub severity: Severity,
+  pub certainty: Certainty,
+  pub effective: Option<String>,
+  pub onset: Option<String>,
+  pub expires: Option<String>,
+  pub sender_name: Option<String>,
+  pub headline: Option<String>,
+  pub description: Option<String>,
+  pub instruction: Option<String>,
+  pub web: Option<String>,
+  #[serde(rename = "area", default)]
+  pub areas: Vec<CapArea>,
+}
+
+/// Language of an `<info>` block without one, as given by the standard.
+fn default_language() -> String {
+  "en-US".into()
+}
+
+impl CapInfo {
+  fn trim(&mut self) {
+    for text in [
+      &mut self.headline,
+      &mut self.description,
+      &mut self.instruction,
+    ] {
+      if let Some(trimmed) = text.as_deref().map(str::trim) {
+        *text = (!trimmed.is_empty()).then(|| trimmed.to_string());
+      }
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapAlert {
+  pub identifier: String,
+  pub sender: String,
+  pub sent: String,
+  pub msg_type: MessageType,
+  /// Earlier alerts this one updates or cancels.
+  pub references: Option<String>,
+  #[serde(rename = "info", default)]
+  pub infos: Vec<CapInfo>,
+}
+
+impl std::str::FromStr for CapAlert {
+  type Err = ApiError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut alert: CapAlert = serde_xml_rs::from_str(s)?;
+    alert.infos.iter_mut().for_each(CapInfo::trim);
+    Ok(alert)
+  }
+}
+
+impl CapAlert {
+  /// Read an alert from a local file.
+  pub fn load(path: impl AsRef<Path>) -> Result<Self, ApiError> {
+    std::fs::read_to_string(path)?.parse()
+  }
+
+  /// Details in a language, or the first ones when there are none in it.
+  pub fn info(&self, language: Language) -> Option<&CapInfo> {
+    let prefix = match language {
+      Language::English => "en",
+      Language::French => "fr",
+    };
+    self
+      .infos
+      .iter()
+      .find(|info| info.language.starts_with(prefix))
+      .or_else(|| self.infos.first())
+  }
+
+  /// The alert as the same warning model as the city feed entries.
+  pub fn warning(&self, language: Language) -> Option<WeatherWarning> {
+    let info = self.info(language)?;
+    let mut warning = parse_warning(info.headline.as_deref()?)?;
+    if !info.areas.is_empty() {
+      warning.area = Some(
+        info
+          .areas
+          .iter()
+          .map(|area| area.description.as_str())
+          .collect::<Vec<_>>()
+          .join(", "),
+      );
+    }
+    if self.msg_type == MessageType::Cancel {
+      warning.status = WarningStatus::Ended;
+    }
+    Some(warning)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::types::{WarningKind, WarningLevel};
+
+  #[test]
+  fn test_parse_alert() {
+    let alert = include_str!("../fixtures/cap_snowfall.xml")
+      .parse::<CapAlert>()
+      .unwrap();
+    assert_eq!(alert.msg_type, MessageType::Alert);
+    assert_eq!(alert.infos.len(), 2);
+
+    let info = alert.info(Language::English).unwrap();
+    assert_eq!(info.severity, Severity::Moderate);
+    assert_eq!(info.urgency, Urgency::Expected);
+    assert_eq!(info.certainty, Certainty::Likely);
+    assert_eq!(info.expires.as_deref(), Some("2023-01-08T18:00:00-00:00"));
+    assert_eq!(
+      info.instruction.as_deref(),
+      Some("Consider postponing non-essential travel until conditions improve.")
+    );
+    assert_eq!(info.areas[0].polygons[0].0.len(), 4);
+    assert_eq!(info.areas[0].polygons[0].0[0], (45.4553, -75.9346));
+
+    assert_eq!(
+      alert.warning(Language::French),
+      Some(WeatherWarning {
+        kind: WarningKind::Snowfall,
+        level: WarningLevel::Warning,
+        status: WarningStatus::InEffect,
+        area: Some("Gatineau".into()),
+      })
+    );
+    assert_eq!(
+      alert.warning(Language::English),
+      alert.warning(Language::French)
+    );
+  }
+
+  #[test]
+  fn test_cancel() {
+    let cancel = include_str!("../fixtures/cap_snowfall.xml")
+      .replace("<msgType>Alert</msgType>", "<msgType>Cancel</msgType>")
+      .parse::<CapAlert>()
+      .unwrap();
+    assert_eq!(
+      cancel.warning(Language::English).map(|w| w.status),
+      Some(WarningStatus::Ended)
+    );
+  }
+
+  #[test]
+  fn test_invalid_polygon() {
+    let alert = include_str!("../fixtures/cap_snowfall.xml")
+      .replace("45.6036,-75.6723", "north")
+      .parse::<CapAlert>()
+      .unwrap();
+    let info = alert.info(Language::English).unwrap();
+    assert!(info.areas[0].polygons.is_empty());
+    assert!(info.instruction.is_some());
+    assert_eq!(
+      alert.warning(Language::English).map(|w| w.kind),
+      Some(WarningKind::Snowfall)
+    );
+    assert_eq!(
+      Polygon::try_from("45.45,-75.93 north".to_string()),
+      Err("invalid polygon point \"north\"".to_string())
+    );
+  }
+}
@@ -0,0 +1,770 @@
+//! Environment Canada citypage XML, e.g.
+//! `https://dd.weather.gc.ca/citypage_weather/xml/QC/s0000430_e.xml`.
+//!
+//! Citypage documents have the same forecast as the city feed, plus what the
+//! feed leaves out: hourly forecasts, sunrise and sunset, yesterday's
+//! conditions, regional normals and structured wind and precipitation. The
+//! forecast is decoded into the same entries as the feed, so it works with
+//! [`to_forecast`] and everything built on it.
+use crate::{
+  diff::Period,
+  types::{
+    to_forecast, CurrentForecast, Forecast, ForecastEntry, Observation, PressureTendency,
+    Temperature, Wind,
+  },
+  ApiError, Category, Entry, Feed, ForeCast, Language, Term,
+};
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+/// Element text, `None` when the element is empty.
+#[derive(Debug, Default, Deserialize)]
+struct RawValue {
+  #[serde(rename = "$value", default)]
+  value: Option<String>,
+}
+
+impl RawValue {
+  fn text(&self) -> Option<&str> {
+    self
+      .value
+      .as_deref()
+      .map(str::trim)
+      .filter(|value| !value.is_empty())
+  }
+
+  fn number(&self) -> Option<f32> {
+    self.text()?.parse().ok()
+  }
+}
+
+fn number(value: &Option<RawValue>) -> Option<f32> {
+  value.as_ref().and_then(RawValue::number)
+}
+
+fn text(value: &Option<RawValue>) -> Option<String> {
+  value.as_ref().and_then(RawValue::text).map(Into::into)
+}
+
+/// Value with a `class` attribute, e.g. `<temperature class="high">`.
+#[derive(Debug, Deserialize)]
+struct RawClassValue {
+  class: Option<String>,
+  #[serde(rename = "$value", default)]
+  value: Option<String>,
+}
+
+fn class_value(values: &[RawClassValue], class: &str) -> Option<f32> {
+  values
+    .iter()
+    .find(|value| value.class.as_deref() == Some(class))
+    .and_then(|value| value.value.as_deref()?.trim().parse().ok())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawDateTime {
+  name: Option<String>,
+  zone: Option<String>,
+  time_stamp: Option<String>,
+}
+
+/// RFC 3339 time of the UTC `dateTime` with a name.
+fn utc_time(times: &[RawDateTime], name: &str) -> Option<String> {
+  times
+    .iter()
+    .find(|time| time.name.as_deref() == Some(name) && time.zone.as_deref() == Some("UTC"))
+    .and_then(|time| time_stamp(time.time_stamp.as_deref()?))
+}
+
+/// Convert a `yyyymmddhhmm[ss]` UTC timestamp to RFC 3339.
+pub(crate) fn time_stamp(stamp: &str) -> Option<String> {
+  let stamp = stamp.trim();
+  if !(12..=14).contains(&stamp.len()) || !stamp.bytes().all(|b| b.is_ascii_digit()) {
+    return None;
+  }
+  let seconds = stamp.get(12..14).unwrap_or("00");
+  Some(format!(
+    "{}-{}-{}T{}:{}:{seconds}Z",
+    &stamp[0..4],
+    &stamp[4..6],
+    &stamp[6..8],
+    &stamp[8..10],
+    &stamp[10..12]
+  ))
+}
+
+#[derive(Debug, Deserialize)]
+struct RawName {
+  code: Option<String>,
+  #[serde(rename = "$value")]
+  value: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawLocation {
+  name: RawName,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawEvent {
+  description: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RawWarnings {
+  #[serde(rename = "event", default)]
+  events: Vec<RawEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawWind {
+  speed: Option<RawValue>,
+  gust: Option<RawValue>,
+  direction: Option<RawValue>,
+}
+
+impl RawWind {
+  fn to_wind(&self) -> Option<Wind> {
+    Some(Wind {
+      direction: text(&self.direction),
+      speed: number(&self.speed)?,
+      gust: number(&self.gust).filter(|gust| *gust > 0.),
+    })
+  }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawPressure {
+  tendency: Option<String>,
+  #[serde(rename = "$value", default)]
+  value: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawCurrent {
+  station: Option<RawValue>,
+  #[serde(rename = "dateTime", default)]
+  date_time: Vec<RawDateTime>,
+  condition: Option<String>,
+  temperature: Option<RawValue>,
+  dewpoint: Option<RawValue>,
+  wind_chill: Option<RawValue>,
+  humidex: Option<RawValue>,
+  pressure: Option<RawPressure>,
+  visibility: Option<RawValue>,
+  relative_humidity: Option<RawValue>,
+  wind: Option<RawWind>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSummary {
+  text_summary: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawAbbreviated {
+  pop: Option<RawValue>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTemperatures {
+  text_summary: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawWinds {
+  #[serde(rename = "wind", default)]
+  winds: Vec<RawWind>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawAmount {
+  units: Option<String>,
+  #[serde(rename = "$value", default)]
+  value: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawAccumulation {
+  name: Option<String>,
+  amount: RawAmount,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawPrecipitation {
+  #[serde(rename = "precipType", default)]
+  precip_types: Vec<RawValue>,
+  #[serde(rename = "accumulation", default)]
+  accumulations: Vec<RawAccumulation>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawForecast {
+  period: RawValue,
+  text_summary: Option<String>,
+  cloud_precip: Option<RawSummary>,
+  abbreviated_forecast: Option<RawAbbreviated>,
+  temperatures: Option<RawTemperatures>,
+  winds: Option<RawWinds>,
+  precipitation: Option<RawPrecipitation>,
+  relative_humidity: Option<RawValue>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawNormals {
+  #[serde(rename = "temperature", default)]
+  temperatures: Vec<RawClassValue>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawForecastGroup {
+  #[serde(rename = "dateTime", default)]
+  date_time: Vec<RawDateTime>,
+  regional_normals: Option<RawNormals>,
+  #[serde(rename = "forecast", default)]
+  forecasts: Vec<RawForecast>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawHour {
+  #[serde(rename = "dateTimeUTC")]
+  date_time_utc: String,
+  condition: Option<String>,
+  temperature: Option<RawValue>,
+  lop: Option<RawValue>,
+  wind_chill: Option<RawValue>,
+  humidex: Option<RawValue>,
+  wind: Option<RawWind>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawHourlyGroup {
+  #[serde(rename = "hourlyForecast", default)]
+  hours: Vec<RawHour>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawYesterday {
+  #[serde(rename = "temperature", default)]
+  temperatures: Vec<RawClassValue>,
+  precip: Option<RawValue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawRiseSet {
+  #[serde(rename = "dateTime", default)]
+  date_time: Vec<RawDateTime>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSiteData {
+  location: RawLocation,
+  warnings: Option<RawWarnings>,
+  current_conditions: Option<RawCurrent>,
+  forecast_group: Option<RawForecastGroup>,
+  hourly_forecast_group: Option<RawHourlyGroup>,
+  yesterday_conditions: Option<RawYesterday>,
+  rise_set: Option<RawRiseSet>,
+}
+
+/// Forecast high and low.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HighLow {
+  pub high: Option<f32>,
+  pub low: Option<f32>,
+}
+
+/// Yesterday's observed extremes and precipitation in mm.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Yesterday {
+  pub high: Option<f32>,
+  pub low: Option<f32>,
+  pub precipitation: Option<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Accumulation {
+  /// What accumulates, e.g. "snow".
+  pub name: Option<String>,
+  pub amount: f32,
+  pub units: Option<String>,
+}
+
+/// Structured values of a forecast period that its text only describes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeriodDetails {
+  pub period: Period,
+  /// Major wind first.
+  pub winds: Vec<Wind>,
+  pub precipitation: Vec<String>,
+  pub accumulation: Option<Accumulation>,
+  /// Relative humidity in percent.
+  pub humidity: Option<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HourlyForecast {
+  /// RFC 3339 start of the hour.
+  pub time: String,
+  pub condition: Option<String>,
+  pub celsius: Option<f32>,
+  /// Likelihood of precipitation in percent.
+  pub lop: Option<u8>,
+  pub wind_chill: Option<f32>,
+  pub humidex: Option<f32>,
+  pub wind: Option<Wind>,
+}
+
+/// Decoded citypage document. Times are RFC 3339.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CityPage {
+  pub name: String,
+  /// Site code, e.g. `s0000430`.
+  pub site: Option<String>,
+  pub issued: Option<String>,
+  /// The forecast as city feed entries.
+  pub entries: Vec<Entry>,
+  pub current: Option<CurrentForecast>,
+  pub periods: Vec<PeriodDetails>,
+  pub hourly: Vec<HourlyForecast>,
+  pub normals: Option<HighLow>,
+  pub yesterday: Option<Yesterday>,
+  pub sunrise: Option<String>,
+  pub sunset: Option<String>,
+}
+
+impl CityPage {
+  /// Forecast entries, with the current conditions taken from the structured
+  /// values rather than the title.
+  pub fn forecast(&self) -> Vec<ForecastEntry> {
+    let mut entries = to_forecast(self.entries.clone().into_iter());
+    if let Some(current) = &self.current {
+      for entry in &mut entries {
+        if let ForecastEntry::Current(entry) = entry {
+          entry.current = current.clone();
+        }
+      }
+    }
+    entries
+  }
+
+  /// Details of a forecast period.
+  pub fn period(&self, period: Period) -> Option<&PeriodDetails> {
+    self.periods.iter().find(|details| details.period == period)
+  }
+}
+
+impl From<CityPage> for ForeCast {
+  fn from(page: CityPage) -> Self {
+    ForeCast(Feed {
+      updated: page.issued,
+      entries: page.entries,
+      warnings: vec![],
+    })
+  }
+}
+
+fn entry(title: String, term: Term, summary: String, updated: Option<String>) -> Entry {
+  Entry {
+    title,
+    category: Category { term },
+    summary,
+    updated,
+  }
+}
+
+/// Uppercase the first letter of a French period name, "samedi soir et nuit".
+fn capitalize(text: &str) -> String {
+  let mut chars = text.chars();
+  chars
+    .next()
+    .map(|first| first.to_uppercase().chain(chars).collect())
+    .unwrap_or_default()
+}
+
+fn current(raw: &RawCurrent, language: Language) -> (Entry, CurrentForecast) {
+  let temperature = number(&raw.temperature).map(|t| Temperature::Current(t, PhantomData));
+  let observation = Observation {
+    observed_at: text(&raw.station),
+    condition: raw.condition.clone().filter(|c| !c.trim().is_empty()),
+    temperature,
+    pressure: raw
+      .pressure
+      .as_ref()
+      .and_then(|p| p.value.as_deref()?.trim().parse().ok()),
+    tendency: raw
+      .pressure
+      .as_ref()
+      .and_then(|p| match p.tendency.as_deref()? {
+        "rising" => Some(PressureTendency::Rising),
+        "falling" => Some(PressureTendency::Falling),
+        "steady" => Some(PressureTendency::Steady),
+        _ => None,
+      }),
+    visibility: number(&raw.visibility),
+    humidity: number(&raw.relative_humidity),
+    wind_chill: number(&raw.wind_chill),
+    humidex: number(&raw.humidex),
+    dewpoint: number(&raw.dewpoint).map(|t| Temperature::Current(t, PhantomData)),
+    wind: raw.wind.as_ref().and_then(RawWind::to_wind),
+    aqhi: None,
+    ceiling: None,
+    heat_index: None,
+    frost_point: None,
+  };
+  let current = CurrentForecast::default().with_observation(observation);
+
+  let label = match language {
+    Language::English => "Current Conditions",
+    Language::French => "Conditions actuelles",
+  };
+  let title = match (&current.description, &current.celsius) {
+    (Some(description), Some(celsius)) => format!("{label}: {description}, {celsius}"),
+    (Some(description), None) => format!("{label}: {description}"),
+    (None, Some(celsius)) => format!("{label}: {celsius}"),
+    (None, None) => label.into(),
+  };
+  let entry = entry(
+    title,
+    Term::Current,
+    String::new(),
+    utc_time(&raw.date_time, "observation"),
+  );
+  (entry, current)
+}
+
+/// City feed entry for a forecast period, titled the way the feed titles it
+/// so that the same grammar parses it.
+fn forecast_entry(raw: &RawForecast, updated: &Option<String>) -> Option<(Entry, Forecast)> {
+  let period = capitalize(raw.period.text()?);
+  let mut title = format!("{period}:");
+  let parts = [
+    raw
+      .cloud_precip
+      .as_ref()
+      .and_then(|c| c.text_summary.as_deref()),
+    raw
+      .temperatures
+      .as_ref()
+      .and_then(|t| t.text_summary.as_deref()),
+  ];
+  for part in parts.into_iter().flatten().map(str::trim) {
+    if !part.is_empty() {
+      title.push(' ');
+      title.push_str(part);
+    }
+  }
+  let pop = raw
+    .abbreviated_forecast
+    .as_ref()
+    .and_then(|a| a.pop.as_ref()?.number());
+  if let Some(pop) = pop {
+    title.push_str(&format!(" POP {pop}%"));
+  }
+
+  let forecast = title.parse::<Forecast>().ok()?;
+  let summary = raw.text_summary.clone().unwrap_or_default();
+  Some((
+    entry(
+      title,
+      Term::ForeCast,
+      summary.trim().into(),
+      updated.clone(),
+    ),
+    forecast,
+  ))
+}
+
+fn period_details(raw: &RawForecast, forecast: &Forecast) -> PeriodDetails {
+  PeriodDetails {
+    period: Period {
+      day_of_week: forecast.day_of_week,
+      day: forecast.day,
+    },
+    winds: raw
+      .winds
+      .iter()
+      .flat_map(|winds| &winds.winds)
+      .filter_map(RawWind::to_wind)
+      .collect(),
+    precipitation: raw
+      .precipitation
+      .iter()
+      .flat_map(|p| &p.precip_types)
+      .filter_map(|p| p.text().map(Into::into))
+      .collect(),
+    accumulation: raw
+      .precipitation
+      .iter()
+      .flat_map(|p| &p.accumulations)
+      .find_map(|a| {
+        Some(Accumulation {
+          name: a.name.clone(),
+          amount: a.amount.value.as_deref()?.trim().parse().ok()?,
+          units: a.amount.units.clone(),
+        })
+      }),
+    humidity: number(&raw.relative_humidity),
+  }
+}
+
+fn hourly(raw: &RawHour) -> Option<HourlyForecast> {
+  Some(HourlyForecast {
+    time: time_stamp(&raw.date_time_utc)?,
+    condition: raw.condition.clone().filter(|c| !c.trim().is_empty()),
+    celsius: number(&raw.temperature),
+    lop: number(&raw.lop).map(|lop| lop as u8),
+    wind_chill: number(&raw.wind_chill),
+    humidex: number(&raw.humidex),
+    wind: raw.wind.as_ref().and_then(RawWind::to_wind),
+  })
+}
+
+/// Remove the XML declaration of already decoded text, which the parser would
+/// decode a second time from the declared encoding, e.g. ISO-8859-1.
+pub(crate) fn strip_declaration(input: &str) -> &str {
+  let input = input.trim_start();
+  match input.strip_prefix("<?xml") {
+    Some(rest) => rest.split_once("?>").map(|(_, rest)| rest).unwrap_or(input),
+    None => input,
+  }
+}
+
+/// Decode a citypage document published in `language`.
+pub fn decode(input: &str, language: Language) -> Result<CityPage, ApiError> {
+  let raw: RawSiteData = serde_xml_rs::from_str(strip_declaration(input))?;
+  let name = raw.location.name.value.trim().to_string();
+  let issued = raw
+    .forecast_group
+    .as_ref()
+    .and_then(|group| utc_time(&group.date_time, "forecastIssue"));
+  let mut entries = vec![];
+
+  for event in raw.warnings.iter().flat_map(|warnings| &warnings.events) {
+    // Descriptions come padded, "SNOWFALL WARNING  IN EFFECT".
+    let description = event.description.split_whitespace().collect::<Vec<_>>();
+    if description.is_empty() {
+      continue;
+    }
+    entries.push(entry(
+      format!("{}, {name}", description.join(" ")),
+      Term::Warnings,
+      String::new(),
+      issued.clone(),
+    ));
+  }
+
+  let current = raw.current_conditions.as_ref().map(|raw| {
+    let (entry, current) = current(raw, language);
+    entries.push(entry);
+    current
+  });
+
+  let mut periods = vec![];
+  for raw in raw.forecast_group.iter().flat_map(|group| &group.forecasts) {
+    if let Some((entry, forecast)) = forecast_entry(raw, &issued) {
+      periods.push(period_details(raw, &forecast));
+      entries.push(entry);
+    }
+  }
+
+  let rise_set = |name| {
+    raw
+      .rise_set
+      .as_ref()
+      .and_then(|rise_set| utc_time(&rise_set.date_time, name))
+  };
+
+  Ok(CityPage {
+    site: raw.location.name.code.clone(),
+    issued: issued.clone(),
+    entries,
+    current,
+    periods,
+    hourly: raw
+      .hourly_forecast_group
+      .iter()
+      .flat_map(|group| &group.hours)
+      .filter_map(hourly)
+      .collect(),
+    normals: raw
+      .forecast_group
+      .as_ref()
+      .and_then(|group| group.regional_normals.as_ref())
+      .map(|normals| HighLow {
+        high: class_value(&normals.temperatures, "high"),
+        low: class_value(&normals.temperatures, "low"),
+      }),
+    yesterday: raw
+      .yesterday_conditions
+      .as_ref()
+      .map(|yesterday| Yesterday {
+        high: class_value(&yesterday.temperatures, "high"),
+        low: class_value(&yesterday.temperatures, "low"),
+        precipitation: number(&yesterday.precip),
+      }),
+    sunrise: rise_set("sunrise"),
+    sunset: rise_set("sunset"),
+    name,
+  })
+}
+
+/// Decode a citypage document as served. The documents declare ISO-8859-1,
+/// so anything that isn't valid UTF-8 is read as Latin-1.
+pub fn decode_bytes(input: &[u8], language: Language) -> Result<CityPage, ApiError> {
+  match std::str::from_utf8(input) {
+    Ok(input) => decode(input, language),
+    Err(_) => decode(
+      &input.iter().map(|&b| char::from(b)).collect::<String>(),
+      language,
+    ),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::types::{DayNight, DayOfWeek};
+
+  fn page() -> CityPage {
+    decode(
+      include_str!("../fixtures/citypage_s0000430_e.xml"),
+      Language::English,
+    )
+    .unwrap()
+  }
+
+  #[test]
+  fn test_time_stamp() {
+    assert_eq!(
+      time_stamp("20230107204500").as_deref(),
+      Some("2023-01-07T20:45:00Z")
+    );
+    assert_eq!(
+      time_stamp("202301072100").as_deref(),
+      Some("2023-01-07T21:00:00Z")
+    );
+    assert_eq!(time_stamp("2023"), None);
+  }
+
+  #[test]
+  fn test_same_model_as_feed() {
+    let page = page();
+    assert_eq!(page.name, "Gatineau");
+    assert_eq!(page.issued.as_deref(), Some("2023-01-07T20:45:00Z"));
+
+    let titles = page
+      .entries
+      .iter()
+      .map(|entry| entry.title.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(
+      titles,
+      [
+        "SNOWFALL WARNING IN EFFECT, Gatineau",
+        "Current Conditions: Light Snow, -3.4°C",
+        "Saturday night: Periods of snow. Low minus 5.",
+        "Sunday: Cloudy. High minus 2.",
+        "Monday: Chance of flurries. High minus 3. POP 60%",
+      ]
+    );
+
+    let forecast = page.forecast();
+    let Some(ForecastEntry::Current(current)) = forecast.get(1) else {
+      panic!("no current conditions");
+    };
+    let observation = current.current.observation.as_ref().unwrap();
+    assert_eq!(observation.pressure, Some(101.2));
+    assert_eq!(observation.tendency, Some(PressureTendency::Falling));
+    assert_eq!(observation.wind_chill, Some(-9.));
+    assert_eq!(
+      observation.wind,
+      Some(Wind {
+        direction: Some("NE".into()),
+        speed: 17.,
+        gust: None,
+      })
+    );
+    assert_eq!(
+      forecast
+        .iter()
+        .filter(|entry| matches!(entry, ForecastEntry::Future { .. }))
+        .count(),
+      3
+    );
+  }
+
+  #[test]
+  fn test_extras() {
+    let page = page();
+    assert_eq!(page.sunrise.as_deref(), Some("2023-01-07T12:35:00Z"));
+    assert_eq!(page.sunset.as_deref(), Some("2023-01-07T21:37:00Z"));
+    assert_eq!(
+      page.normals,
+      Some(HighLow {
+        high: Some(-6.),
+        low: Some(-15.),
+      })
+    );
+    assert_eq!(
+      page.yesterday,
+      Some(Yesterday {
+        high: Some(-1.6),
+        low: Some(-7.8),
+        precipitation: Some(0.4),
+      })
+    );
+
+    assert_eq!(page.hourly.len(), 2);
+    assert_eq!(page.hourly[1].time, "2023-01-07T22:00:00Z");
+    assert_eq!(page.hourly[1].lop, Some(70));
+    assert_eq!(page.hourly[1].wind.as_ref().unwrap().gust, Some(40.));
+
+    let tonight = page
+      .period(Period {
+        day_of_week: DayOfWeek::Saturday,
+        day: DayNight::Night,
+      })
+      .unwrap();
+    assert_eq!(tonight.precipitation, ["snow"]);
+    assert_eq!(
+      tonight.accumulation,
+      Some(Accumulation {
+        name: Some("snow".into()),
+        amount: 3.,
+        units: Some("cm".into()),
+      })
+    );
+    assert_eq!(tonight.winds[0].speed, 20.);
+    assert_eq!(tonight.humidity, Some(90.));
+  }
+
+  #[test]
+  fn test_latin1() {
+    let latin1 = include_str!("../fixtures/citypage_s0000430_e.xml")
+      .replace("Gatineau", "Lévis")
+      .chars()
+      .map(|c| c as u8)
+      .collect::<Vec<_>>();
+    assert_eq!(
+      decode_bytes(&latin1, Language::English).unwrap().name,
+      "Lévis"
+    );
+  }
+
+  #[test]
+  fn test_to_feed() {
+    let forecast = ForeCast::from(page());
+    assert_eq!(forecast.updated(), Some("2023-01-07T20:45:00Z"));
+    assert_eq!(forecast.current_forecast().count(), 2);
+  }
+}
@@ -0,0 +1,520 @@
+//! Fetch feeds for many cities over one connection pool.
+use crate::{
+  air_quality::{AirQuality, Region},
+  cap::CapAlert,
+  citypage::{self, CityPage},
+  stream, ApiError, ForeCast, Language,
+};
+use serde::{Deserialize, Serialize};
+
+static BASE_URL: &str = "https://weather.gc.ca/rss/city";
+static AQHI_URL: &str = "https://dd.weather.gc.ca/air_quality/aqhi";
+static MARINE_URL: &str = "https://weather.gc.ca/rss/marine";
+
+/// Number of feeds fetched at the same time by default.
+const DEFAULT_CONCURRENCY: usize = 8;
+
+/// A city feed, e.g. `qc-58` in English.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Location {
+  pub city: String,
+  pub language: Language,
+}
+
+impl Location {
+  pub fn new(city: impl Into<String>, language: Language) -> Self {
+    Self {
+      city: city.into(),
+      language,
+    }
+  }
+}
+
+fn feed_url(base_url: &str, location: &Location) -> String {
+  format!(
+    "{}/{}_{}.xml",
+    base_url.trim_end_matches('/'),
+    location.city,
+    location.language.feed_suffix()
+  )
+}
+
+/// Client for the city feeds. Clones share the same connection pool.
+#[derive(Debug, Clone)]
+pub struct WeatherClient {
+  #[cfg(feature = "async")]
+  client: reqwest::Client,
+  #[cfg(not(feature = "async"))]
+  client: reqwest::blocking::Client,
+  base_url: String,
+  aqhi_url: String,
+  marine_url: String,
+  concurrency: usize,
+}
+
+impl Default for WeatherClient {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl WeatherClient {
+  pub fn new() -> Self {
+    Self {
+      client: Default::default(),
+      base_url: BASE_URL.into(),
+      aqhi_url: AQHI_URL.into(),
+      marine_url: MARINE_URL.into(),
+      concurrency: DEFAULT_CONCURRENCY,
+    }
+  }
+
+  /// Maximum number of feeds fetched at the same time by a batch.
+  pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+    self.concurrency = concurrency.max(1);
+    self
+  }
+
+  /// Fetch feeds from another server, e.g. a mirror.
+  pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+    self.base_url = base_url.into();
+    self
+  }
+
+  /// Fetch AQHI documents from another server.
+  pub fn with_aqhi_url(mut self, aqhi_url: impl Into<String>) -> Self {
+    self.aqhi_url = aqhi_url.into();
+    self
+  }
+
+  /// Fetch marine feeds from another server.
+  pub fn with_marine_url(mut self, marine_url: impl Into<String>) -> Self {
+    self.marine_url = marine_url.into();
+    self
+  }
+
+  fn aqhi_url(&self, path: &str) -> String {
+    format!("{}/{path}", self.aqhi_url.trim_end_matches('/'))
+  }
+
+  /// RSS feed url for a location.
+  fn url(&self, location: &Location) -> String {
+    feed_url(&self.base_url, location)
+  }
+
+  /// Gets the weather forecast for one location.
+  #[cfg(feature = "async")]
+  pub async fn get_weather(&self, location: &Location) -> Result<ForeCast, ApiError> {
+    let body = self
+      .client
+      .get(self.url(location))
+      .send()
+      .await?
+      .error_for_status()?
+      .text()
+      .await?;
+    Ok(stream::decode(&body)?.into())
+  }
+
+  #[cfg(not(feature = "async"))]
+  pub fn get_weather(&self, location: &Location) -> Result<ForeCast, ApiError> {
+    let body = self
+      .client
+      .get(self.url(location))
+      .send()?
+      .error_for_status()?
+      .text()?;
+    Ok(stream::decode(&body)?.into())
+  }
+
+  /// Gets the marine feed of a location whose city is a marine region code,
+  /// e.g. `08000` for Lake Ontario. See [`marine`](crate::marine) to decode
+  /// its forecasts.
+  #[cfg(feature = "async")]
+  pub async fn get_marine(&self, location: &Location) -> Result<ForeCast, ApiError> {
+    let body = self
+      .client
+      .get(feed_url(&self.marine_url, location))
+      .send()
+      .await?
+      .error_for_status()?
+      .text()
+      .await?;
+    Ok(stream::decode(&body)?.into())
+  }
+
+  #[cfg(not(feature = "async"))]
+  pub fn get_marine(&self, location: &Location) -> Result<ForeCast, ApiError> {
+    let body = self
+      .client
+      .get(feed_url(&self.marine_url, location))
+      .send()?
+      .error_for_status()?
+      .text()?;
+    Ok(stream::decode(&body)?.into())
+  }
+
+  /// Gets a CAP alert, e.g. one linked from a warnings entry.
+  #[cfg(feature = "async")]
+  pub async fn get_alert(&self, url: &str) -> Result<CapAlert, ApiError> {
+    let body = self
+      .client
+      .get(url)
+      .send()
+      .await?
+      .error_for_status()?
+      .text()
+      .await?;
+    body.parse()
+  }
+
+  #[cfg(not(feature = "async"))]
+  pub fn get_alert(&self, url: &str) -> Result<CapAlert, ApiError> {
+    self
+      .client
+      .get(url)
+      .send()?
+      .error_for_status()?
+      .text()?
+      .parse()
+  }
+
+  /// Gets a citypage document, e.g.
+  /// `https://dd.weather.gc.ca/citypage_weather/xml/QC/s0000430_e.xml`.
+  #[cfg(feature = "async")]
+  pub async fn get_city_page(&self, url: &str, language: Language) -> Result<CityPage, ApiError> {
+    let body = self
+      .client
+      .get(url)
+      .send()
+      .await?
+      .error_for_status()?
+      .bytes()
+      .await?;
+    citypage::decode_bytes(&body, language)
+  }
+
+  #[cfg(not(feature = "async"))]
+  pub fn get_city_page(&self, url: &str, language: Language) -> Result<CityPage, ApiError> {
+    let body = self.client.get(url).send()?.error_for_status()?.bytes()?;
+    citypage::decode_bytes(&body, language)
+  }
+
+  /// Gets the latest AQHI observation and forecast of a region, for a
+  /// location.
+  #[cfg(feature = "async")]
+  pub async fn get_air_quality(
+    &self,
+    location: &Location,
+    region: &Region,
+  ) -> Result<AirQuality, ApiError> {
+    let get = |path: String| async move {
+      self
+        .client
+        .get(self.aqhi_url(&path))
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await
+    };
+    let (observation, forecast) =
+      futures::try_join!(get(region.observation_path()), get(region.forecast_path()))?;
+    AirQuality::decode(
+      location.clone(),
+      region.clone(),
+      Some(&observation),
+      Some(&forecast),
+    )
+  }
+
+  #[cfg(not(feature = "async"))]
+  pub fn get_air_quality(
+    &self,
+    location: &Location,
+    region: &Region,
+  ) -> Result<AirQuality, ApiError> {
+    let get = |path: String| {
+      self
+        .client
+        .get(self.aqhi_url(&path))
+        .send()?
+        .error_for_status()?
+        .text()
+    };
+    let observation = get(region.observation_path())?;
+    let forecast = get(region.forecast_path())?;
+    AirQuality::decode(
+      location.clone(),
+      region.clone(),
+      Some(&observation),
+      Some(&forecast),
+    )
+  }
+
+  /// Gets the weather forecast for every location, at most `concurrency` at a
+  /// time. Results are in the order of `locations`, and a failed location
+  /// doesn't affect the others.
+  #[cfg(feature = "async")]
+  pub async fn get_weather_batch(&self, locations: &[Location]) -> Vec<Result<ForeCast, ApiError>> {
+    use futures::{stream, StreamExt};
+
+    stream::iter(locations)
+      .map(|location| self.get_weather(location))
+      .buffered(self.concurrency)
+      .collect()
+      .await
+  }
+
+  #[cfg(not(feature = "async"))]
+  pub fn get_weather_batch(&self, locations: &[Location]) -> Vec<Result<ForeCast, ApiError>> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let next = AtomicUsize::new(0);
+    let mut results = std::thread::scope(|scope| {
+      let workers = (0..self.concurrency.min(locations.len()))
+        .map(|_| {
+          scope.spawn(|| {
+            let mut results = vec![];
+            loop {
+              let index = next.fetch_add(1, Ordering::Relaxed);
+              let Some(location) = locations.get(index) else {
+                break results;
+              };
+              results.push((index, self.get_weather(location)));
+            }
+          })
+        })
+        .collect::<Vec<_>>();
+      workers
+        .into_iter()
+        .flat_map(|worker| worker.join().expect("Fetch worker panicked"))
+        .collect::<Vec<_>>()
+    });
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::marine::MarineForecast;
+
+  fn mock_feeds(server: &mut mockito::Server) -> Vec<mockito::Mock> {
+    vec![
+      server
+        .mock("GET", "/qc-58_e.xml")
+        .with_body(include_str!("../fixtures/qc-58_e.xml"))
+        .create(),
+      server
+        .mock("GET", "/qc-58_f.xml")
+        .with_body(include_str!("../fixtures/qc-58_f.xml"))
+        .create(),
+      server.mock("GET", "/xx-1_e.xml").with_status(404).create(),
+    ]
+  }
+
+  fn locations() -> Vec<Location> {
+    vec![
+      Location::new("qc-58", Language::English),
+      Location::new("xx-1", Language::English),
+      Location::new("qc-58", Language::French),
+    ]
+  }
+
+  fn assert_batch(results: &[Result<ForeCast, ApiError>]) {
+    assert_eq!(results.len(), 3);
+    assert!(results[0]
+      .as_ref()
+      .unwrap()
+      .current_forecast()
+      .any(|entry| entry.title.starts_with("Current Conditions")));
+    assert!(matches!(results[1], Err(ApiError::Rss(_))));
+    assert!(results[2]
+      .as_ref()
+      .unwrap()
+      .current_forecast()
+      .any(|entry| entry.title.starts_with("Conditions actuelles")));
+  }
+
+  #[cfg(feature = "async")]
+  #[tokio::test]
+  async fn test_batch() {
+    let mut server = mockito::Server::new_async().await;
+    let _mocks = mock_feeds(&mut server);
+    let client = WeatherClient::new()
+      .with_base_url(server.url())
+      .with_concurrency(2);
+
+    assert_batch(&client.get_weather_batch(&locations()).await);
+  }
+
+  #[cfg(feature = "async")]
+  #[tokio::test]
+  async fn test_alert() {
+    let mut server = mockito::Server::new_async().await;
+    let _mock = server
+      .mock("GET", "/alert.cap")
+      .with_body(include_str!("../fixtures/cap_snowfall.xml"))
+      .create();
+
+    let alert = WeatherClient::new()
+      .get_alert(&format!("{}/alert.cap", server.url()))
+      .await
+      .unwrap();
+    assert_eq!(alert.infos.len(), 2);
+  }
+
+  fn mock_air_quality(server: &mut mockito::Server) -> Vec<mockito::Mock> {
+    vec![
+      server
+        .mock(
+          "GET",
+          "/ont/observation/realtime/xml/AQ_OBS_FEVNT_CURRENT.xml",
+        )
+        .with_body(include_str!("../fixtures/aqhi_obs_fevnt.xml"))
+        .create(),
+      server
+        .mock(
+          "GET",
+          "/ont/forecast/realtime/xml/AQ_FCST_FEVNT_CURRENT.xml",
+        )
+        .with_body(include_str!("../fixtures/aqhi_fcst_fevnt.xml"))
+        .create(),
+    ]
+  }
+
+  fn mock_marine(server: &mut mockito::Server) -> mockito::Mock {
+    server
+      .mock("GET", "/08000_e.xml")
+      .with_body(include_str!("../fixtures/marine_08000_e.xml"))
+      .create()
+  }
+
+  #[cfg(feature = "async")]
+  #[tokio::test]
+  async fn test_marine() {
+    let mut server = mockito::Server::new_async().await;
+    let _mock = mock_marine(&mut server);
+
+    let feed = WeatherClient::new()
+      .with_marine_url(server.url())
+      .get_marine(&Location::new("08000", Language::English))
+      .await
+      .unwrap();
+    assert_eq!(MarineForecast::from(feed).areas.len(), 2);
+  }
+
+  #[cfg(feature = "async")]
+  #[tokio::test]
+  async fn test_air_quality() {
+    let mut server = mockito::Server::new_async().await;
+    let _mocks = mock_air_quality(&mut server);
+
+    let air = WeatherClient::new()
+      .with_aqhi_url(server.url())
+      .get_air_quality(
+        &Location::new("on-118", Language::English),
+        &"ont/FEVNT".parse().unwrap(),
+      )
+      .await
+      .unwrap();
+    assert_eq!(air.region_name.as_deref(), Some("Ottawa"));
+    assert_eq!(air.forecast.len(), 3);
+  }
+
+  #[cfg(feature = "async")]
+  #[tokio::test]
+  async fn test_city_page() {
+    let mut server = mockito::Server::new_async().await;
+    let _mock = server
+      .mock("GET", "/s0000430_e.xml")
+      .with_body(include_str!("../fixtures/citypage_s0000430_e.xml"))
+      .create();
+
+    let page = WeatherClient::new()
+      .get_city_page(
+        &format!("{}/s0000430_e.xml", server.url()),
+        Language::English,
+      )
+      .await
+      .unwrap();
+    assert_eq!(page.site.as_deref(), Some("s0000430"));
+  }
+
+  #[cfg(not(feature = "async"))]
+  #[test]
+  fn test_batch() {
+    let mut server = mockito::Server::new();
+    let _mocks = mock_feeds(&mut server);
+    let client = WeatherClient::new()
+      .with_base_url(server.url())
+      .with_concurrency(2);
+
+    assert_batch(&client.get_weather_batch(&locations()));
+  }
+
+  #[cfg(not(feature = "async"))]
+  #[test]
+  fn test_alert() {
+    let mut server = mockito::Server::new();
+    let _mock = server
+      .mock("GET", "/alert.cap")
+      .with_body(include_str!("../fixtures/cap_snowfall.xml"))
+      .create();
+
+    let alert = WeatherClient::new()
+      .get_alert(&format!("{}/alert.cap", server.url()))
+      .unwrap();
+    assert_eq!(alert.infos.len(), 2);
+  }
+
+  #[cfg(not(feature = "async"))]
+  #[test]
+  fn test_city_page() {
+    let mut server = mockito::Server::new();
+    let _mock = server
+      .mock("GET", "/s0000430_e.xml")
+      .with_body(include_str!("../fixtures/citypage_s0000430_e.xml"))
+      .create();
+
+    let page = WeatherClient::new()
+      .get_city_page(
+        &format!("{}/s0000430_e.xml", server.url()),
+        Language::English,
+      )
+      .unwrap();
+    assert_eq!(page.site.as_deref(), Some("s0000430"));
+  }
+
+  #[cfg(not(feature = "async"))]
+  #[test]
+  fn test_marine() {
+    let mut server = mockito::Server::new();
+    let _mock = mock_marine(&mut server);
+
+    let feed = WeatherClient::new()
+      .with_marine_url(server.url())
+      .get_marine(&Location::new("08000", Language::English))
+      .unwrap();
+    assert_eq!(MarineForecast::from(feed).areas.len(), 2);
+  }
+
+  #[cfg(not(feature = "async"))]
+  #[test]
+  fn test_air_quality() {
+    let mut server = mockito::Server::new();
+    let _mocks = mock_air_quality(&mut server);
+
+    let air = WeatherClient::new()
+      .with_aqhi_url(server.url())
+      .get_air_quality(
+        &Location::new("on-118", Language::English),
+        &"ont/FEVNT".parse().unwrap(),
+      )
+      .unwrap();
+    assert_eq!(air.region_name.as_deref(), Some("Ottawa"));
+    assert_eq!(air.forecast.len(), 3);
+  }
+}
@@ -0,0 +1,351 @@
+//! Climate normals and records per calendar day, from the daily data
+//! Environment Canada exports as CSV on its historical climate data site.
+//!
+//! Normals are the mean highs and lows of every year loaded, so they are only
+//! as representative as the years the files cover. The official normals use
+//! the thirty years from 1991 to 2020.
+use crate::{
+  dates::{forecast_date, updated_on},
+  types::{DayNight, Departure, Forecast, ForecastEntry, ForecastWithEntry},
+};
+use chrono::{Datelike, NaiveDate, TimeZone};
+use serde::Serialize;
+use std::{
+  collections::{BTreeMap, HashSet},
+  path::Path,
+};
+use thiserror::Error;
+
+/// Environment variable listing the climate data files to load, separated
+/// like `PATH`.
+pub const NORMALS_ENV: &str = "MY_WEATHER_NORMALS";
+
+const YEAR: &str = "Year";
+const MONTH: &str = "Month";
+const DAY: &str = "Day";
+const MAX_TEMP: &str = "Max Temp (°C)";
+const MIN_TEMP: &str = "Min Temp (°C)";
+
+/// Climate data loading errors.
+#[derive(Error, Debug)]
+pub enum ClimateError {
+  #[error("Climate data io error {0}")]
+  Io(#[from] std::io::Error),
+  #[error("Climate data has no {0} column")]
+  MissingColumn(&'static str),
+}
+
+/// Record temperature of a calendar day and the year it was set.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Record {
+  pub celsius: f32,
+  pub year: i32,
+}
+
+/// Normals and records of a calendar day, in celsius.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DayNormals {
+  /// Mean of the daily highs.
+  pub high: Option<f32>,
+  /// Mean of the daily lows.
+  pub low: Option<f32>,
+  pub record_high: Option<Record>,
+  pub record_low: Option<Record>,
+  /// Years with a high or a low for the day.
+  pub years: usize,
+}
+
+/// Highs and lows of a calendar day over the years loaded.
+#[derive(Debug, Clone, Default)]
+struct Accumulator {
+  highs: Vec<(i32, f32)>,
+  lows: Vec<(i32, f32)>,
+  years: usize,
+}
+
+impl Accumulator {
+  fn normals(&self) -> DayNormals {
+    let mean = |values: &[(i32, f32)]| {
+      (!values.is_empty())
+        .then(|| values.iter().map(|(_, celsius)| celsius).sum::<f32>() / values.len() as f32)
+    };
+    // The earliest year keeps a record it shares with later years.
+    let record = |values: &[(i32, f32)], higher: fn(f32, f32) -> bool| {
+      values.iter().fold(
+        None,
+        |record: Option<Record>, &(year, celsius)| match record {
+          Some(record) if !higher(celsius, record.celsius) => Some(record),
+          _ => Some(Record { celsius, year }),
+        },
+      )
+    };
+    DayNormals {
+      high: mean(&self.highs),
+      low: mean(&self.lows),
+      record_high: record(&self.highs, |a, b| a > b),
+      record_low: record(&self.lows, |a, b| a < b),
+      years: self.years,
+    }
+  }
+}
+
+/// Daily climate data by calendar day.
+#[derive(Debug, Clone, Default)]
+pub struct Normals {
+  days: BTreeMap<(u32, u32), Accumulator>,
+  dates: HashSet<NaiveDate>,
+}
+
+impl Normals {
+  /// Normals of the CSV files at `paths`.
+  pub fn load<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) -> Result<Self, ClimateError> {
+    let mut normals = Self::default();
+    for path in paths {
+      normals.add(&std::fs::read_to_string(path)?)?;
+    }
+    Ok(normals)
+  }
+
+  /// Normals of the files named by [`NORMALS_ENV`], or none when it isn't
+  /// set.
+  pub fn from_env() -> Result<Self, ClimateError> {
+    match std::env::var_os(NORMALS_ENV) {
+      Some(paths) => Self::load(std::env::split_paths(&paths)),
+      None => Ok(Self::default()),
+    }
+  }
+
+  /// Add the days of a daily data CSV export. A date already loaded from
+  /// another file is skipped, so overlapping exports aren't counted twice.
+  /// Rows without a valid date are skipped too.
+  pub fn add(&mut self, csv: &str) -> Result<(), ClimateError> {
+    let mut lines = csv.trim_start_matches('\u{feff}').lines();
+    let header = fields(lines.next().unwrap_or_default());
+    let column = |name: &'static str| {
+      header
+        .iter()
+        .position(|field| field == name)
+        .ok_or(ClimateError::MissingColumn(name))
+    };
+    let (year, month, day) = (column(YEAR)?, column(MONTH)?, column(DAY)?);
+    let (max, min) = (column(MAX_TEMP)?, column(MIN_TEMP)?);
+
+    for line in lines {
+      let row = fields(line);
+      let number = |index: usize| -> Option<f32> {
+        row.get(index).and_then(|field| field.trim().parse().ok())
+      };
+      let Some(date) =
+        number(year)
+          .zip(number(month).zip(number(day)))
+          .and_then(|(year, (month, day))| {
+            NaiveDate::from_ymd_opt(year as i32, month as u32, day as u32)
+          })
+      else {
+        continue;
+      };
+      let (high, low) = (number(max), number(min));
+      if (high.is_none() && low.is_none()) || !self.dates.insert(date) {
+        continue;
+      }
+
+      let days = self.days.entry((date.month(), date.day())).or_default();
+      days.highs.extend(high.map(|high| (date.year(), high)));
+      days.lows.extend(low.map(|low| (date.year(), low)));
+      days.years += 1;
+    }
+    Ok(())
+  }
+
+  /// Whether no day has been loaded.
+  pub fn is_empty(&self) -> bool {
+    self.days.is_empty()
+  }
+
+  /// Normals of the calendar day of `date`. February 29 falls back to
+  /// February 28 when no leap year was loaded.
+  pub fn day(&self, date: NaiveDate) -> Option<DayNormals> {
+    let key = (date.month(), date.day());
+    self
+      .days
+      .get(&key)
+      .or_else(|| (key == (2, 29)).then(|| self.days.get(&(2, 28))).flatten())
+      .map(Accumulator::normals)
+  }
+
+  /// How a forecast for `date` compares with the normal high of a day or
+  /// the normal low of a night.
+  pub fn departure(&self, date: NaiveDate, forecast: &Forecast) -> Option<Departure> {
+    let normals = self.day(date)?;
+    let celsius = forecast.celsius.value();
+    let (normal, record) = match forecast.day {
+      DayNight::Day => (
+        normals.high?,
+        normals
+          .record_high
+          .is_some_and(|record| celsius > record.celsius),
+      ),
+      DayNight::Night => (
+        normals.low?,
+        normals
+          .record_low
+          .is_some_and(|record| celsius < record.celsius),
+      ),
+    };
+    Some(Departure {
+      normal,
+      departure: celsius - normal,
+      record,
+    })
+  }
+
+  /// Set the departure from normal of every future forecast, dated from the
+  /// time its entry was updated in the location's time zone `tz`.
+  pub fn annotate<Tz: TimeZone>(&self, entries: &mut [ForecastEntry], tz: &Tz) {
+    for entry in entries {
+      let ForecastEntry::Future { day, night, .. } = entry else {
+        continue;
+      };
+      for ForecastWithEntry { forecast, entry } in day.iter_mut().chain(night.iter_mut()) {
+        forecast.normal = updated_on(entry, tz)
+          .and_then(|issued| self.departure(forecast_date(issued, forecast), forecast));
+      }
+    }
+  }
+}
+
+impl std::str::FromStr for Normals {
+  type Err = ClimateError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut normals = Self::default();
+    normals.add(s)?;
+    Ok(normals)
+  }
+}
+
+/// Fields of a CSV line, without their quotes. A quote within a quoted field
+/// is doubled. Fields never span lines in the exports.
+fn fields(line: &str) -> Vec<String> {
+  let mut fields = vec![];
+  let mut field = String::new();
+  let mut quoted = false;
+  let mut chars = line.chars().peekable();
+  while let Some(c) = chars.next() {
+    match c {
+      '"' if quoted && chars.peek() == Some(&'"') => {
+        field.push('"');
+        chars.next();
+      }
+      '"' => quoted = !quoted,
+      ',' if !quoted => fields.push(std::mem::take(&mut field)),
+      c => field.push(c),
+    }
+  }
+  fields.push(field);
+  fields
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::types::feed_forecast;
+  use chrono::Utc;
+
+  const CSV: &str = include_str!("../fixtures/climate_daily_6105978.csv");
+
+  #[test]
+  fn test_fields() {
+    assert_eq!(
+      fields(r#""OTTAWA CDA RCS","","say ""hi"", twice",-75.67"#),
+      vec!["OTTAWA CDA RCS", "", r#"say "hi", twice"#, "-75.67"]
+    );
+  }
+
+  #[test]
+  fn test_normals() {
+    let normals = CSV.parse::<Normals>().unwrap();
+    let day = normals
+      .day(NaiveDate::from_ymd_opt(2023, 1, 8).unwrap())
+      .unwrap();
+    assert_eq!(day.high, Some(-6.));
+    assert_eq!(day.low, Some(-13.));
+    assert_eq!(
+      day.record_high,
+      Some(Record {
+        celsius: -5.,
+        year: 2020
+      })
+    );
+    assert_eq!(
+      day.record_low,
+      Some(Record {
+        celsius: -15.,
+        year: 2021
+      })
+    );
+    assert_eq!(day.years, 3);
+    assert!(normals
+      .day(NaiveDate::from_ymd_opt(2023, 7, 1).unwrap())
+      .is_none());
+
+    // The same export twice counts each day once.
+    let mut twice = normals.clone();
+    twice.add(CSV).unwrap();
+    assert_eq!(
+      twice.day(NaiveDate::from_ymd_opt(2023, 1, 8).unwrap()),
+      Some(day)
+    );
+
+    assert!(matches!(
+      "\"Year\",\"Month\",\"Day\"".parse::<Normals>(),
+      Err(ClimateError::MissingColumn(MAX_TEMP))
+    ));
+  }
+
+  #[test]
+  fn test_annotate() {
+    let normals = CSV.parse::<Normals>().unwrap();
+    let mut entries = feed_forecast(include_str!("../fixtures/qc-58_e.xml"));
+    normals.annotate(&mut entries, &Utc);
+
+    let departures = entries
+      .iter()
+      .filter_map(|entry| match entry {
+        ForecastEntry::Future { day, night, .. } => Some(day.iter().chain(night.iter())),
+        _ => None,
+      })
+      .flatten()
+      .map(|period| {
+        let forecast = &period.forecast;
+        (forecast.day_of_week, forecast.day, forecast.normal)
+      })
+      .collect::<Vec<_>>();
+
+    use crate::types::DayOfWeek::*;
+    let departure = |normal, departure, record| {
+      Some(Departure {
+        normal,
+        departure,
+        record,
+      })
+    };
+    // Saturday January 7: low minus 5 against a normal low of minus 14.
+    assert!(departures.contains(&(Saturday, DayNight::Night, departure(-14., 9., false))));
+    // Sunday January 8: high minus 2, above the record of minus 5.
+    assert!(departures.contains(&(Sunday, DayNight::Day, departure(-6., 4., true))));
+    // Wednesday January 11: low minus 18 beats the record of minus 17.
+    assert!(departures.contains(&(Wednesday, DayNight::Night, departure(-16., -2., true))));
+    assert!(departures.contains(&(Friday, DayNight::Day, departure(-6., -1., false))));
+
+    let mut entries = feed_forecast(include_str!("../fixtures/qc-58_e.xml"));
+    Normals::default().annotate(&mut entries, &Utc);
+    assert!(entries.iter().all(|entry| match entry {
+      ForecastEntry::Future { day, night, .. } => day
+        .iter()
+        .chain(night.iter())
+        .all(|period| period.forecast.normal.is_none()),
+      _ => true,
+    }));
+  }
+}
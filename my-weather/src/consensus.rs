@@ -0,0 +1,514 @@
+//! Consensus of the forecasts of several providers, period by period.
+//!
+//! The merged forecast is a [`ForeCast`] like any other, with the spread of
+//! the sources written into each period's summary, so whatever shows a
+//! forecast also shows how much the sources agree.
+#[cfg(feature = "chrono")]
+use crate::dates::{forecast_date, parse_time};
+use crate::{
+  diff::{periods, Period},
+  l10n::format_number,
+  parsers::parse_title_language,
+  provider::{feed, forecast_entry},
+  types::{to_forecast, DayNight, Forecast, ForecastEntry},
+  Entry, ForeCast, Language,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Spread in degrees above which the sources of a period disagree.
+pub const DEFAULT_THRESHOLD: f32 = 3.;
+
+/// What one source forecast for a period.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceForecast {
+  pub source: String,
+  pub celsius: f32,
+  pub description: String,
+  pub pop: Option<u8>,
+  /// Language the source words its conditions in, when known.
+  pub language: Option<Language>,
+}
+
+/// The combined forecast of the sources covering a period.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeriodConsensus {
+  pub period: Period,
+  pub sources: Vec<SourceForecast>,
+  /// Median of the source temperatures, to the nearest degree.
+  pub celsius: f32,
+  /// Difference between the warmest and the coldest source.
+  pub spread: f32,
+  /// Condition given by most sources in the language of the merge, worded as
+  /// the first of them words it. Empty when no source is in that language.
+  pub condition: String,
+  /// Highest probability of precipitation of any source.
+  pub pop: Option<u8>,
+  /// The spread is above the threshold.
+  pub disagreement: bool,
+}
+
+impl PeriodConsensus {
+  fn new(period: Period, sources: Vec<SourceForecast>, threshold: f32, language: Language) -> Self {
+    let mut temperatures = sources
+      .iter()
+      .map(|source| source.celsius)
+      .collect::<Vec<_>>();
+    temperatures.sort_by(f32::total_cmp);
+    let middle = temperatures.len() / 2;
+    let median = if temperatures.len() % 2 == 0 {
+      (temperatures[middle - 1] + temperatures[middle]) / 2.
+    } else {
+      temperatures[middle]
+    };
+    let spread = temperatures[temperatures.len() - 1] - temperatures[0];
+
+    // Count conditions regardless of case and final period, keeping the first
+    // wording seen; ties go to the earlier source. Conditions in another
+    // language can't be compared, nor shown.
+    let mut conditions: Vec<(String, usize, &str)> = vec![];
+    for source in sources
+      .iter()
+      .filter(|source| source.language.is_none_or(|known| known == language))
+    {
+      let description = source.description.trim().trim_end_matches('.');
+      let key = description.to_lowercase();
+      match conditions.iter_mut().find(|(seen, ..)| *seen == key) {
+        Some((_, count, _)) => *count += 1,
+        None => conditions.push((key, 1, description)),
+      }
+    }
+    let condition = conditions
+      .iter()
+      .fold(
+        None,
+        |best: Option<&(String, usize, &str)>, candidate| match best {
+          Some(best) if best.1 >= candidate.1 => Some(best),
+          _ => Some(candidate),
+        },
+      )
+      .map(|(.., description)| description.to_string())
+      .unwrap_or_default();
+
+    Self {
+      period,
+      celsius: median.round(),
+      spread: (spread * 10.).round() / 10.,
+      condition,
+      pop: sources.iter().filter_map(|source| source.pop).max(),
+      disagreement: spread > threshold,
+      sources,
+    }
+  }
+
+  /// Sentence added to the period summary, e.g. "Consensus of 3 sources,
+  /// spread 2°C."
+  fn note(&self, language: Language) -> String {
+    let count = self.sources.len();
+    let spread = format_number(self.spread, language);
+    let plural = if count == 1 { "" } else { "s" };
+    match (language, self.disagreement) {
+      (Language::English, false) => {
+        format!("Consensus of {count} source{plural}, spread {spread}°C.")
+      }
+      (Language::English, true) => {
+        format!("Consensus of {count} source{plural}, spread {spread}°C. Sources disagree.")
+      }
+      (Language::French, false) => {
+        format!("Consensus de {count} source{plural}, écart de {spread}°C.")
+      }
+      (Language::French, true) => {
+        format!("Consensus de {count} source{plural}, écart de {spread}°C. Les sources divergent.")
+      }
+    }
+  }
+}
+
+impl std::fmt::Display for PeriodConsensus {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "{}: {}°C, spread {}°C (",
+      self.period, self.celsius, self.spread
+    )?;
+    for (index, source) in self.sources.iter().enumerate() {
+      if index > 0 {
+        write!(f, ", ")?;
+      }
+      write!(f, "{} {}°C", source.source, source.celsius)?;
+    }
+    write!(f, ")")
+  }
+}
+
+/// Forecasts of several sources merged into one.
+#[derive(Debug, Clone)]
+pub struct Consensus {
+  pub periods: Vec<PeriodConsensus>,
+  /// The merged forecast, titled like a city feed.
+  pub feed: ForeCast,
+}
+
+impl Consensus {
+  /// The merged forecast, grouped by day of the week like [`to_forecast`]
+  /// groups a feed. [`merge`] keeps a week at most, so no two days collide.
+  pub fn entries(&self) -> Vec<ForecastEntry> {
+    to_forecast(self.feed.clone().entries())
+  }
+
+  /// Periods where the sources disagree by more than the threshold.
+  pub fn disagreements(&self) -> impl Iterator<Item = &PeriodConsensus> {
+    self.periods.iter().filter(|period| period.disagreement)
+  }
+}
+
+/// Day a period falls on, counted from the Common Era, when the date its
+/// source was issued on is known.
+type Day = Option<i32>;
+
+/// Days of the periods of a source issued at `updated`.
+///
+/// Sources are issued west of Greenwich, so the first period falls on the day
+/// before the UTC date of issue at the earliest, and each period on or after
+/// the one before it.
+#[cfg(feature = "chrono")]
+fn days(updated: Option<&str>, periods: &[(Period, &Forecast)]) -> Vec<Day> {
+  use chrono::{Datelike, Duration};
+
+  let Some(issued) = updated.and_then(parse_time) else {
+    return vec![None; periods.len()];
+  };
+  let mut date = issued.date_naive() - Duration::days(1);
+  periods
+    .iter()
+    .map(|(_, forecast)| {
+      date = forecast_date(date, forecast);
+      Some(date.num_days_from_ce())
+    })
+    .collect()
+}
+
+#[cfg(not(feature = "chrono"))]
+fn days(_updated: Option<&str>, periods: &[(Period, &Forecast)]) -> Vec<Day> {
+  vec![None; periods.len()]
+}
+
+/// Merge the forecasts of named sources, aligning their periods by date and
+/// day or night.
+///
+/// Periods are dated from the time each source was updated. When a source
+/// has no such time, or dates are not available, all periods are aligned by
+/// day of the week instead. Conditions are only taken from sources in
+/// `language`.
+///
+/// Periods are kept in date order or, without dates, in forecast order, a
+/// period only some sources cover being placed after the period preceding it
+/// in those sources. As periods are titled by day of the week, dated periods
+/// a week or more after the first are left out. Warnings of all sources are
+/// kept once each, and current conditions are those of the first source
+/// reporting them. A period is flagged as a disagreement when its
+/// temperatures are more than `threshold` degrees apart.
+pub fn merge(
+  sources: &[(&str, &[ForecastEntry])],
+  threshold: f32,
+  language: Language,
+) -> Consensus {
+  let mut order: Vec<(Day, Period)> = vec![];
+  let mut by_period: HashMap<(Day, Period), Vec<SourceForecast>> = HashMap::new();
+  let mut warnings: Vec<Entry> = vec![];
+  let mut current = None;
+  let mut updated = None;
+
+  let mut dated = vec![];
+  for (source, entries) in sources {
+    let mut source_updated = None;
+    let mut source_language = None;
+    for entry in entries.iter() {
+      match entry {
+        ForecastEntry::Warning(entry) => {
+          if !warnings.iter().any(|warning| warning.title == entry.title) {
+            warnings.push(entry.clone());
+          }
+        }
+        ForecastEntry::Current(with_entry) => {
+          current.get_or_insert_with(|| with_entry.entry.clone());
+        }
+        ForecastEntry::Future { day, night, .. } => {
+          for with_entry in [day, night].into_iter().flatten() {
+            if source_updated.is_none() {
+              source_updated = with_entry.entry.updated.clone();
+            }
+            if source_language.is_none() {
+              source_language = parse_title_language(&with_entry.entry.title);
+            }
+          }
+        }
+      }
+    }
+    if updated.is_none() {
+      updated = source_updated.clone();
+    }
+
+    let periods = periods(entries);
+    let days = days(source_updated.as_deref(), &periods);
+    dated.push((source, source_language, periods, days));
+  }
+
+  let by_date = dated
+    .iter()
+    .all(|(.., days)| days.iter().all(Option::is_some));
+  for (source, language, periods, days) in dated {
+    // Without dates, a week long forecast can come back to its first day of
+    // the week, which is dropped rather than mixed with the first.
+    let mut seen = HashSet::new();
+    let mut previous = None;
+    for ((period, forecast), day) in periods.into_iter().zip(days) {
+      let period = (if by_date { day } else { None }, period);
+      if !seen.insert(period) {
+        continue;
+      }
+      if !order.contains(&period) {
+        let at = previous
+          .and_then(|previous| order.iter().position(|known| *known == previous))
+          .map_or(0, |index| index + 1);
+        order.insert(at, period);
+      }
+      previous = Some(period);
+      by_period.entry(period).or_default().push(SourceForecast {
+        source: source.to_string(),
+        celsius: forecast.celsius.value(),
+        description: forecast.description.clone(),
+        pop: forecast.pop,
+        language,
+      });
+    }
+  }
+
+  if by_date {
+    order.sort_by_key(|(day, period)| (*day, period.day == DayNight::Night));
+    if let Some(&(Some(first), _)) = order.first() {
+      order.retain(|(day, _)| day.is_some_and(|day| day < first + 7));
+    }
+  }
+  let periods = order
+    .into_iter()
+    .filter_map(|period| {
+      let sources = by_period.remove(&period)?;
+      Some(PeriodConsensus::new(period.1, sources, threshold, language))
+    })
+    .collect::<Vec<_>>();
+
+  let mut entries = warnings;
+  entries.extend(current);
+  entries.extend(periods.iter().map(|consensus| {
+    let mut entry = forecast_entry(
+      language,
+      consensus.period.day_of_week,
+      consensus.period.day,
+      &consensus.condition,
+      consensus.celsius,
+      consensus.pop,
+      None,
+      updated.clone(),
+    );
+    entry.summary = format!("{} {}", entry.summary, consensus.note(language));
+    entry
+  }));
+
+  Consensus {
+    periods,
+    feed: feed(updated, entries),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::types::{feed_forecast, DayOfWeek, ForecastWithEntry, Temperature};
+  use std::marker::PhantomData;
+
+  fn saturday_night() -> Period {
+    Period {
+      day_of_week: DayOfWeek::Saturday,
+      day: DayNight::Night,
+    }
+  }
+
+  #[test]
+  fn test_single_source() {
+    let ec = feed_forecast(include_str!("../fixtures/qc-58_e.xml"));
+    let consensus = merge(&[("ec", &ec)], DEFAULT_THRESHOLD, Language::English);
+
+    assert_eq!(consensus.periods.len(), 12);
+    assert_eq!(consensus.disagreements().count(), 0);
+    let first = &consensus.periods[0];
+    assert_eq!(first.period, saturday_night());
+    assert_eq!(first.celsius, -5.);
+    assert_eq!(first.spread, 0.);
+    assert_eq!(first.condition, "Periods of snow");
+
+    let entries = consensus.entries();
+    assert_eq!(entries.len(), ec.len());
+    assert!(matches!(entries[0], ForecastEntry::Current(_)));
+    let ForecastEntry::Future {
+      night: Some(ForecastWithEntry { forecast, entry }),
+      ..
+    } = &entries[1]
+    else {
+      panic!("expected Saturday night, got {:?}", entries[1]);
+    };
+    assert_eq!(forecast.celsius, Temperature::Low(-5., PhantomData));
+    assert_eq!(
+      entry.summary,
+      "Periods of snow. Low minus 5. Consensus of 1 source, spread 0°C."
+    );
+  }
+
+  #[test]
+  fn test_disagreement() {
+    let feed = include_str!("../fixtures/qc-58_e.xml");
+    let ec = feed_forecast(feed);
+    let colder = feed_forecast(&feed.replace(
+      "Saturday night: Periods of snow. Low minus 5.",
+      "Saturday night: Snow. Low minus 9. POP 80%",
+    ));
+    let warmer = feed_forecast(&feed.replace(
+      "Saturday night: Periods of snow. Low minus 5.",
+      "Saturday night: Snow. Low minus 4.",
+    ));
+    let consensus = merge(
+      &[("ec", &ec), ("colder", &colder), ("warmer", &warmer)],
+      DEFAULT_THRESHOLD,
+      Language::English,
+    );
+
+    let disagreements = consensus.disagreements().collect::<Vec<_>>();
+    assert_eq!(disagreements.len(), 1);
+    let saturday = disagreements[0];
+    assert_eq!(saturday.period, saturday_night());
+    assert_eq!(saturday.celsius, -5.);
+    assert_eq!(saturday.spread, 5.);
+    assert_eq!(saturday.condition, "Snow");
+    assert_eq!(saturday.pop, Some(80));
+    assert_eq!(
+      saturday.to_string(),
+      "Saturday night: -5°C, spread 5°C (ec -5°C, colder -9°C, warmer -4°C)"
+    );
+
+    let titles = consensus
+      .feed
+      .clone()
+      .entries()
+      .map(|entry| entry.title)
+      .collect::<Vec<_>>();
+    assert!(titles.contains(&"Saturday night: Snow. Low minus 5. POP 80%".to_string()));
+    let summary = consensus
+      .feed
+      .entries()
+      .find(|entry| entry.title.starts_with("Saturday night"))
+      .unwrap()
+      .summary;
+    assert!(summary.ends_with("Consensus of 3 sources, spread 5°C. Sources disagree."));
+  }
+
+  #[test]
+  fn test_alignment() {
+    let ec = feed_forecast(include_str!("../fixtures/qc-58_e.xml"));
+    // A source starting a period later, merged first.
+    let later = ec
+      .iter()
+      .filter(|entry| {
+        !matches!(
+          entry,
+          ForecastEntry::Future {
+            day: None,
+            night: Some(_),
+            ..
+          }
+        )
+      })
+      .cloned()
+      .collect::<Vec<_>>();
+    let consensus = merge(
+      &[("later", &later), ("ec", &ec)],
+      DEFAULT_THRESHOLD,
+      Language::French,
+    );
+
+    let order = consensus
+      .periods
+      .iter()
+      .map(|consensus| consensus.period)
+      .collect::<Vec<_>>();
+    let expected = periods(&ec)
+      .into_iter()
+      .map(|(period, _)| period)
+      .collect::<Vec<_>>();
+    assert_eq!(order, expected);
+    assert_eq!(consensus.periods[0].sources.len(), 1);
+    assert_eq!(consensus.periods[1].sources.len(), 2);
+    let summary = consensus
+      .feed
+      .entries()
+      .find(|entry| entry.title.starts_with("Samedi soir et nuit"))
+      .unwrap()
+      .summary;
+    // No source words its conditions in French.
+    assert_eq!(consensus.periods[0].condition, "");
+    assert_eq!(
+      summary,
+      "Minimum moins 5. Consensus de 1 source, écart de 0°C."
+    );
+  }
+
+  #[test]
+  fn test_languages() {
+    let ec = feed_forecast(include_str!("../fixtures/qc-58_e.xml"));
+    let fr = feed_forecast(include_str!("../fixtures/qc-58_f.xml"));
+    let consensus = merge(
+      &[("ec", &ec), ("fr", &fr)],
+      DEFAULT_THRESHOLD,
+      Language::French,
+    );
+
+    let first = &consensus.periods[0];
+    assert_eq!(first.sources.len(), 2);
+    assert_eq!(first.sources[0].language, Some(Language::English));
+    assert_eq!(first.sources[1].language, Some(Language::French));
+    assert_eq!(first.condition, "Neige intermittente");
+    let summary = consensus
+      .feed
+      .entries()
+      .find(|entry| entry.title.starts_with("Samedi soir et nuit"))
+      .unwrap()
+      .summary;
+    assert_eq!(
+      summary,
+      "Neige intermittente. Minimum moins 5. Consensus de 2 sources, écart de 0°C."
+    );
+  }
+
+  #[cfg(feature = "chrono")]
+  #[test]
+  fn test_dates() {
+    let feed = include_str!("../fixtures/qc-58_e.xml");
+    let ec = feed_forecast(feed);
+    // The same days of the week, a week later.
+    let next_week = feed_forecast(&feed.replace("2023-01-07T", "2023-01-14T"));
+    let consensus = merge(
+      &[("ec", &ec), ("next week", &next_week)],
+      DEFAULT_THRESHOLD,
+      Language::English,
+    );
+
+    // Next week's periods fall on the same days of the week as this week's,
+    // and are left out rather than mixed with them.
+    assert_eq!(consensus.periods.len(), 12);
+    assert!(consensus
+      .periods
+      .iter()
+      .all(|period| period.sources.len() == 1 && period.sources[0].source == "ec"));
+    assert_eq!(consensus.periods[0].period, saturday_night());
+    assert_eq!(consensus.entries().len(), ec.len());
+  }
+}
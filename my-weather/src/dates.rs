@@ -0,0 +1,62 @@
+//! Dates of feed entries and of the forecast periods they describe.
+use crate::{
+  types::{DayOfWeek, Forecast},
+  Entry,
+};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc, Weekday};
+
+/// Parse an RFC 3339 feed timestamp.
+pub(crate) fn parse_time(time: &str) -> Option<DateTime<Utc>> {
+  DateTime::parse_from_rfc3339(time)
+    .ok()
+    .map(|time| time.with_timezone(&Utc))
+}
+
+/// Day of the week of a date or time starting with `yyyy-mm-dd`.
+pub(crate) fn day_of_week(date: &str) -> Option<DayOfWeek> {
+  let date = NaiveDate::parse_from_str(date.get(..10)?, "%Y-%m-%d").ok()?;
+  Some(match date.weekday() {
+    Weekday::Mon => DayOfWeek::Monday,
+    Weekday::Tue => DayOfWeek::Tuesday,
+    Weekday::Wed => DayOfWeek::Wednesday,
+    Weekday::Thu => DayOfWeek::Thursday,
+    Weekday::Fri => DayOfWeek::Friday,
+    Weekday::Sat => DayOfWeek::Saturday,
+    Weekday::Sun => DayOfWeek::Sunday,
+  })
+}
+
+/// First date on or after `issued` falling on the forecast's day of the week.
+pub(crate) fn forecast_date(issued: NaiveDate, forecast: &Forecast) -> NaiveDate {
+  let target = forecast.day_of_week as i64;
+  let issued_day = issued.weekday().num_days_from_monday() as i64;
+  issued + Duration::days((target - issued_day).rem_euclid(7))
+}
+
+/// Local date in `tz` an entry was updated on.
+pub(crate) fn updated_on<Tz: TimeZone>(entry: &Entry, tz: &Tz) -> Option<NaiveDate> {
+  entry
+    .updated
+    .as_deref()
+    .and_then(parse_time)
+    .map(|time| time.with_timezone(tz).date_naive())
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::parsers::parse_forecast;
+
+  #[test]
+  fn test_forecast_date() {
+    let (_, forecast) = parse_forecast("Sunday: Cloudy. High minus 2.").unwrap();
+    let saturday = NaiveDate::from_ymd_opt(2023, 1, 7).unwrap();
+    assert_eq!(
+      forecast_date(saturday, &forecast),
+      NaiveDate::from_ymd_opt(2023, 1, 8).unwrap()
+    );
+
+    let (_, forecast) = parse_forecast("Saturday night: Snow. Low minus 5.").unwrap();
+    assert_eq!(forecast_date(saturday, &forecast), saturday);
+  }
+}
@@ -0,0 +1,311 @@
+//! Changes between two refreshes of a forecast.
+use crate::types::{
+  Celsius, CurrentForecast, DayNight, DayOfWeek, Forecast, ForecastEntry, ForecastWithEntry,
+  Temperature,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A day or night forecast period.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Period {
+  pub day_of_week: DayOfWeek,
+  pub day: DayNight,
+}
+
+impl std::fmt::Display for Period {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self.day {
+      DayNight::Day => write!(f, "{}", self.day_of_week.as_str()),
+      DayNight::Night => write!(f, "{} night", self.day_of_week.as_str()),
+    }
+  }
+}
+
+/// A difference between an older and a newer forecast.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "content")]
+pub enum Change {
+  Temperature {
+    period: Period,
+    old: Temperature<Celsius>,
+    new: Temperature<Celsius>,
+  },
+  Description {
+    period: Period,
+    old: String,
+    new: String,
+  },
+  Pop {
+    period: Period,
+    old: Option<u8>,
+    new: Option<u8>,
+  },
+  /// A period at the end of the forecast that wasn't in the older one.
+  PeriodAdded(Period),
+  /// A period that has passed and is no longer forecast.
+  PeriodRolledOff(Period),
+  CurrentTemperature {
+    old: Option<Temperature<Celsius>>,
+    new: Option<Temperature<Celsius>>,
+  },
+  CurrentDescription {
+    old: Option<String>,
+    new: Option<String>,
+  },
+  WarningAdded(String),
+  WarningEnded(String),
+}
+
+impl Change {
+  /// Change in degrees for a temperature change.
+  pub fn delta(&self) -> Option<f32> {
+    match self {
+      Self::Temperature { old, new, .. } => Some(new.value() - old.value()),
+      Self::CurrentTemperature {
+        old: Some(old),
+        new: Some(new),
+      } => Some(new.value() - old.value()),
+      _ => None,
+    }
+  }
+}
+
+impl std::fmt::Display for Change {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let optional = |value: Option<String>| value.unwrap_or_else(|| "not reported".into());
+    let pop = |pop: &Option<u8>| {
+      pop
+        .map(|pop| format!("{pop}%"))
+        .unwrap_or_else(|| "none".into())
+    };
+
+    match self {
+      Self::Temperature { period, old, new } => write!(
+        f,
+        "{period}: {old} → {new} ({:+})",
+        self.delta().unwrap_or_default()
+      ),
+      Self::Description { period, old, new } => write!(f, "{period}: {old} → {new}"),
+      Self::Pop { period, old, new } => write!(f, "{period}: POP {} → {}", pop(old), pop(new)),
+      Self::PeriodAdded(period) => write!(f, "{period}: added"),
+      Self::PeriodRolledOff(period) => write!(f, "{period}: rolled off"),
+      Self::CurrentTemperature { old, new } => write!(
+        f,
+        "Current: {} → {}",
+        optional(old.as_ref().map(|t| t.to_string())),
+        optional(new.as_ref().map(|t| t.to_string()))
+      ),
+      Self::CurrentDescription { old, new } => write!(
+        f,
+        "Current: {} → {}",
+        optional(old.clone()),
+        optional(new.clone())
+      ),
+      Self::WarningAdded(title) => write!(f, "Warning added: {title}"),
+      Self::WarningEnded(title) => write!(f, "Warning ended: {title}"),
+    }
+  }
+}
+
+/// Forecast periods keyed by period, in forecast order.
+pub(crate) fn periods(entries: &[ForecastEntry]) -> Vec<(Period, &Forecast)> {
+  entries
+    .iter()
+    .filter_map(|entry| match entry {
+      ForecastEntry::Future { day, night, .. } => Some([day, night]),
+      _ => None,
+    })
+    .flatten()
+    .flatten()
+    .map(|ForecastWithEntry { forecast, .. }| {
+      (
+        Period {
+          day_of_week: forecast.day_of_week,
+          day: forecast.day,
+        },
+        forecast,
+      )
+    })
+    .collect()
+}
+
+fn current(entries: &[ForecastEntry]) -> Option<&CurrentForecast> {
+  entries.iter().find_map(|entry| match entry {
+    ForecastEntry::Current(current) => Some(&current.current),
+    _ => None,
+  })
+}
+
+fn warnings(entries: &[ForecastEntry]) -> Vec<&str> {
+  entries
+    .iter()
+    .filter_map(|entry| match entry {
+      ForecastEntry::Warning(entry) => Some(entry.title.as_str()),
+      _ => None,
+    })
+    .collect()
+}
+
+/// Changes from an older to a newer [`to_forecast`](crate::types::to_forecast)
+/// output: current conditions first, then warnings, then periods in forecast
+/// order.
+pub fn diff(old: &[ForecastEntry], new: &[ForecastEntry]) -> Vec<Change> {
+  let mut changes = vec![];
+
+  if let (Some(old), Some(new)) = (current(old), current(new)) {
+    if old.celsius != new.celsius {
+      changes.push(Change::CurrentTemperature {
+        old: old.celsius.clone(),
+        new: new.celsius.clone(),
+      });
+    }
+    if old.description != new.description {
+      changes.push(Change::CurrentDescription {
+        old: old.description.clone(),
+        new: new.description.clone(),
+      });
+    }
+  }
+
+  let (old_warnings, new_warnings) = (warnings(old), warnings(new));
+  changes.extend(
+    new_warnings
+      .iter()
+      .filter(|title| !old_warnings.contains(title))
+      .map(|title| Change::WarningAdded(title.to_string())),
+  );
+  changes.extend(
+    old_warnings
+      .iter()
+      .filter(|title| !new_warnings.contains(title))
+      .map(|title| Change::WarningEnded(title.to_string())),
+  );
+
+  let old_periods = periods(old);
+  let new_periods = periods(new);
+  let new_by_period = new_periods.iter().copied().collect::<HashMap<_, _>>();
+  let old_by_period = old_periods.iter().copied().collect::<HashMap<_, _>>();
+
+  for (period, old) in &old_periods {
+    let Some(new) = new_by_period.get(period) else {
+      changes.push(Change::PeriodRolledOff(*period));
+      continue;
+    };
+    if old.celsius != new.celsius {
+      changes.push(Change::Temperature {
+        period: *period,
+        old: old.celsius.clone(),
+        new: new.celsius.clone(),
+      });
+    }
+    if old.description != new.description {
+      changes.push(Change::Description {
+        period: *period,
+        old: old.description.clone(),
+        new: new.description.clone(),
+      });
+    }
+    if old.pop != new.pop {
+      changes.push(Change::Pop {
+        period: *period,
+        old: old.pop,
+        new: new.pop,
+      });
+    }
+  }
+  changes.extend(
+    new_periods
+      .iter()
+      .filter(|(period, _)| !old_by_period.contains_key(period))
+      .map(|(period, _)| Change::PeriodAdded(*period)),
+  );
+
+  changes
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::types::feed_forecast;
+  use std::marker::PhantomData;
+
+  #[test]
+  fn test_no_changes() {
+    let feed = feed_forecast(include_str!("../fixtures/qc-58_e.xml"));
+    assert!(diff(&feed, &feed).is_empty());
+  }
+
+  #[test]
+  fn test_changes() {
+    let old = include_str!("../fixtures/qc-58_e.xml");
+    let new = old
+      .replace(
+        "Saturday night: Periods of snow. Low minus 5.",
+        "Saturday night: Snow. Low minus 8.",
+      )
+      .replace("High minus 3. POP 60%", "High minus 3. POP 80%")
+      .replace("Light Snow, -3.4", "Light Snow, -4.0")
+      .replace(
+        "No watches or warnings in effect, Gatineau",
+        "Snowfall warning in effect, Gatineau",
+      );
+
+    let saturday_night = Period {
+      day_of_week: DayOfWeek::Saturday,
+      day: DayNight::Night,
+    };
+    let changes = diff(&feed_forecast(old), &feed_forecast(&new));
+    assert_eq!(
+      changes,
+      vec![
+        Change::CurrentTemperature {
+          old: Some(Temperature::Current(-3.4, PhantomData)),
+          new: Some(Temperature::Current(-4.0, PhantomData)),
+        },
+        Change::WarningAdded("Snowfall warning in effect, Gatineau".into()),
+        Change::Temperature {
+          period: saturday_night,
+          old: Temperature::Low(-5., PhantomData),
+          new: Temperature::Low(-8., PhantomData),
+        },
+        Change::Description {
+          period: saturday_night,
+          old: "Periods of snow.".into(),
+          new: "Snow.".into(),
+        },
+        Change::Pop {
+          period: Period {
+            day_of_week: DayOfWeek::Monday,
+            day: DayNight::Day,
+          },
+          old: Some(60),
+          new: Some(80),
+        },
+      ]
+    );
+    assert_eq!(changes[2].delta(), Some(-3.));
+    assert_eq!(changes[2].to_string(), "Saturday night: -5°C → -8°C (-3)");
+  }
+
+  #[test]
+  fn test_rolled_off() {
+    let old = feed_forecast(include_str!("../fixtures/qc-58_e.xml"));
+    let mut new = old.clone();
+    let first = new
+      .iter()
+      .position(|entry| matches!(entry, ForecastEntry::Future { .. }))
+      .unwrap();
+    new.remove(first);
+
+    let saturday_night = Period {
+      day_of_week: DayOfWeek::Saturday,
+      day: DayNight::Night,
+    };
+    assert_eq!(
+      diff(&old, &new),
+      vec![Change::PeriodRolledOff(saturday_night)]
+    );
+    assert_eq!(diff(&new, &old), vec![Change::PeriodAdded(saturday_night)]);
+  }
+}
@@ -0,0 +1,241 @@
+//! City feeds by location, to pick the feed nearest to a GPS position.
+//!
+//! The built-in list covers the provincial and territorial capitals and the
+//! largest cities. Other city feeds can be loaded from a TOML list:
+//!
+//! ```toml
+//! [[city]]
+//! code = "on-118"
+//! name = "Ottawa (Kanata - Orléans)"
+//! latitude = 45.42
+//! longitude = -75.70
+//! ```
+use crate::provider::Coordinates;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use thiserror::Error;
+
+/// Mean radius of the earth in km.
+const EARTH_RADIUS: f64 = 6371.;
+
+/// Distance in km beyond which a city's forecast is a poor guess of the
+/// weather elsewhere.
+pub const FAR: f64 = 50.;
+
+/// Built-in city feeds: code, name, latitude and longitude.
+const CITIES: [(&str, &str, f64, f64); 19] = [
+  ("ab-50", "Edmonton", 53.55, -113.49),
+  ("ab-52", "Calgary", 51.05, -114.07),
+  ("bc-74", "Vancouver", 49.28, -123.12),
+  ("bc-85", "Victoria", 48.43, -123.37),
+  ("mb-38", "Winnipeg", 49.90, -97.14),
+  ("nb-29", "Fredericton", 45.96, -66.64),
+  ("nl-24", "St. John's", 47.56, -52.71),
+  ("ns-19", "Halifax", 44.65, -63.57),
+  ("nt-24", "Yellowknife", 62.45, -114.37),
+  ("nu-21", "Iqaluit", 63.75, -68.52),
+  ("on-118", "Ottawa (Kanata - Orléans)", 45.42, -75.70),
+  ("on-143", "Toronto", 43.65, -79.38),
+  ("pe-5", "Charlottetown", 46.24, -63.13),
+  ("qc-133", "Québec", 46.81, -71.21),
+  ("qc-147", "Montréal", 45.50, -73.57),
+  ("qc-58", "Gatineau", 45.48, -75.70),
+  ("sk-32", "Regina", 50.45, -104.61),
+  ("sk-40", "Saskatoon", 52.13, -106.67),
+  ("yt-16", "Whitehorse", 60.72, -135.06),
+];
+
+/// City list loading errors.
+#[derive(Error, Debug)]
+pub enum CityListError {
+  #[error("City list io error {0}")]
+  Io(#[from] std::io::Error),
+  #[error("Invalid city list {0}")]
+  Toml(#[from] toml::de::Error),
+}
+
+/// A city feed and where it is.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct City {
+  /// Feed code, e.g. `qc-147`.
+  pub code: String,
+  pub name: String,
+  #[serde(flatten)]
+  pub coordinates: Coordinates,
+}
+
+/// A city and how far it is, in km.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Nearby<'a> {
+  pub city: &'a City,
+  pub distance: f64,
+}
+
+impl Nearby<'_> {
+  /// The city is more than [`FAR`] away.
+  pub fn is_far(&self) -> bool {
+    self.distance > FAR
+  }
+}
+
+impl std::fmt::Display for Nearby<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "{} ({}), {:.1} km",
+      self.city.name, self.city.code, self.distance
+    )
+  }
+}
+
+/// City feeds searchable by distance. Searches go through every city, which
+/// is quick enough for the few hundred feeds there are.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CityIndex {
+  #[serde(rename = "city", default)]
+  cities: Vec<City>,
+}
+
+impl Default for CityIndex {
+  /// The built-in cities.
+  fn default() -> Self {
+    Self::new(
+      CITIES
+        .iter()
+        .map(|&(code, name, latitude, longitude)| City {
+          code: code.into(),
+          name: name.into(),
+          coordinates: Coordinates {
+            latitude,
+            longitude,
+          },
+        }),
+    )
+  }
+}
+
+impl CityIndex {
+  pub fn new(cities: impl IntoIterator<Item = City>) -> Self {
+    Self {
+      cities: cities.into_iter().collect(),
+    }
+  }
+
+  /// Cities of a TOML list.
+  pub fn load(path: impl AsRef<Path>) -> Result<Self, CityListError> {
+    std::fs::read_to_string(path)?.parse()
+  }
+
+  /// Add cities, replacing those with the same code.
+  pub fn extend(&mut self, cities: impl IntoIterator<Item = City>) {
+    for city in cities {
+      self.cities.retain(|c| c.code != city.code);
+      self.cities.push(city);
+    }
+  }
+
+  pub fn cities(&self) -> &[City] {
+    &self.cities
+  }
+
+  /// The `n` cities nearest to `coordinates`, nearest first.
+  pub fn nearest(&self, coordinates: &Coordinates, n: usize) -> Vec<Nearby<'_>> {
+    let mut nearby = self
+      .cities
+      .iter()
+      .map(|city| Nearby {
+        city,
+        distance: distance(coordinates, &city.coordinates),
+      })
+      .collect::<Vec<_>>();
+    nearby.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+    nearby.truncate(n);
+    nearby
+  }
+}
+
+impl std::str::FromStr for CityIndex {
+  type Err = CityListError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Ok(toml::from_str(s)?)
+  }
+}
+
+/// Great-circle distance between two points in km.
+pub fn distance(from: &Coordinates, to: &Coordinates) -> f64 {
+  let (lat1, lat2) = (from.latitude.to_radians(), to.latitude.to_radians());
+  let half_lat = (lat2 - lat1) / 2.;
+  let half_lon = (to.longitude - from.longitude).to_radians() / 2.;
+  let a = half_lat.sin().powi(2) + lat1.cos() * lat2.cos() * half_lon.sin().powi(2);
+  2. * EARTH_RADIUS * a.sqrt().asin()
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn at(coordinates: &str) -> Coordinates {
+    coordinates.parse().unwrap()
+  }
+
+  #[test]
+  fn test_distance() {
+    let montreal = at("45.50,-73.57");
+    assert_eq!(distance(&montreal, &montreal), 0.);
+    let toronto = distance(&montreal, &at("43.65,-79.38"));
+    assert!((500. ..510.).contains(&toronto), "{toronto}");
+    // Across the antimeridian.
+    let pacific = distance(&at("0,179.5"), &at("0,-179.5"));
+    assert!((pacific - 111.2).abs() < 0.1, "{pacific}");
+  }
+
+  #[test]
+  fn test_nearest() {
+    let index = CityIndex::default();
+    let nearest = index.nearest(&at("45.5,-73.6"), 3);
+    let codes = nearest
+      .iter()
+      .map(|nearby| nearby.city.code.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(codes, ["qc-147", "qc-58", "on-118"]);
+    assert_eq!(nearest[0].to_string(), "Montréal (qc-147), 2.3 km");
+    assert!(!nearest[0].is_far());
+    // Kapuskasing, between Timmins and Thunder Bay.
+    assert!(index.nearest(&at("49.42,-82.43"), 1)[0].is_far());
+    assert!(index.nearest(&at("45.5,-73.6"), 0).is_empty());
+    assert_eq!(index.nearest(&at("45.5,-73.6"), 100).len(), CITIES.len());
+  }
+
+  #[test]
+  fn test_load() {
+    let list = r#"
+[[city]]
+code = "qc-136"
+name = "Sherbrooke"
+latitude = 45.40
+longitude = -71.89
+
+[[city]]
+code = "qc-147"
+name = "Montreal"
+latitude = 45.50
+longitude = -73.57
+"#
+    .parse::<CityIndex>()
+    .unwrap();
+    assert_eq!(list.cities().len(), 2);
+
+    let mut index = CityIndex::default();
+    index.extend(list.cities().to_vec());
+    assert_eq!(index.cities().len(), CITIES.len() + 1);
+    let nearest = index.nearest(&at("45.3,-72"), 1);
+    assert_eq!(nearest[0].city.name, "Sherbrooke");
+    assert_eq!(index.nearest(&at("45.5,-73.6"), 1)[0].city.name, "Montreal");
+
+    assert!(matches!(
+      "[[city]]\ncode = 1".parse::<CityIndex>(),
+      Err(CityListError::Toml(_))
+    ));
+  }
+}
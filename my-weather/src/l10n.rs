@@ -0,0 +1,227 @@
+//! Localized display strings for the forecast model.
+//!
+//! The display language is independent of the feed language, so a French feed
+//! can be shown with English labels and vice versa.
+use crate::{
+  types::{
+    Celsius, DayNight, DayOfWeek, Departure, Fahrenheit, Temperature, TimeQualifier,
+    TimelineSegment,
+  },
+  Language,
+};
+
+/// Render a model value for display in a given language.
+pub trait Localize {
+  fn localize(&self, language: Language) -> String;
+}
+
+impl Localize for DayOfWeek {
+  fn localize(&self, language: Language) -> String {
+    match language {
+      Language::English => self.as_str(),
+      Language::French => match self {
+        Self::Monday => "lundi",
+        Self::Tuesday => "mardi",
+        Self::Wednesday => "mercredi",
+        Self::Thursday => "jeudi",
+        Self::Friday => "vendredi",
+        Self::Saturday => "samedi",
+        Self::Sunday => "dimanche",
+      },
+    }
+    .into()
+  }
+}
+
+impl Localize for DayNight {
+  fn localize(&self, language: Language) -> String {
+    match (language, self) {
+      (Language::English, Self::Day) => "Day",
+      (Language::English, Self::Night) => "Night",
+      (Language::French, Self::Day) => "Jour",
+      (Language::French, Self::Night) => "Nuit",
+    }
+    .into()
+  }
+}
+
+impl Localize for TimeQualifier {
+  fn localize(&self, language: Language) -> String {
+    match language {
+      Language::English => self.as_str(),
+      Language::French => match self {
+        Self::ByMorning => "en matinée",
+        Self::ByNoon => "d'ici midi",
+        Self::ByAfternoon => "d'ici l'après-midi",
+        Self::ByEvening => "d'ici le soir",
+        Self::ByMidnight => "d'ici minuit",
+        Self::InTheMorning => "le matin",
+        Self::InTheAfternoon => "en après-midi",
+        Self::InTheEvening => "en soirée",
+        Self::ThisMorning => "ce matin",
+        Self::ThisAfternoon => "cet après-midi",
+        Self::ThisEvening => "ce soir",
+        Self::Overnight => "au cours de la nuit",
+        Self::AfterMidnight => "après minuit",
+        Self::NearNoon => "vers midi",
+        Self::NearMidnight => "vers minuit",
+        Self::Other(other) => other,
+      },
+    }
+    .into()
+  }
+}
+
+/// Only the times are localized, e.g. "Rain ending in the afternoon" or
+/// "Rain cessant en après-midi"; the condition is kept as worded in the feed.
+impl Localize for TimelineSegment {
+  fn localize(&self, language: Language) -> String {
+    match (&self.start, &self.end, language) {
+      (Some(start), ..) => format!("{} {}", self.condition, start.localize(language)),
+      (None, Some(end), Language::English) => {
+        format!("{} ending {}", self.condition, end.localize(language))
+      }
+      (None, Some(end), Language::French) => {
+        format!("{} cessant {}", self.condition, end.localize(language))
+      }
+      (None, None, _) => self.condition.clone(),
+    }
+  }
+}
+
+/// Conditions in order of occurrence, e.g. "Rain ending in the afternoon →
+/// snow in the afternoon".
+impl Localize for [TimelineSegment] {
+  fn localize(&self, language: Language) -> String {
+    self
+      .iter()
+      .map(|segment| segment.localize(language))
+      .collect::<Vec<_>>()
+      .join(" → ")
+  }
+}
+
+impl Localize for Temperature<Celsius> {
+  fn localize(&self, language: Language) -> String {
+    match language {
+      Language::English => self.to_string(),
+      Language::French => format!("{}\u{a0}°C", format_number(self.value(), language)),
+    }
+  }
+}
+
+impl Localize for Temperature<Fahrenheit> {
+  fn localize(&self, language: Language) -> String {
+    match language {
+      Language::English => self.to_string(),
+      Language::French => format!("{:.0}\u{a0}°F", self.value()),
+    }
+  }
+}
+
+impl Localize for Departure {
+  fn localize(&self, language: Language) -> String {
+    let degrees = self.departure.round();
+    let text = match (language, degrees) {
+      (Language::English, 0.) => "near normal".into(),
+      (Language::French, 0.) => "près de la normale".into(),
+      (Language::English, d) if d > 0. => format!("{d}° above normal"),
+      (Language::French, d) if d > 0. => format!("{d}° au-dessus de la normale"),
+      (Language::English, d) => format!("{}° below normal", -d),
+      (Language::French, d) => format!("{}° sous la normale", -d),
+    };
+    if self.record {
+      format!("{text} (record)")
+    } else {
+      text
+    }
+  }
+}
+
+/// Placeholder for a value a station did not report.
+pub fn not_reported(language: Language) -> &'static str {
+  match language {
+    Language::English => "Not reported",
+    Language::French => "Non disponible",
+  }
+}
+
+/// Format a number with the decimal separator of the language.
+pub fn format_number(n: f32, language: Language) -> String {
+  match language {
+    Language::English => n.to_string(),
+    Language::French => n.to_string().replace('.', ","),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use std::marker::PhantomData;
+
+  #[test]
+  fn test_day_of_week() {
+    assert_eq!(DayOfWeek::Monday.localize(Language::English), "Monday");
+    assert_eq!(DayOfWeek::Monday.localize(Language::French), "lundi");
+    assert_eq!(DayNight::Night.localize(Language::French), "Nuit");
+    assert_eq!(
+      TimeQualifier::InTheAfternoon.localize(Language::French),
+      "en après-midi"
+    );
+  }
+
+  #[test]
+  fn test_timeline() {
+    let timeline = |rain: &str, snow: &str| {
+      [
+        TimelineSegment {
+          condition: rain.into(),
+          start: None,
+          end: Some(TimeQualifier::InTheAfternoon),
+        },
+        TimelineSegment {
+          condition: snow.into(),
+          start: Some(TimeQualifier::InTheAfternoon),
+          end: None,
+        },
+      ]
+    };
+    assert_eq!(
+      timeline("Rain", "snow").localize(Language::English),
+      "Rain ending in the afternoon → snow in the afternoon"
+    );
+    assert_eq!(
+      timeline("Pluie", "neige").localize(Language::French),
+      "Pluie cessant en après-midi → neige en après-midi"
+    );
+  }
+
+  #[test]
+  fn test_temperature() {
+    let temp = Temperature::<Celsius>::Current(-3.4, PhantomData);
+    assert_eq!(temp.localize(Language::English), "-3.4°C");
+    assert_eq!(temp.localize(Language::French), "-3,4\u{a0}°C");
+
+    let temp = Temperature::<Fahrenheit>::High(25.6, PhantomData);
+    assert_eq!(temp.localize(Language::English), "26°F");
+    assert_eq!(temp.localize(Language::French), "26\u{a0}°F");
+  }
+
+  #[test]
+  fn test_departure() {
+    let departure = |departure, record| Departure {
+      normal: -8.,
+      departure,
+      record,
+    };
+    assert_eq!(departure(6.2, false).to_string(), "6° above normal");
+    assert_eq!(
+      departure(-2.6, true).localize(Language::French),
+      "3° sous la normale (record)"
+    );
+    assert_eq!(departure(-0.4, false).to_string(), "near normal");
+    let fahrenheit = departure(5., false).fahrenheit();
+    assert_eq!(fahrenheit.normal, 17.6);
+    assert_eq!(fahrenheit.to_string(), "9° above normal");
+  }
+}
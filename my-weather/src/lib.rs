@@ -1,14 +1,60 @@
 //! Gets weather forecast for Weather Canada RSS feed.
-use serde::{de::Visitor, Deserialize, Deserializer, Serialize};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use thiserror::Error;
 
+#[cfg(feature = "archive")]
+pub mod accuracy;
+pub mod air_quality;
+pub mod alerts;
+#[cfg(feature = "archive")]
+pub mod archive;
+#[cfg(feature = "chrono")]
+pub mod astronomy;
+pub mod cap;
+pub mod citypage;
+mod client;
+#[cfg(feature = "chrono")]
+pub mod climate;
+pub mod consensus;
+#[cfg(feature = "chrono")]
+mod dates;
+pub mod diff;
+pub mod geo;
+pub mod l10n;
+pub mod marine;
+pub mod metar;
+pub mod meteorology;
 mod parsers;
+pub mod provider;
+pub mod stream;
+pub mod tracker;
 pub mod types;
 
-static WEATHER_FEED: &str = "https://weather.gc.ca/rss/city/qc-58_e.xml";
+pub use client::{Location, WeatherClient};
+
+static WEATHER_CITY: &str = "qc-58";
+
+/// Language of the RSS feed. EC publishes every city feed in both.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Language {
+  #[default]
+  English,
+  French,
+}
+
+impl Language {
+  /// Suffix used by EC for the feed file name.
+  fn feed_suffix(&self) -> &str {
+    match self {
+      Self::English => "e",
+      Self::French => "f",
+    }
+  }
+}
 
 /// Weather Forecast
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ForeCast(Feed);
 
 impl std::fmt::Display for ForeCast {
@@ -20,6 +66,15 @@ impl std::fmt::Display for ForeCast {
   }
 }
 
+impl std::str::FromStr for ForeCast {
+  type Err = ApiError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let raw: RawFeed = serde_xml_rs::from_str(s)?;
+    Ok(ForeCast(raw.into()))
+  }
+}
+
 impl ForeCast {
   pub fn current_forecast(&self) -> impl Iterator<Item = &Entry> {
     self
@@ -29,16 +84,118 @@ impl ForeCast {
       .filter(|entry| matches!(entry.category.term, Term::Current | Term::Warnings))
   }
 
+  /// Problems found while decoding the feed. Entries that could not be
+  /// decoded are left out of the forecast rather than failing the whole feed.
+  pub fn warnings(&self) -> &[DecodeWarning] {
+    &self.0.warnings
+  }
+
+  /// Time the feed was last updated, in RFC 3339 format.
+  pub fn updated(&self) -> Option<&str> {
+    self.0.updated.as_deref()
+  }
+
   pub fn entries(self) -> impl Iterator<Item = Entry> {
     self.0.entries.into_iter()
   }
 }
 
 /// RSS Feed Element.
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 struct Feed {
-  #[serde(rename = "entry", deserialize_with = "deserialize_entries")]
+  updated: Option<String>,
   entries: Vec<Entry>,
+  warnings: Vec<DecodeWarning>,
+}
+
+/// RSS Feed Element as it appears in the feed, before validation.
+#[derive(Deserialize, Debug)]
+struct RawFeed {
+  updated: Option<String>,
+  #[serde(rename = "entry", default)]
+  entries: Vec<PartialEntry<'static>>,
+}
+
+/// RSS Entry Element with every element optional, so a single malformed
+/// entry doesn't fail the feed.
+#[derive(Deserialize, Debug, Default)]
+struct PartialEntry<'a> {
+  title: Option<Cow<'a, str>>,
+  category: Option<Category>,
+  summary: Option<Cow<'a, str>>,
+  updated: Option<Cow<'a, str>>,
+}
+
+impl From<RawFeed> for Feed {
+  fn from(raw: RawFeed) -> Self {
+    let mut warnings = vec![];
+    let entries = raw
+      .entries
+      .into_iter()
+      .enumerate()
+      .filter_map(|(index, raw)| check_entry(index, raw, &mut warnings))
+      .map(EntryRef::into_owned)
+      .collect();
+
+    Feed {
+      updated: raw.updated,
+      entries,
+      warnings,
+    }
+  }
+}
+
+/// Validate the elements of an entry, recording what is missing or unknown.
+/// Returns `None` for entries that should not be part of the forecast.
+fn check_entry<'a>(
+  index: usize,
+  PartialEntry {
+    title,
+    category,
+    summary,
+    updated,
+  }: PartialEntry<'a>,
+  warnings: &mut Vec<DecodeWarning>,
+) -> Option<EntryRef<'a>> {
+  let Some(title) = title else {
+    warnings.push(DecodeWarning::MissingTitle { index });
+    return None;
+  };
+  let category = category.unwrap_or_else(|| {
+    warnings.push(DecodeWarning::MissingCategory {
+      title: title.to_string(),
+    });
+    Category {
+      term: Term::Missing,
+    }
+  });
+  if let Term::Other(term) = &category.term {
+    warnings.push(DecodeWarning::UnknownCategory {
+      title: title.to_string(),
+      term: term.clone(),
+    });
+  }
+  let summary = summary.map(truncate_summary);
+  if summary.is_none() {
+    warnings.push(DecodeWarning::MissingSummary {
+      title: title.to_string(),
+    });
+  }
+
+  // Ignore watches / warnings that are are not in effect
+  if matches!(category.term, Term::Warnings)
+    && (title.starts_with("No watches or warnings in effect")
+      || title.starts_with("Aucune veille ou alerte en vigueur"))
+  {
+    return None;
+  }
+
+  Some(EntryRef {
+    title,
+    category,
+    summary: summary.unwrap_or_default(),
+    updated,
+  })
 }
 
 /// RSS Entry Element.
@@ -46,35 +203,45 @@ struct Feed {
 pub struct Entry {
   pub title: String,
   // link: String,
-  // updated: String,
   pub category: Category,
-  #[serde(deserialize_with = "deserialize_summary")]
   pub summary: String,
+  /// Time the entry was issued or observed, in RFC 3339 format.
+  #[serde(default)]
+  pub updated: Option<String>,
 }
 
-fn deserialize_summary<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
-  struct TruncatingStringVisitor;
-
-  impl<'de> Visitor<'de> for TruncatingStringVisitor {
-    type Value = String;
+/// RSS Entry Element borrowing its text from the feed where possible.
+#[derive(Debug, Clone)]
+pub struct EntryRef<'a> {
+  pub title: Cow<'a, str>,
+  pub category: Category,
+  pub summary: Cow<'a, str>,
+  pub updated: Option<Cow<'a, str>>,
+}
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-      formatter.write_str("expecting string")
+impl EntryRef<'_> {
+  pub fn into_owned(self) -> Entry {
+    Entry {
+      title: self.title.into_owned(),
+      category: self.category,
+      summary: self.summary.into_owned(),
+      updated: self.updated.map(Cow::into_owned),
     }
+  }
+}
 
-    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
-    where
-      E: serde::de::Error,
-    {
-      v.rfind("Forecast issued")
-        .map(|index| {
-          let (keep, _) = v.split_at(index);
-          Ok(keep.trim().into())
-        })
-        .unwrap_or_else(|| Ok(v.into()))
-    }
+/// Drop the "Forecast issued" trailer from a summary.
+fn truncate_summary(summary: Cow<'_, str>) -> Cow<'_, str> {
+  let Some(index) = summary
+    .rfind("Forecast issued")
+    .or_else(|| summary.rfind("Prévisions émises"))
+  else {
+    return summary;
+  };
+  match summary {
+    Cow::Borrowed(summary) => Cow::Borrowed(summary[..index].trim()),
+    Cow::Owned(summary) => Cow::Owned(summary[..index].trim().into()),
   }
-  deserializer.deserialize_string(TruncatingStringVisitor)
 }
 
 impl Entry {
@@ -90,53 +257,65 @@ impl std::fmt::Display for Entry {
 }
 
 // RSS Category Element.
-#[derive(Debug, Deserialize, Serialize, Copy, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Category {
   pub term: Term,
 }
 
-/// RSS Category term attribute.
-#[derive(Debug, Deserialize, PartialEq, Eq, Serialize, Copy, Clone)]
+/// RSS Category term attribute. French feeds use their own terms, and terms
+/// added to the feed after this was written are kept as `Other`. Entries
+/// without a category, or with an empty one, are `Missing`.
+#[derive(Debug, Deserialize, PartialEq, Eq, Serialize, Clone)]
+#[serde(from = "String", into = "String")]
 pub enum Term {
-  #[serde(rename = "Current Conditions")]
   Current,
-  #[serde(rename = "Weather Forecasts")]
   ForeCast,
-  #[serde(rename = "Warnings and Watches")]
   Warnings,
+  Missing,
+  Other(String),
 }
 
-/// If no watches or warnings are in effect then exclude from deserialization.
-fn deserialize_entries<'de, D>(deserializer: D) -> Result<Vec<Entry>, D::Error>
-where
-  D: Deserializer<'de>,
-{
-  struct FilteredEntries;
-
-  impl<'de> Visitor<'de> for FilteredEntries {
-    type Value = Vec<Entry>;
-
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-      formatter.write_str("a list of Entry")
+impl From<&str> for Term {
+  fn from(term: &str) -> Self {
+    match term {
+      "Current Conditions" | "Conditions actuelles" => Self::Current,
+      "Weather Forecasts" | "Prévisions météo" => Self::ForeCast,
+      "Warnings and Watches" | "Veilles et avertissements" => Self::Warnings,
+      "" => Self::Missing,
+      _ => Self::Other(term.into()),
     }
+  }
+}
 
-    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-    where
-      A: serde::de::SeqAccess<'de>,
-    {
-      let mut entries = vec![];
-      while let Some(next) = seq.next_element::<Entry>()? {
-        // Ignore watches / warnings that are are not in effect
-        if !(matches!(next.category.term, Term::Warnings)
-          && next.title.starts_with("No watches or warnings in effect"))
-        {
-          entries.push(next);
-        }
-      }
-      Ok(entries)
+impl From<String> for Term {
+  fn from(term: String) -> Self {
+    term.as_str().into()
+  }
+}
+
+impl From<Term> for String {
+  fn from(term: Term) -> Self {
+    match term {
+      Term::Current => "Current Conditions".into(),
+      Term::ForeCast => "Weather Forecasts".into(),
+      Term::Warnings => "Warnings and Watches".into(),
+      Term::Missing => String::new(),
+      Term::Other(term) => term,
     }
   }
-  deserializer.deserialize_seq(FilteredEntries)
+}
+
+/// Problems with a feed that were worked around while decoding it.
+#[derive(Error, Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum DecodeWarning {
+  #[error("Entry {index} has no title and was skipped")]
+  MissingTitle { index: usize },
+  #[error("Entry \"{title}\" has no category")]
+  MissingCategory { title: String },
+  #[error("Entry \"{title}\" has no summary")]
+  MissingSummary { title: String },
+  #[error("Entry \"{title}\" has unknown category \"{term}\"")]
+  UnknownCategory { title: String, term: String },
 }
 
 /// API or Deserialization errors.
@@ -146,6 +325,20 @@ pub enum ApiError {
   Rss(#[from] reqwest::Error),
   #[error("Deserialize error {0}")]
   Parse(#[from] serde_xml_rs::Error),
+  #[error("Xml error {0}")]
+  Xml(#[from] quick_xml::Error),
+  #[error("Read error {0}")]
+  Io(#[from] std::io::Error),
+  #[error("Json error {0}")]
+  Json(#[from] serde_json::Error),
+  #[error("Report error {0}")]
+  Report(#[from] metar::ReportParseError),
+  #[error("Invalid coordinates \"{0}\", expected latitude,longitude")]
+  Coordinates(String),
+  #[error("Invalid AQHI region \"{0}\", expected zone/code, e.g. ont/FEVNT")]
+  Region(String),
+  #[error("No AQHI forecast periods for {0}")]
+  NoForecast(String),
   #[error("Error for testing")]
   TestError(String),
 }
@@ -153,14 +346,25 @@ pub enum ApiError {
 /// Gets the weather forecast from remote RSS feed.
 #[cfg(feature = "async")]
 pub async fn get_weather() -> Result<ForeCast, ApiError> {
-  let body = reqwest::get(WEATHER_FEED).await?.text().await?;
-  Ok(ForeCast(serde_xml_rs::from_str(&body)?))
+  get_weather_in(Language::English).await
 }
 
 #[cfg(not(feature = "async"))]
 pub fn get_weather() -> Result<ForeCast, ApiError> {
-  let body = reqwest::blocking::get(WEATHER_FEED)?.text()?;
-  Ok(ForeCast(serde_xml_rs::from_str(&body)?))
+  get_weather_in(Language::English)
+}
+
+/// Gets the weather forecast from the remote RSS feed in the given language.
+#[cfg(feature = "async")]
+pub async fn get_weather_in(language: Language) -> Result<ForeCast, ApiError> {
+  WeatherClient::new()
+    .get_weather(&Location::new(WEATHER_CITY, language))
+    .await
+}
+
+#[cfg(not(feature = "async"))]
+pub fn get_weather_in(language: Language) -> Result<ForeCast, ApiError> {
+  WeatherClient::new().get_weather(&Location::new(WEATHER_CITY, language))
 }
 
 #[cfg(test)]
@@ -196,4 +400,129 @@ mod test {
     let current = result.current_forecast();
     assert!(current.count() > 0);
   }
+
+  #[test]
+  fn test_unknown_category() {
+    let feed = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+<title>Gatineau - Weather - Environment Canada</title>
+<entry>
+<title>Air Quality Health Index: 3</title>
+<category term="Air Quality"/>
+<summary type="html">Low risk.</summary>
+<foo>bar</foo>
+</entry>
+<entry>
+<title>Sunday: Cloudy. High minus 2.</title>
+<category term="Weather Forecasts"/>
+</entry>
+<entry>
+<category term="Weather Forecasts"/>
+<summary type="html">Cloudy.</summary>
+</entry>
+<entry>
+<title>Monday: Sunny. High minus 8.</title>
+<summary type="html">Sunny.</summary>
+</entry>
+</feed>"#
+      .parse::<ForeCast>()
+      .unwrap();
+
+    assert_eq!(
+      feed.warnings(),
+      [
+        DecodeWarning::UnknownCategory {
+          title: "Air Quality Health Index: 3".into(),
+          term: "Air Quality".into()
+        },
+        DecodeWarning::MissingSummary {
+          title: "Sunday: Cloudy. High minus 2.".into()
+        },
+        DecodeWarning::MissingTitle { index: 2 },
+        DecodeWarning::MissingCategory {
+          title: "Monday: Sunny. High minus 8.".into()
+        },
+      ]
+    );
+    let terms = feed
+      .entries()
+      .map(|entry| entry.category.term)
+      .collect::<Vec<_>>();
+    assert_eq!(
+      terms,
+      [
+        Term::Other("Air Quality".into()),
+        Term::ForeCast,
+        Term::Missing
+      ]
+    );
+  }
+
+  #[test]
+  fn test_french_feed() {
+    let english = include_str!("../fixtures/qc-58_e.xml")
+      .parse::<ForeCast>()
+      .unwrap();
+    let french = include_str!("../fixtures/qc-58_f.xml")
+      .parse::<ForeCast>()
+      .unwrap();
+
+    assert_eq!(english.0.entries.len(), french.0.entries.len());
+    for (en, fr) in english.0.entries.iter().zip(&french.0.entries) {
+      assert_eq!(en.category.term, fr.category.term);
+    }
+    assert!(english.warnings().is_empty());
+    assert!(french.warnings().is_empty());
+    assert!(french
+      .0
+      .entries
+      .iter()
+      .all(|entry| !entry.summary.contains("Prévisions émises")));
+  }
+
+  #[test]
+  fn test_french_to_forecast() {
+    let english = include_str!("../fixtures/qc-58_e.xml")
+      .parse::<ForeCast>()
+      .unwrap();
+    let french = include_str!("../fixtures/qc-58_f.xml")
+      .parse::<ForeCast>()
+      .unwrap();
+
+    let english = types::to_forecast(english.entries());
+    let french = types::to_forecast(french.entries());
+    assert_eq!(english.len(), french.len());
+    assert!(matches!(french[0], types::ForecastEntry::Current(_)));
+
+    let celsius = |entries: &[types::ForecastEntry]| {
+      entries
+        .iter()
+        .filter_map(|entry| match entry {
+          types::ForecastEntry::Future { day, night, .. } => Some(
+            day
+              .iter()
+              .chain(night.iter())
+              .map(|fc| format!("{}", fc.forecast.celsius))
+              .collect::<Vec<_>>(),
+          ),
+          _ => None,
+        })
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(celsius(&english), celsius(&french));
+
+    // Only English summaries have a timeline.
+    let timelines = |entries: &[types::ForecastEntry]| {
+      entries
+        .iter()
+        .filter_map(|entry| match entry {
+          types::ForecastEntry::Future { day, night, .. } => Some(day.iter().chain(night.iter())),
+          _ => None,
+        })
+        .flatten()
+        .filter(|fc| !fc.forecast.timeline.is_empty())
+        .count()
+    };
+    assert!(timelines(&english) > 0);
+    assert_eq!(timelines(&french), 0);
+  }
 }
@@ -1,45 +1,685 @@
-use clap::Parser;
-use my_weather::get_weather;
+#[cfg(feature = "chrono")]
+use chrono::Local;
+#[cfg(feature = "archive")]
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{Parser, Subcommand, ValueEnum};
+#[cfg(feature = "archive")]
+use my_weather::{accuracy::accuracy, archive::Archive};
+use my_weather::{
+  air_quality::{AirQuality, Region},
+  alerts::{AlertEngine, Rules},
+  cap::CapAlert,
+  consensus::{self, DEFAULT_THRESHOLD},
+  diff::diff,
+  geo::{CityIndex, FAR},
+  marine::MarineForecast,
+  metar::{self, Metar, Taf},
+  provider::{AviationWeather, Coordinates, Nws, OpenMeteo, WeatherProvider},
+  tracker::WarningTracker,
+  types::{to_forecast, ForecastEntry},
+  ApiError, ForeCast, Language, Location, WeatherClient,
+};
+#[cfg(feature = "chrono")]
+use my_weather::{astronomy, climate::Normals, l10n::Localize, types::ForecastWithEntry};
+#[cfg(feature = "chrono")]
+use std::collections::HashMap;
+use std::{
+  io::Read,
+  path::{Path, PathBuf},
+};
 
 #[derive(Debug, Parser)]
 struct Args {
   #[arg(short, default_value_t = false)]
   current: bool,
+  /// Use the French language feed.
+  #[arg(short, default_value_t = false)]
+  french: bool,
+  /// City feed code, e.g. qc-58, or latitude,longitude for the other
+  /// providers. May be repeated to fetch several cities.
+  #[arg(long = "city", default_value = "qc-58")]
+  cities: Vec<String>,
+  /// GPS position, e.g. 45.5,-73.6, to fetch the forecast of the nearest city
+  /// feed instead of --city.
+  #[arg(long)]
+  near: Option<Coordinates>,
+  /// TOML list of city feeds and their coordinates, searched with the
+  /// built-in ones for --near.
+  #[arg(long)]
+  city_list: Option<PathBuf>,
+  /// Airport to show the latest METAR and TAF of, e.g. CYOW.
+  #[arg(long)]
+  station: Option<String>,
+  /// AQHI region to show the air quality of, as zone/code, e.g. ont/FEVNT.
+  #[arg(long)]
+  aqhi: Option<Region>,
+  /// Marine region to show the forecast of, e.g. 08000 for Lake Ontario.
+  #[arg(long)]
+  marine: Option<String>,
+  /// Where forecasts come from. May be repeated to merge several providers
+  /// into a consensus forecast.
+  #[arg(long = "provider", value_enum, default_value = "ec")]
+  providers: Vec<Provider>,
+  /// Spread in °C above which merged providers are reported to disagree.
+  #[arg(long, default_value_t = DEFAULT_THRESHOLD)]
+  threshold: f32,
+  /// TOML file of alert rules to check each forecast against.
+  #[arg(long)]
+  rules: Option<PathBuf>,
+  /// File remembering the warnings seen on previous runs, so only new,
+  /// updated and ended warnings are reported.
+  #[arg(long)]
+  warning_state: Option<PathBuf>,
+  /// Daily data CSV exported from the historical climate data site, to show
+  /// how far each forecast is from normal. May be repeated to load several
+  /// years.
+  #[cfg(feature = "chrono")]
+  #[arg(long = "normals")]
+  normals: Vec<PathBuf>,
+  /// Latitude and longitude of the city, e.g. 45.48,-75.65, to show its
+  /// sunrise, sunset and moon phase. Cities given as coordinates don't need
+  /// it.
+  #[cfg(feature = "chrono")]
+  #[arg(long)]
+  coordinates: Option<Coordinates>,
+  /// JSON lines file each fetched feed is appended to.
+  #[cfg(feature = "archive")]
+  #[arg(long, global = true)]
+  archive: Option<PathBuf>,
+  #[command(subcommand)]
+  command: Option<Command>,
 }
 
-#[cfg(feature = "async")]
-#[tokio::main]
-pub async fn main() {
-  let args = Args::parse();
+#[derive(Debug, Copy, Clone, ValueEnum)]
+enum Provider {
+  /// Environment Canada city feeds.
+  Ec,
+  /// Open-Meteo, anywhere.
+  OpenMeteo,
+  /// US National Weather Service, in English only.
+  Nws,
+}
+
+impl Provider {
+  fn name(self) -> String {
+    self
+      .to_possible_value()
+      .map(|value| value.get_name().to_string())
+      .unwrap_or_default()
+  }
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+  /// Show what changed between two saved feeds.
+  Diff { old: PathBuf, new: PathBuf },
+  /// Show a CAP alert saved to a file.
+  Alert { path: PathBuf },
+  /// Decode METAR and TAF reports from a file, or from stdin without one.
+  Metar { path: Option<PathBuf> },
+  /// Query the feeds saved with --archive.
+  #[cfg(feature = "archive")]
+  History {
+    #[command(subcommand)]
+    query: HistoryQuery,
+  },
+}
 
-  match get_weather().await {
-    Ok(forecast) => {
-      if args.current {
-        for entry in forecast.current_forecast() {
-          println!("{entry}");
+#[cfg(feature = "archive")]
+#[derive(Debug, Subcommand)]
+enum HistoryQuery {
+  /// Forecasts issued on a local date, e.g. 2023-01-07.
+  Issued { date: NaiveDate },
+  /// Current conditions observed between two times, e.g. 2023-01-07T19:00:00Z.
+  Current {
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+  },
+  /// Forecast error by lead time for each --city, against the archived
+  /// current conditions.
+  Accuracy,
+}
+
+impl Args {
+  fn language(&self) -> Language {
+    if self.french {
+      Language::French
+    } else {
+      Language::English
+    }
+  }
+
+  fn locations(&self) -> Vec<Location> {
+    self
+      .cities
+      .iter()
+      .map(|city| Location::new(city.as_str(), self.language()))
+      .collect()
+  }
+
+  /// Locations to fetch from a provider. With --near, the providers taking
+  /// coordinates get the position itself rather than the nearest city feed.
+  fn locations_for(&self, provider: Provider) -> Vec<Location> {
+    match (self.near, provider) {
+      (Some(near), Provider::OpenMeteo | Provider::Nws) => {
+        vec![Location::new(near.to_string(), self.language())]
+      }
+      _ => self.locations(),
+    }
+  }
+
+  /// Replace the cities with the city feed nearest to --near.
+  fn resolve_near(&mut self) {
+    let Some(near) = self.near else { return };
+    let mut index = CityIndex::default();
+    if let Some(path) = &self.city_list {
+      match CityIndex::load(path) {
+        Ok(list) => index.extend(list.cities().to_vec()),
+        Err(e) => eprintln!("{e}"),
+      }
+    }
+    if let Some(nearest) = index.nearest(&near, 1).first() {
+      println!("Nearest city: {nearest}");
+      if nearest.is_far() {
+        eprintln!("No city within {FAR} km, --city-list can add nearer ones");
+      }
+      self.cities = vec![nearest.city.code.clone()];
+    }
+  }
+
+  fn print(&self, location: &Location, result: Result<ForeCast, ApiError>) {
+    if self.cities.len() > 1 {
+      println!("{}:", location.city);
+    }
+    match result {
+      Ok(forecast) => {
+        for warning in forecast.warnings() {
+          eprintln!("{warning}");
         }
-      } else {
-        println!("{forecast}");
+        self.alerts(&forecast);
+        self.track_warnings(location, &forecast);
+        if self.current {
+          for entry in forecast.current_forecast() {
+            println!("{entry}");
+          }
+        } else {
+          self.print_forecast(&forecast);
+        }
+        #[cfg(feature = "chrono")]
+        self.print_almanac(location);
+      }
+      Err(e) => eprintln!("{e}"),
+    }
+  }
+
+  /// Print the periods of a forecast, beside their departure from normal
+  /// when there are --normals.
+  fn print_forecast(&self, forecast: &ForeCast) {
+    #[cfg(feature = "chrono")]
+    if let Some(normals) = self.normals() {
+      return self.print_with_normals(forecast, &normals);
+    }
+    println!("{forecast}");
+  }
+
+  /// Print today's sun and moon, when the coordinates of the city are known
+  /// from --coordinates or the city itself.
+  #[cfg(feature = "chrono")]
+  fn print_almanac(&self, location: &Location) {
+    let coordinates = self
+      .coordinates
+      .or(self.near)
+      .or_else(|| location.city.parse::<Coordinates>().ok());
+    if let Some(coordinates) = coordinates {
+      println!(
+        "{}",
+        astronomy::almanac(&coordinates, Local::now().date_naive(), &Local)
+      );
+    }
+  }
+
+  /// Normals of the --normals files, if any.
+  #[cfg(feature = "chrono")]
+  fn normals(&self) -> Option<Normals> {
+    if self.normals.is_empty() {
+      return None;
+    }
+    Normals::load(&self.normals)
+      .map_err(|e| eprintln!("{e}"))
+      .ok()
+  }
+
+  /// Print a forecast with the departure from normal of each period in a
+  /// column beside it.
+  #[cfg(feature = "chrono")]
+  fn print_with_normals(&self, forecast: &ForeCast, normals: &Normals) {
+    let mut periods = to_forecast(forecast.clone().entries());
+    normals.annotate(&mut periods, &Local);
+    let departures = periods
+      .iter()
+      .flat_map(|period| match period {
+        ForecastEntry::Future { day, night, .. } => day.iter().chain(night).collect(),
+        _ => vec![],
+      })
+      .filter_map(|ForecastWithEntry { forecast, entry }| {
+        Some((entry.title.as_str(), forecast.normal?))
+      })
+      .collect::<HashMap<_, _>>();
+
+    let entries = forecast.clone().entries().collect::<Vec<_>>();
+    let width = entries
+      .iter()
+      .map(|entry| entry.title.chars().count())
+      .max()
+      .unwrap_or_default();
+    for entry in entries {
+      match departures.get(entry.title.as_str()) {
+        Some(departure) => println!(
+          "{:width$}  {}",
+          entry.title,
+          departure.localize(self.language())
+        ),
+        None => println!("{entry}"),
+      }
+    }
+  }
+
+  /// Print the forecast of each location, merged into a consensus when there
+  /// are several providers. Forecasts are printed and tracked under the
+  /// location fetched from the first provider.
+  fn print_all(&self, mut results: Vec<Vec<Result<ForeCast, ApiError>>>) {
+    // The feeds as fetched are archived, never the consensus of them.
+    #[cfg(feature = "archive")]
+    for (provider, results) in self.providers.iter().zip(&results) {
+      for (location, result) in self.locations_for(*provider).iter().zip(results) {
+        self.record(*provider, location, result);
+      }
+    }
+
+    let locations = self.providers.first().map_or_else(
+      || self.locations(),
+      |provider| self.locations_for(*provider),
+    );
+    if results.len() == 1 {
+      for (location, result) in locations.iter().zip(results.remove(0)) {
+        self.print(location, result);
+      }
+      return;
+    }
+
+    let mut columns = results.into_iter().map(Vec::into_iter).collect::<Vec<_>>();
+    for location in &locations {
+      let mut sources = vec![];
+      for (provider, column) in self.providers.iter().zip(&mut columns) {
+        match column.next() {
+          Some(Ok(forecast)) => sources.push((provider.name(), to_forecast(forecast.entries()))),
+          Some(Err(e)) => eprintln!("{}: {e}", provider.name()),
+          None => (),
+        }
+      }
+      if sources.is_empty() {
+        continue;
+      }
+      let sources = sources
+        .iter()
+        .map(|(name, entries)| (name.as_str(), entries.as_slice()))
+        .collect::<Vec<(&str, &[ForecastEntry])>>();
+      let consensus = consensus::merge(&sources, self.threshold, self.language());
+      self.print(location, Ok(consensus.feed.clone()));
+      for period in consensus.disagreements() {
+        println!("Disagreement: {period}");
+      }
+    }
+  }
+
+  /// Print the air quality fetched for --aqhi, and the alerts it fires.
+  fn print_air_quality(&self, result: Result<AirQuality, ApiError>) {
+    let air_quality = match result {
+      Ok(air_quality) => air_quality,
+      Err(e) => return eprintln!("{e}"),
+    };
+    print!("{air_quality}");
+    let Some(path) = &self.rules else { return };
+    match Rules::load(path) {
+      Ok(rules) => {
+        for event in AlertEngine::new(rules).evaluate_with_air_quality(&[], Some(&air_quality)) {
+          println!("Alert: {event}");
+        }
+      }
+      Err(e) => eprintln!("{e}"),
+    }
+  }
+
+  /// Print the marine forecast fetched for --marine, checking its warnings
+  /// like those of a city feed.
+  fn print_marine(&self, location: &Location, result: Result<ForeCast, ApiError>) {
+    match result {
+      Ok(feed) => {
+        self.alerts(&feed);
+        self.track_warnings(location, &feed);
+        print!("{}", MarineForecast::from(feed));
+      }
+      Err(e) => eprintln!("{e}"),
+    }
+  }
+
+  /// Print the alerts a forecast fires, if there are rules.
+  fn alerts(&self, forecast: &ForeCast) {
+    let Some(path) = &self.rules else { return };
+    match Rules::load(path) {
+      Ok(rules) => {
+        for event in AlertEngine::new(rules).evaluate(&to_forecast(forecast.clone().entries())) {
+          println!("Alert: {event}");
+        }
+      }
+      Err(e) => eprintln!("{e}"),
+    }
+  }
+
+  /// Print the warnings that changed since the last run, if state is kept.
+  fn track_warnings(&self, location: &Location, forecast: &ForeCast) {
+    let Some(path) = &self.warning_state else {
+      return;
+    };
+    let result = WarningTracker::load(path).and_then(|mut tracker| {
+      for event in tracker.update(location, &to_forecast(forecast.clone().entries())) {
+        if event.is_notable() {
+          println!("Warning {event}");
+        }
+      }
+      tracker.save(path)
+    });
+    if let Err(e) = result {
+      eprintln!("{e}");
+    }
+  }
+
+  /// Append a feed fetched from a provider to the archive, if there is one.
+  /// Feeds of other providers than Environment Canada are tagged with it.
+  #[cfg(feature = "archive")]
+  fn record(&self, provider: Provider, location: &Location, result: &Result<ForeCast, ApiError>) {
+    let name = match provider {
+      Provider::Ec => None,
+      _ => Some(provider.name()),
+    };
+    if let (Some(path), Ok(forecast)) = (&self.archive, result) {
+      let archive = Archive::open(path);
+      if let Err(e) = archive.record_from(name.as_deref(), location, forecast, Utc::now()) {
+        eprintln!("{e}");
       }
     }
-    Err(e) => eprintln!("{e}"),
+  }
+
+  /// Print the answer to a history query, or an error without --archive.
+  #[cfg(feature = "archive")]
+  fn history(&self, query: &HistoryQuery) {
+    let Some(path) = &self.archive else {
+      eprintln!("history needs an --archive file");
+      return;
+    };
+    let archive = Archive::open(path);
+
+    let result = match query {
+      HistoryQuery::Issued { date } => archive.issued_on(*date, &Local).map(|feeds| {
+        for feed in feeds {
+          if let Some(issued) = feed.issued {
+            println!(
+              "{} issued {}",
+              feed.location.city,
+              issued.with_timezone(&Local).format("%v %r")
+            );
+          }
+          for entry in feed.entries {
+            println!("  {entry}");
+          }
+        }
+      }),
+      HistoryQuery::Current { from, to } => archive.current_between(*from, *to).map(|current| {
+        for observation in current {
+          let current = observation.current.current;
+          println!(
+            "{} {}: {}, {}",
+            observation.observed.with_timezone(&Local).format("%v %r"),
+            observation.location.city,
+            current.description.as_deref().unwrap_or("Not reported"),
+            current
+              .celsius
+              .map(|celsius| celsius.to_string())
+              .unwrap_or_else(|| "—".into())
+          );
+        }
+      }),
+      HistoryQuery::Accuracy => self.locations().iter().try_for_each(|location| {
+        let report = accuracy(archive.feeds()?, location, &Local);
+        println!("{}:", location.city);
+        for (lead, stats) in &report.by_lead {
+          println!(
+            "  {lead} day lead: MAE {:.1}°C, bias {:+.1}°C ({} periods)",
+            stats.mean_absolute_error, stats.bias, stats.count
+          );
+        }
+        let percent = |ratio: Option<f32>| {
+          ratio
+            .map(|ratio| format!("{:.0}%", ratio * 100.))
+            .unwrap_or_else(|| "—".into())
+        };
+        println!(
+          "  Precipitation: hit rate {}, false alarm ratio {}",
+          percent(report.precipitation.hit_rate()),
+          percent(report.precipitation.false_alarm_ratio())
+        );
+        Ok(())
+      }),
+    };
+    if let Err(e) = result {
+      eprintln!("{e}");
+    }
+  }
+
+  /// Run a subcommand. Returns false when there is none.
+  fn run_command(&self) -> bool {
+    match &self.command {
+      Some(Command::Diff { old, new }) => print_diff(old, new),
+      Some(Command::Alert { path }) => print_alert(path, self.language()),
+      Some(Command::Metar { path }) => print_reports(path.as_deref()),
+      #[cfg(feature = "archive")]
+      Some(Command::History { query }) => self.history(query),
+      None => return false,
+    }
+    true
   }
 }
 
-#[cfg(not(feature = "async"))]
-pub fn main() {
-  let args = Args::parse();
+/// Print the details of a CAP alert file.
+fn print_alert(path: &Path, language: Language) {
+  let alert = match CapAlert::load(path) {
+    Ok(alert) => alert,
+    Err(e) => return eprintln!("{}: {e}", path.display()),
+  };
+  let Some(info) = alert.info(language) else {
+    return eprintln!("{}: alert has no details", path.display());
+  };
 
-  match get_weather() {
-    Ok(forecast) => {
-      if args.current {
-        for entry in forecast.current_forecast() {
-          println!("{entry}");
+  println!("{}", info.headline.as_deref().unwrap_or(&info.event));
+  for area in &info.areas {
+    println!("Area: {}", area.description);
+  }
+  println!("Severity: {:?}, urgency: {:?}", info.severity, info.urgency);
+  for (label, time) in [("Onset", &info.onset), ("Expires", &info.expires)] {
+    if let Some(time) = time {
+      println!("{label}: {time}");
+    }
+  }
+  if let Some(description) = &info.description {
+    println!("\n{description}");
+  }
+  if let Some(instruction) = &info.instruction {
+    println!("\n{instruction}");
+  }
+}
+
+/// Decode and print the reports of a file, or stdin.
+fn print_reports(path: Option<&Path>) {
+  let text = match path {
+    Some(path) => std::fs::read_to_string(path),
+    None => {
+      let mut text = String::new();
+      std::io::stdin().read_to_string(&mut text).map(|_| text)
+    }
+  };
+  match text {
+    Ok(text) => {
+      for report in metar::decode(&text) {
+        match report {
+          Ok(report) => println!("{report}"),
+          Err(e) => eprintln!("{e}"),
         }
-      } else {
-        println!("{forecast}");
       }
     }
     Err(e) => eprintln!("{e}"),
   }
 }
+
+/// Print the latest airport reports fetched for --station.
+fn print_station(metar: Result<Metar, ApiError>, taf: Result<Taf, ApiError>) {
+  match metar {
+    Ok(metar) => println!("{metar}"),
+    Err(e) => eprintln!("{e}"),
+  }
+  match taf {
+    Ok(taf) => println!("{taf}"),
+    Err(e) => eprintln!("{e}"),
+  }
+}
+
+/// Print the changes between two feed files.
+fn print_diff(old: &Path, new: &Path) {
+  let read = |path: &Path| -> Result<ForeCast, String> {
+    let feed = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    feed
+      .parse::<ForeCast>()
+      .map_err(|e| format!("{}: {e}", path.display()))
+  };
+
+  match (read(old), read(new)) {
+    (Ok(old), Ok(new)) => {
+      let changes = diff(&to_forecast(old.entries()), &to_forecast(new.entries()));
+      if changes.is_empty() {
+        println!("No changes");
+      }
+      for change in changes {
+        println!("{change}");
+      }
+    }
+    (Err(e), _) | (_, Err(e)) => eprintln!("{e}"),
+  }
+}
+
+/// Fetch every location from a provider.
+#[cfg(feature = "async")]
+async fn fetch_from(
+  provider: Provider,
+  locations: Vec<Location>,
+) -> Vec<Result<ForeCast, ApiError>> {
+  match provider {
+    Provider::Ec => WeatherClient::new().get_weather_batch(&locations).await,
+    Provider::OpenMeteo => fetch(&OpenMeteo::new(), &locations).await,
+    Provider::Nws => fetch(&Nws::new(), &locations).await,
+  }
+}
+
+#[cfg(not(feature = "async"))]
+fn fetch_from(provider: Provider, locations: Vec<Location>) -> Vec<Result<ForeCast, ApiError>> {
+  match provider {
+    Provider::Ec => WeatherClient::new().get_weather_batch(&locations),
+    Provider::OpenMeteo => fetch(&OpenMeteo::new(), &locations),
+    Provider::Nws => fetch(&Nws::new(), &locations),
+  }
+}
+
+/// Fetch every location from a provider without batching of its own.
+#[cfg(feature = "async")]
+async fn fetch(
+  provider: &impl WeatherProvider,
+  locations: &[Location],
+) -> Vec<Result<ForeCast, ApiError>> {
+  futures::future::join_all(
+    locations
+      .iter()
+      .map(|location| provider.get_weather(location)),
+  )
+  .await
+}
+
+#[cfg(not(feature = "async"))]
+fn fetch(
+  provider: &impl WeatherProvider,
+  locations: &[Location],
+) -> Vec<Result<ForeCast, ApiError>> {
+  locations
+    .iter()
+    .map(|location| provider.get_weather(location))
+    .collect()
+}
+
+#[cfg(feature = "async")]
+#[tokio::main]
+pub async fn main() {
+  let mut args = Args::parse();
+  if args.run_command() {
+    return;
+  }
+  args.resolve_near();
+  let locations = args.locations();
+
+  let results = futures::future::join_all(
+    args
+      .providers
+      .iter()
+      .map(|provider| fetch_from(*provider, args.locations_for(*provider))),
+  )
+  .await;
+  args.print_all(results);
+  if let (Some(region), Some(location)) = (&args.aqhi, locations.first()) {
+    let result = WeatherClient::new().get_air_quality(location, region).await;
+    args.print_air_quality(result);
+  }
+  if let Some(marine) = &args.marine {
+    let location = Location::new(marine.as_str(), args.language());
+    let result = WeatherClient::new().get_marine(&location).await;
+    args.print_marine(&location, result);
+  }
+  if let Some(station) = &args.station {
+    let client = AviationWeather::new();
+    let (metar, taf) = futures::join!(client.get_metar(station), client.get_taf(station));
+    print_station(metar, taf);
+  }
+}
+
+#[cfg(not(feature = "async"))]
+pub fn main() {
+  let mut args = Args::parse();
+  if args.run_command() {
+    return;
+  }
+  args.resolve_near();
+  let locations = args.locations();
+
+  let results = args
+    .providers
+    .iter()
+    .map(|provider| fetch_from(*provider, args.locations_for(*provider)))
+    .collect();
+  args.print_all(results);
+  if let (Some(region), Some(location)) = (&args.aqhi, locations.first()) {
+    args.print_air_quality(WeatherClient::new().get_air_quality(location, region));
+  }
+  if let Some(marine) = &args.marine {
+    let location = Location::new(marine.as_str(), args.language());
+    args.print_marine(&location, WeatherClient::new().get_marine(&location));
+  }
+  if let Some(station) = &args.station {
+    let client = AviationWeather::new();
+    print_station(client.get_metar(station), client.get_taf(station));
+  }
+}
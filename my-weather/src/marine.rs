@@ -0,0 +1,480 @@
+//! Environment Canada marine forecasts, e.g.
+//! `https://weather.gc.ca/rss/marine/08000_e.xml` for Lake Ontario.
+//!
+//! Marine feeds are Atom feeds like the city feeds, fetched with
+//! [`WeatherClient::get_marine`](crate::WeatherClient::get_marine), with an
+//! entry per marine area for the forecast, the waves and the extended
+//! forecast. Warning entries are the same as in the city feeds, so alert
+//! rules and the warning tracker work on a marine feed as they are. Winds are
+//! kept in knots and waves in metres, as mariners use them.
+use crate::{
+  parsers::{parse_marine_wind, parse_visibility, parse_warning, parse_waves},
+  types::WeatherWarning,
+  ApiError, Entry, ForeCast, Term,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A wind speed range in knots, e.g. "southwest 15 to 25 knots".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarineWind {
+  /// Compass direction, e.g. "SW".
+  pub direction: Option<String>,
+  pub low: u32,
+  pub high: u32,
+  pub gust: Option<u32>,
+}
+
+impl fmt::Display for MarineWind {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if let Some(direction) = &self.direction {
+      write!(f, "{direction} ")?;
+    }
+    if self.low == self.high {
+      write!(f, "{} kn", self.high)?;
+    } else {
+      write!(f, "{}-{} kn", self.low, self.high)?;
+    }
+    if let Some(gust) = self.gust {
+      write!(f, " gust {gust}")?;
+    }
+    Ok(())
+  }
+}
+
+/// A wave height range in metres.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WaveHeight {
+  pub low: f32,
+  pub high: f32,
+}
+
+impl fmt::Display for WaveHeight {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if self.low == self.high {
+      write!(f, "{} m", self.high)
+    } else {
+      write!(f, "{}-{} m", self.low, self.high)
+    }
+  }
+}
+
+/// A warning in effect for a marine area, e.g. "Strong wind warning in
+/// effect".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarineWarning {
+  pub title: String,
+  pub warning: WeatherWarning,
+}
+
+/// A day of the extended forecast, e.g. "Thursday: Wind northwest 10 to 15
+/// knots."
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtendedPeriod {
+  pub period: String,
+  pub text: String,
+  pub wind: Vec<MarineWind>,
+}
+
+/// The forecast for one marine area.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MarineArea {
+  pub name: String,
+  /// Issue time as written, e.g. "04:00 AM EDT 05 September 2023".
+  pub issued: Option<String>,
+  /// The forecast text without its header.
+  pub forecast: String,
+  /// Winds in order of the forecast.
+  pub wind: Vec<MarineWind>,
+  /// Wave heights in order of the forecast.
+  pub waves: Vec<WaveHeight>,
+  /// e.g. "fair in showers".
+  pub visibility: Option<String>,
+  pub extended: Vec<ExtendedPeriod>,
+  pub warnings: Vec<MarineWarning>,
+}
+
+impl MarineArea {
+  fn new(name: &str) -> Self {
+    Self {
+      name: name.into(),
+      ..Default::default()
+    }
+  }
+
+  /// Highest wind speed of the forecast, in knots.
+  pub fn max_wind(&self) -> Option<u32> {
+    self.wind.iter().map(|wind| wind.high).max()
+  }
+
+  /// Highest gust of the forecast, in knots.
+  pub fn max_gust(&self) -> Option<u32> {
+    self.wind.iter().filter_map(|wind| wind.gust).max()
+  }
+
+  /// Highest waves of the forecast, in metres.
+  pub fn max_waves(&self) -> Option<f32> {
+    self
+      .waves
+      .iter()
+      .map(|waves| waves.high)
+      .max_by(f32::total_cmp)
+  }
+
+  fn add_warning(&mut self, title: &str, warning: WeatherWarning) {
+    let known = self
+      .warnings
+      .iter()
+      .any(|known| known.warning.kind == warning.kind && known.warning.level == warning.level);
+    if !known {
+      self.warnings.push(MarineWarning {
+        title: title.into(),
+        warning,
+      });
+    }
+  }
+}
+
+fn join<T: fmt::Display>(items: &[T]) -> String {
+  items
+    .iter()
+    .map(T::to_string)
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+impl fmt::Display for MarineArea {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    writeln!(f, "{}", self.name)?;
+    for warning in &self.warnings {
+      writeln!(f, "  {}", warning.title)?;
+    }
+    if !self.wind.is_empty() {
+      writeln!(f, "  Wind: {}", join(&self.wind))?;
+    }
+    if !self.waves.is_empty() {
+      writeln!(f, "  Waves: {}", join(&self.waves))?;
+    }
+    if let Some(visibility) = &self.visibility {
+      writeln!(f, "  Visibility: {visibility}")?;
+    }
+    for period in &self.extended {
+      writeln!(f, "  {}: {}", period.period, period.text)?;
+    }
+    Ok(())
+  }
+}
+
+/// The forecasts of the marine areas of a feed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MarineForecast {
+  pub updated: Option<String>,
+  /// Areas in the order of the feed.
+  pub areas: Vec<MarineArea>,
+}
+
+impl MarineForecast {
+  pub fn area(&self, name: &str) -> Option<&MarineArea> {
+    self
+      .areas
+      .iter()
+      .find(|area| area.name.eq_ignore_ascii_case(name))
+  }
+
+  fn area_mut(&mut self, name: &str) -> &mut MarineArea {
+    let index = match self
+      .areas
+      .iter()
+      .position(|area| area.name.eq_ignore_ascii_case(name))
+    {
+      Some(index) => index,
+      None => {
+        self.areas.push(MarineArea::new(name));
+        self.areas.len() - 1
+      }
+    };
+    &mut self.areas[index]
+  }
+}
+
+impl fmt::Display for MarineForecast {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for area in &self.areas {
+      write!(f, "{area}")?;
+    }
+    Ok(())
+  }
+}
+
+/// Kinds of marine entries, by category term in either language.
+enum Section {
+  Forecast,
+  Waves,
+  Extended,
+}
+
+impl Section {
+  fn from_term(term: &Term) -> Option<Self> {
+    match term {
+      Term::Other(term) => match term.as_str() {
+        "Marine Forecasts" | "Prévisions maritimes" => Some(Self::Forecast),
+        "Waves" | "Vagues" => Some(Self::Waves),
+        "Extended Forecasts" | "Prévisions à long terme" => Some(Self::Extended),
+        _ => None,
+      },
+      _ => None,
+    }
+  }
+}
+
+/// Lines of an HTML summary, without tags and blank lines.
+fn lines(summary: &str) -> Vec<String> {
+  let mut text = String::new();
+  let mut rest = summary;
+  while let Some(start) = rest.find('<') {
+    text.push_str(&rest[..start]);
+    let Some(end) = rest[start..].find('>') else {
+      break;
+    };
+    let tag = &rest[start + 1..start + end];
+    if tag.trim_end_matches('/').trim().eq_ignore_ascii_case("br") {
+      text.push('\n');
+    }
+    rest = &rest[start + end + 1..];
+  }
+  text.push_str(rest);
+  text
+    .lines()
+    .map(str::trim)
+    .filter(|line| !line.is_empty())
+    .map(Into::into)
+    .collect()
+}
+
+/// Sentences of a line, with their final period.
+fn sentences(line: &str) -> impl Iterator<Item = &str> {
+  line
+    .split_inclusive(". ")
+    .map(str::trim)
+    .filter(|sentence| !sentence.is_empty())
+}
+
+/// Issue time of a header line, e.g. "Issued 04:00 AM EDT 05 September 2023".
+fn issued(line: &str) -> Option<&str> {
+  ["Issued ", "Émises à ", "Émises "]
+    .iter()
+    .find_map(|prefix| line.strip_prefix(prefix))
+    .map(str::trim)
+}
+
+impl MarineArea {
+  fn add_forecast(&mut self, summary: &str) {
+    let mut text = vec![];
+    for line in lines(summary) {
+      if let Some(issued) = issued(&line) {
+        self.issued.get_or_insert_with(|| issued.into());
+        continue;
+      }
+      for sentence in sentences(&line) {
+        if let Some(warning) = parse_warning(sentence.trim_end_matches('.')) {
+          self.add_warning(sentence.trim_end_matches('.'), warning);
+        }
+        // Later sentences leave out a direction that doesn't change.
+        for mut wind in parse_marine_wind(sentence) {
+          if wind.direction.is_none() {
+            wind.direction = self.wind.last().and_then(|last| last.direction.clone());
+          }
+          self.wind.push(wind);
+        }
+        if let Some(visibility) = parse_visibility(sentence) {
+          self.visibility = Some(visibility);
+        }
+      }
+      text.push(line);
+    }
+    self.forecast = text.join(" ");
+  }
+
+  fn add_waves(&mut self, summary: &str) {
+    for line in lines(summary) {
+      if issued(&line).is_some() {
+        continue;
+      }
+      for sentence in sentences(&line) {
+        self.waves.extend(parse_waves(sentence));
+      }
+    }
+  }
+
+  fn add_extended(&mut self, summary: &str) {
+    for line in lines(summary) {
+      let Some((period, text)) = line.split_once(": ") else {
+        continue;
+      };
+      if issued(&line).is_some() {
+        continue;
+      }
+      self.extended.push(ExtendedPeriod {
+        period: period.trim().into(),
+        text: text.trim().into(),
+        wind: sentences(text).flat_map(parse_marine_wind).collect(),
+      });
+    }
+  }
+}
+
+impl From<ForeCast> for MarineForecast {
+  fn from(feed: ForeCast) -> Self {
+    let mut forecast = MarineForecast {
+      updated: feed.updated().map(Into::into),
+      areas: vec![],
+    };
+
+    for Entry {
+      title,
+      category,
+      summary,
+      ..
+    } in feed.entries()
+    {
+      if category.term == Term::Warnings {
+        // "Strong wind warning in effect, Western Lake Ontario"
+        let Some(warning) = parse_warning(&title) else {
+          continue;
+        };
+        let (Some(area), Some((headline, _))) = (warning.area.clone(), title.rsplit_once(','))
+        else {
+          continue;
+        };
+        forecast
+          .area_mut(&area)
+          .add_warning(headline.trim(), warning);
+        continue;
+      }
+      let Some(section) = Section::from_term(&category.term) else {
+        continue;
+      };
+      // "Western Lake Ontario - Waves"
+      let area = title
+        .rsplit_once(" - ")
+        .map(|(area, _)| area)
+        .unwrap_or(&title)
+        .trim();
+      let area = forecast.area_mut(area);
+      match section {
+        Section::Forecast => area.add_forecast(&summary),
+        Section::Waves => area.add_waves(&summary),
+        Section::Extended => area.add_extended(&summary),
+      }
+    }
+
+    forecast
+  }
+}
+
+impl std::str::FromStr for MarineForecast {
+  type Err = ApiError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Ok(ForeCast::from(crate::stream::decode(s)?).into())
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::types::{to_forecast, ForecastEntry, WarningKind};
+
+  const ENGLISH: &str = include_str!("../fixtures/marine_08000_e.xml");
+
+  #[test]
+  fn test_areas() {
+    let forecast = ENGLISH.parse::<MarineForecast>().unwrap();
+    assert_eq!(forecast.updated.as_deref(), Some("2023-09-05T08:00:00Z"));
+    assert_eq!(
+      forecast
+        .areas
+        .iter()
+        .map(|area| area.name.as_str())
+        .collect::<Vec<_>>(),
+      vec!["Western Lake Ontario", "Eastern Lake Ontario"]
+    );
+
+    let western = forecast.area("western lake ontario").unwrap();
+    assert_eq!(
+      western.issued.as_deref(),
+      Some("04:00 AM EDT 05 September 2023")
+    );
+    assert_eq!(western.warnings.len(), 1);
+    assert_eq!(western.warnings[0].warning.kind, WarningKind::StrongWind);
+    assert_eq!(western.max_wind(), Some(30));
+    assert_eq!(western.max_gust(), Some(30));
+    assert_eq!(western.max_waves(), Some(3.));
+    assert_eq!(western.visibility.as_deref(), Some("fair in showers"));
+    assert_eq!(western.extended.len(), 2);
+    assert_eq!(western.extended[0].period, "Thursday");
+    assert_eq!(western.extended[0].wind[0].to_string(), "NW 10-15 kn");
+    assert!(western.extended[1].wind.is_empty());
+    assert_eq!(
+      western.to_string(),
+      "Western Lake Ontario
+  Strong wind warning in effect
+  Wind: SW 15-25 kn, SW 20-30 kn, NW 15-20 kn gust 30, NW 10 kn
+  Waves: 1-2 m, 2-3 m, 0-1 m
+  Visibility: fair in showers
+  Thursday: Wind northwest 10 to 15 knots.
+  Friday: Wind light.
+"
+    );
+
+    let eastern = forecast.area("Eastern Lake Ontario").unwrap();
+    assert_eq!(eastern.warnings[0].warning.kind, WarningKind::Gale);
+    assert_eq!(eastern.max_wind(), Some(35));
+    assert_eq!(eastern.max_waves(), Some(4.5));
+    assert_eq!(eastern.visibility.as_deref(), Some("poor in fog"));
+  }
+
+  #[test]
+  fn test_french() {
+    let forecast = include_str!("../fixtures/marine_08000_f.xml")
+      .parse::<MarineForecast>()
+      .unwrap();
+    assert_eq!(forecast.areas.len(), 1);
+    let area = &forecast.areas[0];
+    assert_eq!(area.name, "Ouest du lac Ontario");
+    assert_eq!(
+      area.issued.as_deref(),
+      Some("04h00 HAE le 05 septembre 2023")
+    );
+    assert_eq!(area.warnings.len(), 1);
+    assert_eq!(area.warnings[0].warning.kind, WarningKind::StrongWind);
+    assert_eq!(
+      area
+        .wind
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>(),
+      vec!["SW 15-25 kn", "SW 20-30 kn", "NW 15-20 kn gust 30"]
+    );
+    assert_eq!(area.max_waves(), Some(3.));
+    assert_eq!(
+      area.visibility.as_deref(),
+      Some("passable dans les averses")
+    );
+  }
+
+  #[test]
+  fn test_warning_entries() {
+    // The warnings of a marine feed are the same entries as in a city feed.
+    let feed = ENGLISH.parse::<ForeCast>().unwrap();
+    let warnings = to_forecast(feed.entries())
+      .into_iter()
+      .filter_map(|entry| match entry {
+        ForecastEntry::Warning(entry) => entry.title.parse::<WeatherWarning>().ok(),
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[1].area.as_deref(), Some("Eastern Lake Ontario"));
+  }
+}
@@ -0,0 +1,596 @@
+//! METAR airport observations and TAF aerodrome forecasts.
+//!
+//! Airports report every hour or more often, so a METAR is usually fresher
+//! than the current conditions of the city feed. Reports are decoded into the
+//! same [`Observation`] model, with speeds in km/h, visibility in km and
+//! pressure in kPa. Cloud heights stay in feet, as pilots use them.
+use crate::{
+  parsers::{parse_metar, parse_taf},
+  types::{Celsius, CurrentForecast, Observation, Temperature, Wind},
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, marker::PhantomData};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("invalid report: {0}")]
+pub struct ReportParseError(pub(crate) String);
+
+/// Day of the month and UTC time, e.g. `071900Z`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ReportTime {
+  pub day: u8,
+  pub hour: u8,
+  pub minute: u8,
+}
+
+impl fmt::Display for ReportTime {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{:02} {:02}:{:02}Z", self.day, self.hour, self.minute)
+  }
+}
+
+/// Period a forecast applies to, e.g. `0718/0818`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Validity {
+  pub from: ReportTime,
+  pub to: ReportTime,
+}
+
+impl fmt::Display for Validity {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{} to {}", self.from, self.to)
+  }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Cover {
+  Few,
+  Scattered,
+  Broken,
+  Overcast,
+  /// Sky obscured, by fog or snow for instance.
+  VerticalVisibility,
+}
+
+impl Cover {
+  fn as_str(&self) -> &str {
+    match self {
+      Self::Few => "few clouds",
+      Self::Scattered => "scattered clouds",
+      Self::Broken => "broken clouds",
+      Self::Overcast => "overcast",
+      Self::VerticalVisibility => "vertical visibility",
+    }
+  }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Convective {
+  Cumulonimbus,
+  ToweringCumulus,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CloudLayer {
+  pub cover: Cover,
+  /// Height above ground in feet, when reported.
+  pub height: Option<u32>,
+  pub convective: Option<Convective>,
+}
+
+impl fmt::Display for CloudLayer {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.cover.as_str())?;
+    if let Some(height) = self.height {
+      write!(f, " {height} ft")?;
+    }
+    match self.convective {
+      Some(Convective::Cumulonimbus) => write!(f, " cumulonimbus"),
+      Some(Convective::ToweringCumulus) => write!(f, " towering cumulus"),
+      None => Ok(()),
+    }
+  }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Intensity {
+  Light,
+  Moderate,
+  Heavy,
+  /// Within 8 km of the airport, but not at it.
+  Vicinity,
+}
+
+/// Present weather group, e.g. `-SN` or `+TSRA`, keeping the two letter
+/// codes of the report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PresentWeather {
+  pub intensity: Intensity,
+  pub descriptor: Option<String>,
+  pub phenomena: Vec<String>,
+}
+
+fn phenomenon(code: &str) -> &str {
+  match code {
+    "DZ" => "drizzle",
+    "RA" => "rain",
+    "SN" => "snow",
+    "SG" => "snow grains",
+    "IC" => "ice crystals",
+    "PL" => "ice pellets",
+    "GR" => "hail",
+    "GS" => "small hail",
+    "UP" => "unknown precipitation",
+    "BR" => "mist",
+    "FG" => "fog",
+    "FU" => "smoke",
+    "VA" => "volcanic ash",
+    "DU" => "dust",
+    "SA" => "sand",
+    "HZ" => "haze",
+    "PY" => "spray",
+    "PO" => "dust whirls",
+    "SQ" => "squalls",
+    "FC" => "funnel cloud",
+    "SS" => "sandstorm",
+    "DS" => "duststorm",
+    code => code,
+  }
+}
+
+impl fmt::Display for PresentWeather {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let phenomena = self
+      .phenomena
+      .iter()
+      .map(|code| phenomenon(code))
+      .collect::<Vec<_>>()
+      .join(" and ");
+    let mut words = vec![];
+    match self.intensity {
+      Intensity::Light => words.push("light"),
+      Intensity::Heavy => words.push("heavy"),
+      Intensity::Moderate | Intensity::Vicinity => (),
+    }
+    let showers;
+    let thunderstorm;
+    match self.descriptor.as_deref() {
+      Some("SH") if phenomena.is_empty() => words.push("showers"),
+      Some("SH") => {
+        showers = format!("{phenomena} showers");
+        words.push(&showers);
+      }
+      Some("TS") if phenomena.is_empty() => words.push("thunderstorm"),
+      Some("TS") => {
+        thunderstorm = format!("thunderstorm with {phenomena}");
+        words.push(&thunderstorm);
+      }
+      descriptor => {
+        words.push(match descriptor {
+          Some("MI") => "shallow",
+          Some("PR") => "partial",
+          Some("BC") => "patches of",
+          Some("DR") => "drifting",
+          Some("BL") => "blowing",
+          Some("FZ") => "freezing",
+          _ => "",
+        });
+        words.push(&phenomena);
+      }
+    }
+    if self.intensity == Intensity::Vicinity {
+      words.push("in the vicinity");
+    }
+    let text = words
+      .into_iter()
+      .filter(|word| !word.is_empty())
+      .collect::<Vec<_>>()
+      .join(" ");
+    write!(f, "{text}")
+  }
+}
+
+/// Wind, visibility, weather and clouds, as reported by a METAR or forecast
+/// by a TAF group.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Conditions {
+  /// Wind in km/h. A variable wind has no direction.
+  pub wind: Option<Wind>,
+  /// Visibility in km.
+  pub visibility: Option<f32>,
+  pub weather: Vec<PresentWeather>,
+  /// Cloud layers, lowest first. Empty for a clear sky.
+  pub clouds: Vec<CloudLayer>,
+}
+
+impl Conditions {
+  /// Height of the lowest broken or overcast layer in feet.
+  pub fn ceiling(&self) -> Option<u32> {
+    self
+      .clouds
+      .iter()
+      .filter(|layer| {
+        matches!(
+          layer.cover,
+          Cover::Broken | Cover::Overcast | Cover::VerticalVisibility
+        )
+      })
+      .find_map(|layer| layer.height)
+  }
+
+  /// Weather, or sky cover when there is none, e.g. "Light snow".
+  pub fn condition(&self) -> Option<String> {
+    let condition = match self.weather.first() {
+      Some(weather) => weather.to_string(),
+      None => match self
+        .clouds
+        .iter()
+        .map(|layer| layer.cover)
+        .max_by_key(|c| *c as u8)
+      {
+        Some(Cover::Overcast | Cover::VerticalVisibility) => "overcast".into(),
+        Some(Cover::Broken) => "mostly cloudy".into(),
+        Some(Cover::Scattered) => "partly cloudy".into(),
+        Some(Cover::Few) => "mainly clear".into(),
+        None if self.visibility.is_some() => "clear".into(),
+        None => return None,
+      },
+    };
+    let mut chars = condition.chars();
+    chars
+      .next()
+      .map(|first| first.to_uppercase().chain(chars).collect())
+  }
+}
+
+fn wind_text(wind: &Wind) -> String {
+  if wind.speed < 1. {
+    return "calm".into();
+  }
+  let mut text = format!(
+    "{} {} km/h",
+    wind.direction.as_deref().unwrap_or("variable"),
+    wind.speed
+  );
+  if let Some(gust) = wind.gust {
+    text.push_str(&format!(" gust {gust}"));
+  }
+  text
+}
+
+impl fmt::Display for Conditions {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut parts = self
+      .weather
+      .iter()
+      .map(ToString::to_string)
+      .collect::<Vec<_>>();
+    if let Some(wind) = &self.wind {
+      parts.push(format!("wind {}", wind_text(wind)));
+    }
+    if let Some(visibility) = self.visibility {
+      parts.push(format!("visibility {visibility} km"));
+    }
+    parts.extend(self.clouds.iter().map(ToString::to_string));
+    write!(f, "{}", parts.join(", "))
+  }
+}
+
+/// Routine or special airport observation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Metar {
+  /// ICAO station identifier, e.g. CYOW.
+  pub station: String,
+  pub time: ReportTime,
+  pub conditions: Conditions,
+  pub temperature: Option<f32>,
+  pub dewpoint: Option<f32>,
+  /// Altimeter setting in kPa.
+  pub pressure: Option<f32>,
+  pub remarks: Option<String>,
+}
+
+impl std::str::FromStr for Metar {
+  type Err = ReportParseError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    parse_metar(s)
+  }
+}
+
+impl Metar {
+  pub fn observation(&self) -> Observation {
+    Observation {
+      observed_at: Some(format!("{} {}", self.station, self.time)),
+      condition: self.conditions.condition(),
+      temperature: self
+        .temperature
+        .map(|t| Temperature::Current(t, PhantomData)),
+      pressure: self.pressure,
+      visibility: self.conditions.visibility,
+      dewpoint: self.dewpoint.map(|t| Temperature::Current(t, PhantomData)),
+      wind: self.conditions.wind.clone(),
+      ceiling: self.conditions.ceiling(),
+      ..Default::default()
+    }
+  }
+
+  /// The report as current conditions.
+  pub fn current(&self) -> CurrentForecast {
+    CurrentForecast::default().with_observation(self.observation())
+  }
+}
+
+impl fmt::Display for Metar {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{} {}:", self.station, self.time)?;
+    let mut parts = vec![];
+    if let Some(condition) = self.conditions.condition() {
+      parts.push(condition);
+    }
+    if let Some(temperature) = self.temperature {
+      parts.push(format!(
+        "{}",
+        Temperature::<Celsius>::Current(temperature, PhantomData)
+      ));
+    }
+    if let Some(dewpoint) = self.dewpoint {
+      parts.push(format!(
+        "dewpoint {}",
+        Temperature::<Celsius>::Current(dewpoint, PhantomData)
+      ));
+    }
+    if let Some(wind) = &self.conditions.wind {
+      parts.push(format!("wind {}", wind_text(wind)));
+    }
+    if let Some(visibility) = self.conditions.visibility {
+      parts.push(format!("visibility {visibility} km"));
+    }
+    if let Some(ceiling) = self.conditions.ceiling() {
+      parts.push(format!("ceiling {ceiling} ft"));
+    }
+    if let Some(pressure) = self.pressure {
+      parts.push(format!("pressure {pressure} kPa"));
+    }
+    write!(f, " {}", parts.join(", "))
+  }
+}
+
+/// How a TAF group changes the forecast.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Change {
+  /// Conditions at the start of the forecast.
+  Initial,
+  /// `FM`: from a time on, replacing earlier groups.
+  From(ReportTime),
+  /// `BECMG`: changing gradually during the period.
+  Becoming,
+  /// `TEMPO`: temporary fluctuations during the period.
+  Temporary,
+  /// `PROB30` or `PROB40`, alone or with `TEMPO`.
+  Probability(u8),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TafGroup {
+  pub change: Change,
+  pub period: Option<Validity>,
+  pub conditions: Conditions,
+}
+
+impl fmt::Display for TafGroup {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match (self.change, self.period) {
+      (Change::From(time), _) => write!(f, "From {time}")?,
+      (Change::Becoming, Some(period)) => write!(f, "Becoming {period}")?,
+      (Change::Temporary, Some(period)) => write!(f, "Temporarily {period}")?,
+      (Change::Probability(percent), Some(period)) => write!(f, "{percent}% chance {period}")?,
+      (Change::Initial, _) | (_, None) => write!(f, "Initially")?,
+    }
+    write!(f, ": {}", self.conditions)
+  }
+}
+
+/// Aerodrome forecast.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Taf {
+  pub station: String,
+  pub issued: ReportTime,
+  pub valid: Validity,
+  pub groups: Vec<TafGroup>,
+  pub remarks: Option<String>,
+}
+
+impl std::str::FromStr for Taf {
+  type Err = ReportParseError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    parse_taf(s)
+  }
+}
+
+impl fmt::Display for Taf {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{} forecast issued {}, valid {}",
+      self.station, self.issued, self.valid
+    )?;
+    for group in &self.groups {
+      write!(f, "\n  {group}")?;
+    }
+    Ok(())
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "content")]
+pub enum Report {
+  Metar(Metar),
+  Taf(Taf),
+}
+
+impl std::str::FromStr for Report {
+  type Err = ReportParseError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    if s.trim_start().starts_with("TAF") {
+      s.parse().map(Self::Taf)
+    } else {
+      s.parse().map(Self::Metar)
+    }
+  }
+}
+
+impl fmt::Display for Report {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Metar(metar) => metar.fmt(f),
+      Self::Taf(taf) => taf.fmt(f),
+    }
+  }
+}
+
+/// Decode every report of a text, one per line. Indented lines continue the
+/// report above, as TAF groups are usually written.
+pub fn decode(text: &str) -> Vec<Result<Report, ReportParseError>> {
+  let mut reports: Vec<String> = vec![];
+  for line in text.lines() {
+    if line.trim().is_empty() {
+      continue;
+    }
+    match reports.last_mut() {
+      Some(report) if line.starts_with(char::is_whitespace) => {
+        report.push(' ');
+        report.push_str(line.trim());
+      }
+      _ => reports.push(line.trim().into()),
+    }
+  }
+  reports.iter().map(|report| report.parse()).collect()
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  const METAR: &str = "METAR CYOW 071900Z 03015G25KT 1 1/2SM -SN BR BKN012 OVC030 M03/M05 A2992 RMK SN5SC1SC2 SLP137 T10331052=";
+  const TAF: &str = "TAF CYOW 071738Z 0718/0818 03012KT P6SM -SN OVC030
+     TEMPO 0718/0722 2SM -SN OVC015
+     FM080200 36010KT P6SM BKN040
+     BECMG 0806/0808 32015G25KT
+     PROB30 0712/0716 1SM SN VV008
+     RMK NXT FCST BY 00Z=";
+
+  #[test]
+  fn test_metar() {
+    let metar = METAR.parse::<Metar>().unwrap();
+    assert_eq!(metar.station, "CYOW");
+    assert_eq!(
+      metar.time,
+      ReportTime {
+        day: 7,
+        hour: 19,
+        minute: 0
+      }
+    );
+    // Tenths from the remarks.
+    assert_eq!(metar.temperature, Some(-3.3));
+    assert_eq!(metar.dewpoint, Some(-5.2));
+    assert_eq!(metar.pressure, Some(101.3));
+    assert_eq!(metar.conditions.visibility, Some(2.4));
+    assert_eq!(metar.conditions.ceiling(), Some(1200));
+
+    let observation = metar.observation();
+    assert_eq!(observation.condition.as_deref(), Some("Light snow"));
+    assert_eq!(
+      observation.wind,
+      Some(Wind {
+        direction: Some("NNE".into()),
+        speed: 28.,
+        gust: Some(46.),
+      })
+    );
+    assert_eq!(observation.ceiling, Some(1200));
+    assert_eq!(
+      metar.current().celsius,
+      Some(Temperature::Current(-3.3, PhantomData))
+    );
+    assert_eq!(
+      metar.to_string(),
+      "CYOW 07 19:00Z: Light snow, -3.3°C, dewpoint -5.2°C, wind NNE 28 km/h gust 46, visibility 2.4 km, ceiling 1200 ft, pressure 101.3 kPa"
+    );
+  }
+
+  #[test]
+  fn test_metric_metar() {
+    let metar = "LFPG 071900Z VRB03KT CAVOK 08/02 Q1021 NOSIG"
+      .parse::<Metar>()
+      .unwrap();
+    assert_eq!(metar.conditions.visibility, Some(10.));
+    assert!(metar.conditions.clouds.is_empty());
+    assert_eq!(metar.conditions.wind.as_ref().unwrap().direction, None);
+    assert_eq!(metar.pressure, Some(102.1));
+    assert_eq!(metar.conditions.condition().as_deref(), Some("Clear"));
+
+    let metar = "EGLL 071920Z AUTO 24008MPS 9999 +TSRA FEW020CB 12/10 Q0998"
+      .parse::<Metar>()
+      .unwrap();
+    assert_eq!(metar.conditions.wind.as_ref().unwrap().speed, 29.);
+    assert_eq!(
+      metar.conditions.condition().as_deref(),
+      Some("Heavy thunderstorm with rain")
+    );
+    assert_eq!(
+      metar.conditions.clouds[0].convective,
+      Some(Convective::Cumulonimbus)
+    );
+
+    assert!("CYOW".parse::<Metar>().is_err());
+    assert!("hello world".parse::<Metar>().is_err());
+  }
+
+  #[test]
+  fn test_taf() {
+    let taf = TAF.parse::<Taf>().unwrap();
+    assert_eq!(taf.station, "CYOW");
+    assert_eq!(taf.valid.to.day, 8);
+    assert_eq!(taf.groups.len(), 5);
+    let changes = taf
+      .groups
+      .iter()
+      .map(|group| group.change)
+      .collect::<Vec<_>>();
+    assert_eq!(
+      changes,
+      [
+        Change::Initial,
+        Change::Temporary,
+        Change::From(ReportTime {
+          day: 8,
+          hour: 2,
+          minute: 0
+        }),
+        Change::Becoming,
+        Change::Probability(30),
+      ]
+    );
+    assert_eq!(taf.groups[1].conditions.ceiling(), Some(1500));
+    assert_eq!(taf.groups[4].conditions.ceiling(), Some(800));
+    assert_eq!(taf.remarks.as_deref(), Some("NXT FCST BY 00Z"));
+    assert_eq!(
+      taf.groups[2].to_string(),
+      "From 08 02:00Z: wind N 19 km/h, visibility 9.7 km, broken clouds 4000 ft"
+    );
+  }
+
+  #[test]
+  fn test_decode() {
+    let reports = decode(&format!("{METAR}\n\n{TAF}\nnot a report\n"));
+    assert_eq!(reports.len(), 3);
+    assert!(matches!(reports[0], Ok(Report::Metar(_))));
+    assert!(matches!(&reports[1], Ok(Report::Taf(taf)) if taf.groups.len() == 5));
+    assert!(reports[2].is_err());
+  }
+}
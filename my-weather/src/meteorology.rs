@@ -0,0 +1,229 @@
+//! Feels-like values, dewpoint and frost point computed from the temperature,
+//! humidity and wind, for observations that don't report them.
+//!
+//! Temperatures are in celsius and wind speeds in km/h, as in the feeds.
+use crate::types::{Celsius, Temperature};
+use std::marker::PhantomData;
+
+/// Magnus coefficients over water, from Alduchov and Eskridge.
+const MAGNUS_WATER: (f32, f32) = (17.625, 243.04);
+/// Magnus coefficients over ice.
+const MAGNUS_ICE: (f32, f32) = (22.46, 272.62);
+
+fn celsius(n: f32) -> Temperature<Celsius> {
+  Temperature::Current(n, PhantomData)
+}
+
+/// Wind chill index of Environment Canada and the NWS, for the wind at 10 m.
+/// None above 10 °C or below 4.8 km/h, where the index isn't defined.
+pub fn wind_chill(temperature: &Temperature<Celsius>, wind: f32) -> Option<Temperature<Celsius>> {
+  let t = temperature.value();
+  if t > 10. || wind < 4.8 {
+    return None;
+  }
+  let v = wind.powf(0.16);
+  Some(celsius(13.12 + 0.6215 * t - 11.37 * v + 0.3965 * t * v))
+}
+
+/// Humidex of Environment Canada. None below 20 °C, where it adds little
+/// to the temperature.
+pub fn humidex(
+  temperature: &Temperature<Celsius>,
+  dewpoint: &Temperature<Celsius>,
+) -> Option<Temperature<Celsius>> {
+  let t = temperature.value();
+  if t < 20. {
+    return None;
+  }
+  let vapour_pressure = 6.11 * (5417.753 * (1. / 273.16 - 1. / (273.15 + dewpoint.value()))).exp();
+  Some(celsius(t + 0.5555 * (vapour_pressure - 10.)))
+}
+
+/// Heat index of the NWS, from the Rothfusz regression with its adjustments
+/// for low and high humidity. None below 80 °F (26.7 °C), like the NWS.
+pub fn heat_index(
+  temperature: &Temperature<Celsius>,
+  humidity: f32,
+) -> Option<Temperature<Celsius>> {
+  let t = temperature.value() * 9. / 5. + 32.;
+  let rh = humidity;
+  if t < 80. {
+    return None;
+  }
+  let simple = 0.5 * (t + 61. + (t - 68.) * 1.2 + rh * 0.094);
+
+  let mut index = if (simple + t) / 2. < 80. {
+    simple
+  } else {
+    -42.379 + 2.049_015_2 * t + 10.143_331 * rh
+      - 0.224_755_4 * t * rh
+      - 6.837_83e-3 * t * t
+      - 5.481_717e-2 * rh * rh
+      + 1.228_74e-3 * t * t * rh
+      + 8.5282e-4 * t * rh * rh
+      - 1.99e-6 * t * t * rh * rh
+  };
+  if rh < 13. && (80. ..=112.).contains(&t) {
+    index -= (13. - rh) / 4. * ((17. - (t - 95.).abs()) / 17.).sqrt();
+  } else if rh > 85. && (80. ..=87.).contains(&t) {
+    index += (rh - 85.) / 10. * ((87. - t) / 5.);
+  }
+  Some(celsius((index - 32.) * 5. / 9.))
+}
+
+/// Dewpoint from the relative humidity in percent, undefined for a humidity
+/// of 0.
+pub fn dewpoint(temperature: &Temperature<Celsius>, humidity: f32) -> Option<Temperature<Celsius>> {
+  if humidity <= 0. || humidity.is_nan() {
+    return None;
+  }
+  let (a, b) = MAGNUS_WATER;
+  let t = temperature.value();
+  let gamma = (humidity / 100.).ln() + a * t / (b + t);
+  Some(celsius(b * gamma / (a - gamma)))
+}
+
+/// Relative humidity in percent from the dewpoint.
+pub fn relative_humidity(
+  temperature: &Temperature<Celsius>,
+  dewpoint: &Temperature<Celsius>,
+) -> f32 {
+  let (a, b) = MAGNUS_WATER;
+  let (t, td) = (temperature.value(), dewpoint.value());
+  (100. * ((a * td / (b + td)) - (a * t / (b + t))).exp()).min(100.)
+}
+
+/// Temperature at which the air's moisture turns to frost, from the
+/// dewpoint. None above freezing, where dew forms first.
+pub fn frost_point(dewpoint: &Temperature<Celsius>) -> Option<Temperature<Celsius>> {
+  let td = dewpoint.value();
+  if td > 0. {
+    return None;
+  }
+  let (a, b) = MAGNUS_WATER;
+  let (a_ice, b_ice) = MAGNUS_ICE;
+  // Log of the vapour pressure over its value at 0 °C.
+  let ln = a * td / (b + td);
+  Some(celsius(b_ice * ln / (a_ice - ln)))
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn rounded(temperature: Option<Temperature<Celsius>>) -> Option<f32> {
+    temperature.map(|t| t.value().round())
+  }
+
+  #[test]
+  fn test_wind_chill() {
+    // Environment Canada wind chill table.
+    for (t, wind, expected) in [
+      (0., 10., -3.),
+      (-10., 20., -18.),
+      (-20., 30., -33.),
+      (-40., 60., -64.),
+      (-3.4, 17., -9.),
+    ] {
+      assert_eq!(
+        rounded(wind_chill(&celsius(t), wind)),
+        Some(expected),
+        "{t} {wind}"
+      );
+    }
+    assert_eq!(wind_chill(&celsius(15.), 30.), None);
+    assert_eq!(wind_chill(&celsius(-10.), 2.), None);
+  }
+
+  #[test]
+  fn test_humidex() {
+    assert_eq!(rounded(humidex(&celsius(30.), &celsius(15.))), Some(34.));
+    assert_eq!(rounded(humidex(&celsius(35.), &celsius(25.))), Some(47.));
+    assert_eq!(humidex(&celsius(15.), &celsius(10.)), None);
+  }
+
+  #[test]
+  fn test_heat_index() {
+    // NWS heat index chart, in °F.
+    let fahrenheit = |t: f32, rh| {
+      heat_index(&celsius((t - 32.) * 5. / 9.), rh).map(|hi| (hi.value() * 9. / 5. + 32.).round())
+    };
+    assert_eq!(fahrenheit(90., 50.), Some(95.));
+    assert_eq!(fahrenheit(96., 65.), Some(121.));
+    assert_eq!(fahrenheit(100., 40.), Some(109.));
+    assert_eq!(fahrenheit(86., 90.), Some(105.));
+    assert_eq!(fahrenheit(80., 40.), Some(80.));
+    assert_eq!(fahrenheit(70., 90.), None);
+  }
+
+  #[test]
+  fn test_dewpoint() {
+    let tenths = |t: Option<Temperature<Celsius>>| t.map(|t| (t.value() * 10.).round() / 10.);
+    assert_eq!(tenths(dewpoint(&celsius(20.), 50.)), Some(9.3));
+    assert_eq!(tenths(dewpoint(&celsius(30.), 70.)), Some(23.9));
+    assert_eq!(tenths(dewpoint(&celsius(25.), 100.)), Some(25.));
+    assert_eq!(dewpoint(&celsius(25.), 0.), None);
+    assert_eq!(
+      relative_humidity(&celsius(20.), &celsius(9.26)).round(),
+      50.
+    );
+    assert_eq!(relative_humidity(&celsius(20.), &celsius(21.)), 100.);
+  }
+
+  #[test]
+  fn test_frost_point() {
+    let tenths = |t: Option<Temperature<Celsius>>| t.map(|t| (t.value() * 10.).round() / 10.);
+    assert_eq!(tenths(frost_point(&celsius(-10.))), Some(-8.9));
+    assert_eq!(tenths(frost_point(&celsius(-20.))), Some(-17.9));
+    assert_eq!(tenths(frost_point(&celsius(0.))), Some(0.));
+    assert_eq!(frost_point(&celsius(5.)), None);
+  }
+
+  #[test]
+  fn test_observation() {
+    use crate::types::{Observation, Wind};
+
+    // A winter METAR gives no humidity, wind chill or humidex.
+    let winter = Observation {
+      temperature: Some(celsius(-3.4)),
+      dewpoint: Some(celsius(-5.2)),
+      wind: Some(Wind {
+        direction: Some("ENE".into()),
+        speed: 17.,
+        gust: None,
+      }),
+      ..Default::default()
+    }
+    .with_derived();
+    assert_eq!(winter.humidity, Some(87.));
+    assert_eq!(winter.wind_chill, Some(-9.));
+    assert_eq!(winter.humidex, None);
+    assert_eq!(winter.heat_index, None);
+    assert_eq!(winter.frost_point, Some(celsius(-4.6)));
+
+    let summer = Observation {
+      temperature: Some(celsius(32.)),
+      humidity: Some(60.),
+      humidex: Some(41.),
+      ..Default::default()
+    }
+    .with_derived();
+    assert_eq!(summer.dewpoint, Some(celsius(23.3)));
+    // Reported values are kept.
+    assert_eq!(summer.humidex, Some(41.));
+    assert_eq!(summer.heat_index, Some(37.));
+    assert_eq!(summer.wind_chill, None);
+    assert_eq!(summer.frost_point, None);
+
+    // A humidity of 0 leaves the dewpoint and what depends on it unknown.
+    let dry = Observation {
+      temperature: Some(celsius(32.)),
+      humidity: Some(0.),
+      ..Default::default()
+    }
+    .with_derived();
+    assert_eq!(dry.dewpoint, None);
+    assert_eq!(dry.humidex, None);
+    assert_eq!(dry.frost_point, None);
+  }
+}
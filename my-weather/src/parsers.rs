@@ -1,16 +1,35 @@
 //! Parser combinator functions for parsing text into structured types.
-use crate::types::{CurrentForecast, DayNight, DayOfWeek, Forecast, Temperature};
+use crate::{
+  types::{
+    CurrentForecast, DayNight, DayOfWeek, Forecast, Temperature, TemperatureDetail, TimeQualifier,
+  },
+  Language,
+};
 use nom::{
   branch::alt,
   bytes::complete::{tag, take_until},
-  character::complete::{char, digit1, space0},
-  combinator::{map, map_res, opt, recognize, value},
-  error::context,
-  sequence::{delimited, preceded, tuple},
+  character::complete::{char, digit1, space0, space1},
+  combinator::{eof, map, map_res, opt, recognize, rest, value, verify},
+  error::{context, Error, ErrorKind},
+  multi::many0,
+  sequence::{delimited, preceded, separated_pair, terminated, tuple},
   IResult,
 };
 use std::marker::PhantomData;
 
+mod french;
+mod marine;
+mod metar;
+mod observation;
+mod timeline;
+mod warning;
+
+pub use marine::{parse_marine_wind, parse_visibility, parse_waves};
+pub use metar::{parse_metar, parse_taf};
+pub use observation::parse_observation;
+pub use timeline::parse_timeline;
+pub use warning::parse_warning;
+
 /// Parse an optionally signed number.
 fn parse_number(input: &str) -> IResult<&str, f32> {
   let sign = context("sign", alt((tag("minus"), tag("plus"), tag("zero"))));
@@ -38,31 +57,181 @@ fn parse_number(input: &str) -> IResult<&str, f32> {
 fn parse_temp<Unit>(input: &str) -> IResult<&str, Temperature<Unit>> {
   let high_parser = context(
     "high_parser",
-    map(
-      preceded(
-        alt((
-          tag("High "),
-          tag("Temperature steady near "),
-          tag("Temperature rising to "),
-        )),
-        parse_number,
-      ),
-      |n| Temperature::High(n, PhantomData),
-    ),
+    map(preceded(tag("High "), parse_number), |n| {
+      Temperature::High(n, PhantomData)
+    }),
   );
 
   let low_parser = context(
     "low_parser",
+    map(preceded(tag("Low "), parse_number), |n| {
+      Temperature::Low(n, PhantomData)
+    }),
+  );
+
+  let steady_parser = context(
+    "steady_parser",
     map(
-      preceded(
-        alt((tag("Low "), tag("Temperature falling to "))),
-        parse_number,
-      ),
-      |n| Temperature::Low(n, PhantomData),
+      preceded(tag("Temperature steady near "), parse_number),
+      |n| Temperature::Steady(n, PhantomData),
+    ),
+  );
+
+  let rising_parser = context(
+    "rising_parser",
+    map(
+      tuple((
+        preceded(tag("Temperature rising to "), parse_number),
+        opt(preceded(space1, parse_trend_time)),
+      )),
+      |(n, qualifier)| Temperature::Rising(n, qualifier, PhantomData),
     ),
   );
 
-  context("parse_temp", alt((high_parser, low_parser)))(input)
+  let falling_parser = context(
+    "falling_parser",
+    map(
+      tuple((
+        preceded(tag("Temperature falling to "), parse_number),
+        opt(preceded(space1, parse_trend_time)),
+      )),
+      |(n, qualifier)| Temperature::Falling(n, qualifier, PhantomData),
+    ),
+  );
+
+  context(
+    "parse_temp",
+    alt((
+      high_parser,
+      low_parser,
+      steady_parser,
+      rising_parser,
+      falling_parser,
+    )),
+  )(input)
+}
+
+/// Text up to the end of the sentence or the start of another qualifier
+/// ("with" / "avec").
+fn parse_phrase(input: &str) -> IResult<&str, &str> {
+  let end = input.find('.').unwrap_or(input.len());
+  let end = input[..end]
+    .find(" with ")
+    .or_else(|| input[..end].find(" avec "))
+    .unwrap_or(end);
+  let (phrase, rest) = input.split_at(end);
+  let phrase = phrase.trim_end();
+  if phrase.is_empty() {
+    Err(nom::Err::Error(Error::new(input, ErrorKind::TakeTill1)))
+  } else {
+    Ok((rest, phrase))
+  }
+}
+
+/// Parse a time of day qualifier such as "by morning" or "in the afternoon".
+fn parse_time_qualifier(input: &str) -> IResult<&str, TimeQualifier> {
+  let by = alt((
+    value(TimeQualifier::ByMorning, tag("by morning")),
+    value(TimeQualifier::ByNoon, tag("by noon")),
+    value(TimeQualifier::ByAfternoon, tag("by afternoon")),
+    value(TimeQualifier::ByEvening, tag("by evening")),
+    value(TimeQualifier::ByMidnight, tag("by midnight")),
+  ));
+  let during = alt((
+    value(
+      TimeQualifier::InTheMorning,
+      alt((tag("in the morning"), tag("early in the morning"))),
+    ),
+    value(
+      TimeQualifier::InTheAfternoon,
+      alt((tag("in the afternoon"), tag("late in the afternoon"))),
+    ),
+    value(TimeQualifier::InTheEvening, tag("in the evening")),
+    value(
+      TimeQualifier::ThisMorning,
+      alt((tag("this morning"), tag("early this morning"))),
+    ),
+    value(
+      TimeQualifier::ThisAfternoon,
+      alt((tag("this afternoon"), tag("late this afternoon"))),
+    ),
+    value(
+      TimeQualifier::ThisEvening,
+      alt((tag("this evening"), tag("early this evening"))),
+    ),
+    value(TimeQualifier::Overnight, tag("overnight")),
+    value(TimeQualifier::AfterMidnight, tag("after midnight")),
+    value(TimeQualifier::NearNoon, tag("near noon")),
+    value(TimeQualifier::NearMidnight, tag("near midnight")),
+  ));
+  context("time_qualifier", alt((by, during)))(input)
+}
+
+/// Parse the time a temperature trend is reached, keeping phrasings the
+/// grammar doesn't know such as "late this evening" as written.
+fn parse_trend_time(input: &str) -> IResult<&str, TimeQualifier> {
+  alt((
+    parse_time_qualifier,
+    map(parse_phrase, |phrase| TimeQualifier::Other(phrase.into())),
+  ))(input)
+}
+
+/// Parse an area qualifier such as "near the coast" or "inland".
+fn parse_area_qualifier(input: &str) -> IResult<&str, &str> {
+  let start = alt((
+    tag("near "),
+    tag("along "),
+    tag("over "),
+    tag("on the "),
+    tag("in "),
+    tag("at "),
+    tag("inland"),
+    tag("elsewhere"),
+  ));
+  context(
+    "area_qualifier",
+    recognize(tuple((start, opt(parse_phrase)))),
+  )(input)
+}
+
+/// Parse a secondary value or qualifier following the main temperature.
+fn parse_temp_detail(input: &str) -> IResult<&str, TemperatureDetail> {
+  let except = map(
+    tuple((tag("except "), parse_number, space1, parse_phrase)),
+    |(_, celsius, _, area)| TemperatureDetail::Except {
+      celsius,
+      area: area.into(),
+    },
+  );
+  let rising = map(
+    tuple((
+      tag("with temperature rising to "),
+      parse_number,
+      opt(preceded(space1, parse_trend_time)),
+    )),
+    |(_, celsius, time)| TemperatureDetail::Rising { celsius, time },
+  );
+  let falling = map(
+    tuple((
+      tag("with temperature falling to "),
+      parse_number,
+      opt(preceded(space1, parse_trend_time)),
+    )),
+    |(_, celsius, time)| TemperatureDetail::Falling { celsius, time },
+  );
+  let area = map(parse_area_qualifier, |area| {
+    TemperatureDetail::Area(area.into())
+  });
+
+  context(
+    "parse_temp_detail",
+    preceded(space1, alt((except, rising, falling, area))),
+  )(input)
+}
+
+/// Parse all qualifiers following the main temperature.
+fn parse_temp_details(input: &str) -> IResult<&str, Vec<TemperatureDetail>> {
+  many0(parse_temp_detail)(input)
 }
 
 fn parse_description(input: &str) -> IResult<&str, &str> {
@@ -100,17 +269,60 @@ fn parse_day_of_week(input: &str) -> IResult<&str, DayOfWeek> {
   parser(input)
 }
 
-/// Parses a future forecast.
-pub fn parse_forecast<Unit>(input: &str) -> IResult<&str, Forecast> {
+/// Language of a future forecast title, from the day of the week it starts
+/// with.
+pub fn parse_title_language(input: &str) -> Option<Language> {
+  if parse_day_of_week(input).is_ok() {
+    Some(Language::English)
+  } else if french::parse_day_of_week(input).is_ok() {
+    Some(Language::French)
+  } else {
+    None
+  }
+}
+
+/// Parses a future forecast from either an English or French feed title.
+pub fn parse_forecast(input: &str) -> IResult<&str, Forecast> {
+  let (input, mut forecast) = alt((parse_english_forecast, french::parse_forecast))(input)?;
+  forecast.pop = find_pop(input);
+  Ok((input, forecast))
+}
+
+/// Parse a probability of precipitation, e.g. "POP 40%" or "PdP 40%".
+fn parse_pop(input: &str) -> IResult<&str, u8> {
+  preceded(
+    tuple((alt((tag("POP"), tag("PdP"))), space1)),
+    terminated(map_res(digit1, str::parse), char('%')),
+  )(input)
+}
+
+/// Probability of precipitation from the sentences following the temperature.
+fn find_pop(input: &str) -> Option<u8> {
+  input
+    .split(". ")
+    .find_map(|sentence| parse_pop(sentence.trim_start_matches('.').trim()).ok())
+    .map(|(_, pop)| pop)
+}
+
+fn parse_english_forecast(input: &str) -> IResult<&str, Forecast> {
   let (input, day_of_week) = parse_day_of_week(input)?;
   let (input, day_night) = parse_day_night(input)?;
-  let parser = tuple((map(parse_description, String::from), parse_temp));
-  let mut parser = map(parser, |(description, temp)| Forecast {
+  let parser = tuple((
+    map(parse_description, String::from),
+    parse_temp,
+    parse_temp_details,
+  ));
+  let mut parser = map(parser, |(description, temp, details)| Forecast {
     day: day_night,
     day_of_week,
-    celsius: temp,
+    celsius: temp.clone(),
     fahrenheit: temp.into(),
     description,
+    details,
+    timeline: vec![],
+    pop: None,
+    normal: None,
+    almanac: None,
   });
   parser(input)
 }
@@ -129,23 +341,61 @@ fn parse_signed_number(input: &str) -> IResult<&str, f32> {
   parser(input)
 }
 
-/// Parses the current forecast.
+/// Parses the current forecast from either an English or French feed title.
 pub fn parse_current_forecast(input: &str) -> IResult<&str, CurrentForecast> {
-  let (input, description) = delimited(
-    tag("Current Conditions: "),
-    map(take_until(", "), String::from),
-    tag(", "),
-  )(input)?;
-  let (input, temperature) = map(parse_signed_number, |n| {
-    Temperature::Current(n, PhantomData)
-  })(input)?;
+  alt((
+    parse_english_current_forecast,
+    french::parse_current_forecast,
+  ))(input)
+}
+
+fn parse_english_current_forecast(input: &str) -> IResult<&str, CurrentForecast> {
+  preceded(tag("Current Conditions: "), |input| {
+    parse_current_fields(input, parse_signed_number)
+  })(input)
+}
+
+/// Phrases used in place of a condition the station did not report.
+const NOT_REPORTED: [&str; 4] = [
+  "not observed",
+  "not available",
+  "non observé",
+  "non disponible",
+];
+
+/// Parses "<description>, <temperature>" after the current conditions label,
+/// where either the description or the temperature may be missing.
+fn parse_current_fields(
+  input: &str,
+  number: fn(&str) -> IResult<&str, f32>,
+) -> IResult<&str, CurrentForecast> {
+  let temperature = |input| terminated(number, tuple((opt(tag("°C")), space0, eof)))(input);
+  let (input, (description, n)) = alt((
+    map(temperature, |n| (None, Some(n))),
+    map(
+      separated_pair(take_until(", "), tag(", "), temperature),
+      |(description, n)| (Some(description), Some(n)),
+    ),
+    map(
+      verify(rest, |description: &str| !description.trim().is_empty()),
+      |description| (Some(description), None),
+    ),
+  ))(input)?;
+
+  let description = description.map(str::trim).filter(|description| {
+    let lower = description.to_lowercase();
+    !NOT_REPORTED.iter().any(|phrase| lower.starts_with(phrase))
+  });
+  let celsius = n.map(|n| Temperature::Current(n, PhantomData));
 
   Ok((
     input,
     CurrentForecast {
-      description,
-      celsius: temperature,
-      fahrenheit: temperature.into(),
+      description: description.map(String::from),
+      fahrenheit: celsius.clone().map(Into::into),
+      celsius,
+      observation: None,
+      almanac: None,
     },
   ))
 }
@@ -158,9 +408,9 @@ mod test {
   use super::*;
 
   fn test_parse_entry(input: &str, expected: (String, Temperature<Celsius>)) {
-    let (_, forecast) = parse_forecast::<Celsius>(input).unwrap();
+    let (_, forecast) = parse_forecast(input).unwrap();
     assert_eq!(forecast.description, expected.0);
-    assert_eq!(forecast.celsius, expected.1); // this causes a stack overflow?
+    assert_eq!(forecast.celsius, expected.1);
   }
 
   #[test]
@@ -169,16 +419,23 @@ mod test {
 
     let result = parse_temp::<Celsius>(test).unwrap();
     assert!(matches!(result.1, Temperature::Low(n, _) if n == -9.));
+
+    let test = "Temperature rising to plus 2 this evening.";
+    let (rest, result) = parse_temp::<Celsius>(test).unwrap();
+    assert_eq!(rest, ".");
+    assert_eq!(
+      result,
+      Temperature::Rising(2., Some(TimeQualifier::ThisEvening), PhantomData)
+    );
   }
 
   #[test]
-  #[ignore]
   fn test_parse_temp() {
     test_parse_entry(
       "Saturday: A mix of sun and cloud. Temperature steady near minus 1.",
       (
         "A mix of sun and cloud.".into(),
-        Temperature::<Celsius>::High(-1., PhantomData),
+        Temperature::<Celsius>::Steady(-1., PhantomData),
       ),
     );
     test_parse_entry(
@@ -243,7 +500,7 @@ mod test {
   #[test]
   fn parse_full() {
     let test = "Monday: Sunny. High zero.";
-    let (_, forecast) = parse_forecast::<Celsius>(test).unwrap();
+    let (_, forecast) = parse_forecast(test).unwrap();
 
     assert!(matches!(
         forecast,
@@ -253,11 +510,12 @@ mod test {
             description,
             day: DayNight::Day,
             day_of_week: DayOfWeek::Monday,
+            ..
         } if c == 0. && description == "Sunny."
     ));
 
     let test = "Sunday night: Cloudy periods. Low minus 9.";
-    let (_, forecast) = parse_forecast::<Celsius>(test).unwrap();
+    let (_, forecast) = parse_forecast(test).unwrap();
 
     assert!(matches!(
         forecast,
@@ -267,11 +525,12 @@ mod test {
             description,
             day: DayNight::Night,
             day_of_week: DayOfWeek::Sunday,
+            ..
         } if n == -9. && description == "Cloudy periods."
     ));
 
     let test = "Thursday: Snow. High plus 2.";
-    let (_, forecast) = parse_forecast::<Celsius>(test).unwrap();
+    let (_, forecast) = parse_forecast(test).unwrap();
 
     assert!(matches!(
       forecast,
@@ -281,11 +540,12 @@ mod test {
         description,
         day: DayNight::Day,
         day_of_week: DayOfWeek::Thursday,
+        ..
       } if n == 2. && description == "Snow."
     ));
 
     let test = "Saturday: Chance of flurries. High minus 3. POP 60%";
-    let (_, forecast) = parse_forecast::<Celsius>(test).unwrap();
+    let (_, forecast) = parse_forecast(test).unwrap();
 
     assert!(matches!(
       forecast,
@@ -295,54 +555,58 @@ mod test {
         description,
         day: DayNight::Day,
         day_of_week: DayOfWeek::Saturday,
+        ..
       } if n == -3. && description == "Chance of flurries."
     ));
 
     let test =
       "Wednesday night: Chance of flurries. Temperature rising to minus 2 by morning. POP 40%";
-    let (_, forecast) = parse_forecast::<Celsius>(test).unwrap();
+    let (_, forecast) = parse_forecast(test).unwrap();
 
     assert!(matches!(
       forecast,
       Forecast {
-        celsius: Temperature::High(n, _),
-        fahrenheit: Temperature::High(..),
+        celsius: Temperature::Rising(n, Some(TimeQualifier::ByMorning), _),
+        fahrenheit: Temperature::Rising(..),
         description,
         day: DayNight::Night,
         day_of_week: DayOfWeek::Wednesday,
+        ..
       } if n == -2. && description == "Chance of flurries."
     ));
 
     let test = "Friday: Snow. Temperature falling to minus 3 in the afternoon.";
-    let (_, forecast) = parse_forecast::<Celsius>(test).unwrap();
+    let (_, forecast) = parse_forecast(test).unwrap();
 
     assert!(matches!(
         forecast,
         Forecast {
-            celsius: Temperature::Low(n, _),
-            fahrenheit: Temperature::Low(..),
+            celsius: Temperature::Falling(n, Some(TimeQualifier::InTheAfternoon), _),
+            fahrenheit: Temperature::Falling(..),
             description,
             day: DayNight::Day,
             day_of_week: DayOfWeek::Friday,
+            ..
         } if n == -3. && description == "Snow."
     ));
 
     let test = "Wednesday night: Snow at times heavy. Temperature rising to zero by morning.";
-    let (_, forecast) = parse_forecast::<Celsius>(test).unwrap();
+    let (_, forecast) = parse_forecast(test).unwrap();
 
     assert!(matches!(
         forecast,
         Forecast {
-            celsius: Temperature::High(n, _),
-            fahrenheit: Temperature::High(..),
+            celsius: Temperature::Rising(n, Some(TimeQualifier::ByMorning), _),
+            fahrenheit: Temperature::Rising(..),
             description,
             day: DayNight::Night,
             day_of_week: DayOfWeek::Wednesday,
+            ..
         } if n == 0. && description == "Snow at times heavy."
     ));
 
     let test = "Wednesday: Chance of showers. High 6. POP 40%";
-    let (_, forecast) = parse_forecast::<Celsius>(test).unwrap();
+    let (_, forecast) = parse_forecast(test).unwrap();
 
     assert!(matches!(
         forecast,
@@ -351,12 +615,13 @@ mod test {
             fahrenheit: Temperature::High(..),
             description,
             day: DayNight::Day,
-            day_of_week: DayOfWeek::Wednesday
+            day_of_week: DayOfWeek::Wednesday,
+            ..
         } if n == 6. && description == "Chance of showers."
     ));
 
     let test = "Thursday: A mix of sun and cloud. High 6.";
-    let (_, forecast) = parse_forecast::<Celsius>(test).unwrap();
+    let (_, forecast) = parse_forecast(test).unwrap();
 
     assert!(matches!(
         forecast,
@@ -365,7 +630,8 @@ mod test {
             fahrenheit: Temperature::High(..),
             description,
             day: DayNight::Day,
-            day_of_week: DayOfWeek::Thursday
+            day_of_week: DayOfWeek::Thursday,
+            ..
         } if n == 6. && description == "A mix of sun and cloud."
     ));
   }
@@ -373,7 +639,7 @@ mod test {
   #[test]
   fn test_parse_positive() {
     let test = "Thursday: Snow. High plus 2.";
-    let (_, forecast) = parse_forecast::<Celsius>(test).unwrap();
+    let (_, forecast) = parse_forecast(test).unwrap();
 
     assert!(matches!(
       forecast,
@@ -383,10 +649,211 @@ mod test {
         description,
         day: DayNight::Day,
         day_of_week: DayOfWeek::Thursday,
+        ..
       } if n == 2. && description == "Snow."
     ));
   }
 
+  #[test]
+  fn test_parse_details() {
+    let test = "Tuesday: Sunny. High 15 except 20 inland.";
+    let (rest, forecast) = parse_forecast(test).unwrap();
+    assert_eq!(rest, ".");
+    assert!(matches!(forecast.celsius, Temperature::High(n, _) if n == 15.));
+    assert_eq!(
+      forecast.details,
+      vec![TemperatureDetail::Except {
+        celsius: 20.,
+        area: "inland".into()
+      }]
+    );
+
+    let test = "Tuesday night: Clear. Low minus 5 with temperature rising to plus 2 by morning.";
+    let (rest, forecast) = parse_forecast(test).unwrap();
+    assert_eq!(rest, ".");
+    assert!(matches!(forecast.celsius, Temperature::Low(n, _) if n == -5.));
+    assert_eq!(
+      forecast.details,
+      vec![TemperatureDetail::Rising {
+        celsius: 2.,
+        time: Some(TimeQualifier::ByMorning)
+      }]
+    );
+
+    let test = "Thursday: Cloudy. High plus 3 near the coast.";
+    let (_, forecast) = parse_forecast(test).unwrap();
+    assert_eq!(
+      forecast.details,
+      vec![TemperatureDetail::Area("near the coast".into())]
+    );
+
+    let test = "Tuesday: Rain. High 6 with temperature falling to zero in the afternoon.";
+    let (_, forecast) = parse_forecast(test).unwrap();
+    assert_eq!(
+      forecast.details,
+      vec![TemperatureDetail::Falling {
+        celsius: 0.,
+        time: Some(TimeQualifier::InTheAfternoon)
+      }]
+    );
+
+    let test = "Friday night: Cloudy. Low minus 15 except minus 25 inland.";
+    let (_, forecast) = parse_forecast(test).unwrap();
+    assert_eq!(
+      forecast.details,
+      vec![TemperatureDetail::Except {
+        celsius: -25.,
+        area: "inland".into()
+      }]
+    );
+
+    let test = "Friday: Snow. Temperature falling to minus 3 in the afternoon.";
+    let (rest, forecast) = parse_forecast(test).unwrap();
+    assert_eq!(rest, ".");
+    assert!(forecast.details.is_empty());
+
+    // Phrasings the grammar doesn't know are kept as written.
+    let test = "Monday night: Snow. Temperature rising to zero late this evening.";
+    let (rest, forecast) = parse_forecast(test).unwrap();
+    assert_eq!(rest, ".");
+    assert_eq!(
+      forecast.celsius.qualifier(),
+      Some(TimeQualifier::Other("late this evening".into()))
+    );
+
+    let test =
+      "Tuesday night: Clear. Low minus 5 with temperature rising to plus 2 before morning.";
+    let (rest, forecast) = parse_forecast(test).unwrap();
+    assert_eq!(rest, ".");
+    assert_eq!(
+      forecast.details,
+      vec![TemperatureDetail::Rising {
+        celsius: 2.,
+        time: Some(TimeQualifier::Other("before morning".into()))
+      }]
+    );
+  }
+
+  #[test]
+  fn test_parse_pop() {
+    let (_, forecast) =
+      parse_forecast("Monday: Chance of flurries. High minus 3. POP 60%").unwrap();
+    assert_eq!(forecast.pop, Some(60));
+
+    let (_, forecast) =
+      parse_forecast("Lundi: Possibilité d'averses de neige. Maximum moins 3. PdP 60%").unwrap();
+    assert_eq!(forecast.pop, Some(60));
+
+    let (_, forecast) = parse_forecast("Sunday: Cloudy. High minus 2.").unwrap();
+    assert_eq!(forecast.pop, None);
+  }
+
+  /// Time qualifier named as `TimeQualifier::as_str` names it.
+  fn time_qualifier(name: &str) -> TimeQualifier {
+    use TimeQualifier::*;
+    [
+      ByMorning,
+      ByNoon,
+      ByAfternoon,
+      ByEvening,
+      ByMidnight,
+      InTheMorning,
+      InTheAfternoon,
+      InTheEvening,
+      ThisMorning,
+      ThisAfternoon,
+      ThisEvening,
+      Overnight,
+      AfterMidnight,
+      NearNoon,
+      NearMidnight,
+    ]
+    .into_iter()
+    .find(|qualifier| qualifier.as_str() == name)
+    .unwrap_or_else(|| Other(name.into()))
+  }
+
+  /// Value and time of a trend, e.g. "-6 after midnight".
+  fn trend(text: &str) -> (f32, Option<TimeQualifier>) {
+    let (celsius, time) = match text.split_once(' ') {
+      Some((celsius, time)) => (celsius, Some(time_qualifier(time))),
+      None => (text, None),
+    };
+    (celsius.parse().unwrap(), time)
+  }
+
+  /// Main temperature written as "high 15", "rising 2 by morning", etc.
+  fn corpus_temperature(text: &str) -> Temperature<Celsius> {
+    let (kind, value) = text.split_once(' ').unwrap();
+    let celsius = || value.parse().unwrap();
+    match kind {
+      "high" => Temperature::High(celsius(), PhantomData),
+      "low" => Temperature::Low(celsius(), PhantomData),
+      "steady" => Temperature::Steady(celsius(), PhantomData),
+      "rising" => {
+        let (celsius, time) = trend(value);
+        Temperature::Rising(celsius, time, PhantomData)
+      }
+      "falling" => {
+        let (celsius, time) = trend(value);
+        Temperature::Falling(celsius, time, PhantomData)
+      }
+      _ => panic!("unknown temperature {text}"),
+    }
+  }
+
+  /// Detail written as "except 20 inland", "area near the coast", etc.
+  fn corpus_detail(text: &str) -> TemperatureDetail {
+    let (kind, value) = text.split_once(' ').unwrap();
+    match kind {
+      "except" => {
+        let (celsius, area) = value.split_once(' ').unwrap();
+        TemperatureDetail::Except {
+          celsius: celsius.parse().unwrap(),
+          area: area.into(),
+        }
+      }
+      "rising" => {
+        let (celsius, time) = trend(value);
+        TemperatureDetail::Rising { celsius, time }
+      }
+      "falling" => {
+        let (celsius, time) = trend(value);
+        TemperatureDetail::Falling { celsius, time }
+      }
+      "area" => TemperatureDetail::Area(value.into()),
+      _ => panic!("unknown detail {text}"),
+    }
+  }
+
+  #[test]
+  fn test_title_corpus() {
+    // Each line is a title, its temperature and its details separated by
+    // semicolons, tab separated. Times are named in English in both files.
+    let corpus = [
+      include_str!("../fixtures/titles_e.txt"),
+      include_str!("../fixtures/titles_f.txt"),
+    ];
+    for line in corpus.iter().flat_map(|corpus| corpus.lines()) {
+      let mut columns = line.split('\t');
+      let (Some(title), Some(temperature), Some(details)) =
+        (columns.next(), columns.next(), columns.next())
+      else {
+        panic!("invalid corpus line {line}");
+      };
+      let (rest, forecast) = parse_forecast(title).unwrap_or_else(|e| panic!("{title}: {e}"));
+      assert!(rest.starts_with('.'), "{title}: unparsed \"{rest}\"");
+      assert_eq!(forecast.celsius, corpus_temperature(temperature), "{title}");
+      let details = details
+        .split(';')
+        .map(str::trim)
+        .filter(|detail| !detail.is_empty())
+        .map(corpus_detail)
+        .collect::<Vec<_>>();
+      assert_eq!(forecast.details, details, "{title}");
+    }
+  }
+
   #[test]
   fn test_parse_current() {
     let test = "Current Conditions: Light Snow, -3.4°C";
@@ -394,7 +861,28 @@ mod test {
     let (_, result) = parse_current_forecast(test).unwrap();
 
     assert!(
-      matches!(result, CurrentForecast { celsius: Temperature::Current(n, _), description, .. } if n == -3.4 && description == "Light Snow")
+      matches!(result, CurrentForecast { celsius: Some(Temperature::Current(n, _)), description: Some(description), .. } if n == -3.4 && description == "Light Snow")
+    );
+  }
+
+  #[test]
+  fn test_parse_partial_current() {
+    let (_, result) = parse_current_forecast("Current Conditions: Not observed").unwrap();
+    assert_eq!(result, CurrentForecast::default());
+
+    let (_, result) = parse_current_forecast("Current Conditions: -3.4°C").unwrap();
+    assert_eq!(result.description, None);
+    assert_eq!(
+      result.celsius,
+      Some(Temperature::Current(-3.4, PhantomData))
     );
+
+    let (_, result) = parse_current_forecast("Current Conditions: Mostly Cloudy").unwrap();
+    assert_eq!(result.description.as_deref(), Some("Mostly Cloudy"));
+    assert_eq!(result.celsius, None);
+
+    let (_, result) = parse_current_forecast("Current Conditions: Not observed, 2°C").unwrap();
+    assert_eq!(result.description, None);
+    assert_eq!(result.celsius, Some(Temperature::Current(2., PhantomData)));
   }
 }
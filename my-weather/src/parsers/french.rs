@@ -0,0 +1,427 @@
+//! Parser combinator functions for the French (`_f.xml`) feed titles.
+use super::{parse_current_fields, parse_phrase};
+use crate::types::{
+  CurrentForecast, DayNight, DayOfWeek, Forecast, Temperature, TemperatureDetail, TimeQualifier,
+};
+use nom::{
+  branch::alt,
+  bytes::complete::{tag, tag_no_case, take_until},
+  character::complete::{char, digit1, space0, space1},
+  combinator::{map, map_res, opt, recognize, value},
+  error::context,
+  multi::many0,
+  sequence::{delimited, preceded, tuple},
+  IResult,
+};
+use std::marker::PhantomData;
+
+/// Parse an optionally signed number written out in French.
+fn parse_number(input: &str) -> IResult<&str, f32> {
+  let sign = context("sign", alt((tag("moins"), tag("plus"), tag("zéro"))));
+  let num_parse = tuple((opt(sign), opt(preceded(space0, digit1))));
+
+  context(
+    "parse_number",
+    map_res(num_parse, |(sign, n): (Option<&str>, Option<&str>)| {
+      if sign.map(|s| s == "zéro").unwrap_or(false) {
+        Ok(0.)
+      } else {
+        n.unwrap_or_default().parse::<f32>().map(|num| {
+          if sign.map(|s| s == "moins").unwrap_or(false) {
+            -num
+          } else {
+            num
+          }
+        })
+      }
+    }),
+  )(input)
+}
+
+fn parse_temp<Unit>(input: &str) -> IResult<&str, Temperature<Unit>> {
+  let high_parser = context(
+    "high_parser",
+    map(preceded(tag("Maximum "), parse_number), |n| {
+      Temperature::High(n, PhantomData)
+    }),
+  );
+
+  let low_parser = context(
+    "low_parser",
+    map(preceded(tag("Minimum "), parse_number), |n| {
+      Temperature::Low(n, PhantomData)
+    }),
+  );
+
+  let steady_parser = context(
+    "steady_parser",
+    map(
+      preceded(tag("Températures stables près de "), parse_number),
+      |n| Temperature::Steady(n, PhantomData),
+    ),
+  );
+
+  let rising_parser = context(
+    "rising_parser",
+    map(
+      tuple((
+        preceded(
+          tag("Températures à la hausse pour atteindre "),
+          parse_number,
+        ),
+        opt(preceded(space1, parse_trend_time)),
+      )),
+      |(n, qualifier)| Temperature::Rising(n, qualifier, PhantomData),
+    ),
+  );
+
+  let falling_parser = context(
+    "falling_parser",
+    map(
+      tuple((
+        preceded(
+          tag("Températures à la baisse pour atteindre "),
+          parse_number,
+        ),
+        opt(preceded(space1, parse_trend_time)),
+      )),
+      |(n, qualifier)| Temperature::Falling(n, qualifier, PhantomData),
+    ),
+  );
+
+  context(
+    "parse_temp",
+    alt((
+      high_parser,
+      low_parser,
+      steady_parser,
+      rising_parser,
+      falling_parser,
+    )),
+  )(input)
+}
+
+/// Parse a time of day qualifier such as "en matinée" or "d'ici minuit".
+fn parse_time_qualifier(input: &str) -> IResult<&str, TimeQualifier> {
+  let by = alt((
+    value(
+      TimeQualifier::ByMorning,
+      alt((tag("en matinée"), tag("d'ici le matin"))),
+    ),
+    value(TimeQualifier::ByNoon, tag("d'ici midi")),
+    value(TimeQualifier::ByAfternoon, tag("d'ici l'après-midi")),
+    value(TimeQualifier::ByEvening, tag("d'ici le soir")),
+    value(TimeQualifier::ByMidnight, tag("d'ici minuit")),
+  ));
+  let during = alt((
+    value(TimeQualifier::InTheMorning, tag("le matin")),
+    value(TimeQualifier::InTheAfternoon, tag("en après-midi")),
+    value(TimeQualifier::InTheEvening, tag("en soirée")),
+    value(TimeQualifier::ThisMorning, tag("ce matin")),
+    value(TimeQualifier::ThisAfternoon, tag("cet après-midi")),
+    value(TimeQualifier::ThisEvening, tag("ce soir")),
+    value(TimeQualifier::Overnight, tag("au cours de la nuit")),
+    value(TimeQualifier::AfterMidnight, tag("après minuit")),
+    value(TimeQualifier::NearNoon, tag("vers midi")),
+    value(TimeQualifier::NearMidnight, tag("vers minuit")),
+  ));
+  context("time_qualifier", alt((by, during)))(input)
+}
+
+/// Parse the time a temperature trend is reached, keeping phrasings the
+/// grammar doesn't know such as "tard ce soir" as written.
+fn parse_trend_time(input: &str) -> IResult<&str, TimeQualifier> {
+  alt((
+    parse_time_qualifier,
+    map(parse_phrase, |phrase| TimeQualifier::Other(phrase.into())),
+  ))(input)
+}
+
+/// Parse an area qualifier such as "près de la côte" or "à l'intérieur des terres".
+fn parse_area_qualifier(input: &str) -> IResult<&str, &str> {
+  let start = alt((
+    tag("près "),
+    tag("le long "),
+    tag("sur "),
+    tag("dans "),
+    tag("à l'intérieur"),
+    tag("ailleurs"),
+  ));
+  context(
+    "area_qualifier",
+    recognize(tuple((start, opt(parse_phrase)))),
+  )(input)
+}
+
+/// Parse a secondary value or qualifier following the main temperature.
+fn parse_temp_detail(input: &str) -> IResult<&str, TemperatureDetail> {
+  let except = map(
+    tuple((tag("sauf "), parse_number, space1, parse_phrase)),
+    |(_, celsius, _, area)| TemperatureDetail::Except {
+      celsius,
+      area: area.into(),
+    },
+  );
+  let rising = map(
+    tuple((
+      tag("avec températures à la hausse pour atteindre "),
+      parse_number,
+      opt(preceded(space1, parse_trend_time)),
+    )),
+    |(_, celsius, time)| TemperatureDetail::Rising { celsius, time },
+  );
+  let falling = map(
+    tuple((
+      tag("avec températures à la baisse pour atteindre "),
+      parse_number,
+      opt(preceded(space1, parse_trend_time)),
+    )),
+    |(_, celsius, time)| TemperatureDetail::Falling { celsius, time },
+  );
+  let area = map(parse_area_qualifier, |area| {
+    TemperatureDetail::Area(area.into())
+  });
+
+  context(
+    "parse_temp_detail",
+    preceded(space1, alt((except, rising, falling, area))),
+  )(input)
+}
+
+/// Parse all qualifiers following the main temperature.
+fn parse_temp_details(input: &str) -> IResult<&str, Vec<TemperatureDetail>> {
+  many0(parse_temp_detail)(input)
+}
+
+fn parse_description(input: &str) -> IResult<&str, &str> {
+  let parse_tags = context(
+    "parse tags",
+    alt((
+      take_until("Minimum"),
+      take_until("Maximum"),
+      take_until("Températures"),
+    )),
+  );
+  let parser = map(parse_tags, |s: &str| s.trim());
+  context("parse_description", parser)(input)
+}
+
+/// EC uses "soir et nuit" for the night period, but a bare "soir" or "nuit"
+/// shows up in some feeds.
+fn parse_day_night(input: &str) -> IResult<&str, DayNight> {
+  let day = value(DayNight::Day, tag(":"));
+  let night = value(
+    DayNight::Night,
+    recognize(tuple((
+      space1,
+      alt((tag("soir et nuit"), tag("soir"), tag("nuit"))),
+      tag(":"),
+    ))),
+  );
+  let mut parser = alt((day, night));
+  parser(input)
+}
+
+pub(super) fn parse_day_of_week(input: &str) -> IResult<&str, DayOfWeek> {
+  let mut parser = alt((
+    value(DayOfWeek::Monday, tag_no_case("lundi")),
+    value(DayOfWeek::Tuesday, tag_no_case("mardi")),
+    value(DayOfWeek::Wednesday, tag_no_case("mercredi")),
+    value(DayOfWeek::Thursday, tag_no_case("jeudi")),
+    value(DayOfWeek::Friday, tag_no_case("vendredi")),
+    value(DayOfWeek::Saturday, tag_no_case("samedi")),
+    value(DayOfWeek::Sunday, tag_no_case("dimanche")),
+  ));
+  parser(input)
+}
+
+/// Parses a future forecast.
+pub fn parse_forecast(input: &str) -> IResult<&str, Forecast> {
+  let (input, day_of_week) = parse_day_of_week(input)?;
+  let (input, day_night) = parse_day_night(input)?;
+  let parser = tuple((
+    map(parse_description, String::from),
+    parse_temp,
+    parse_temp_details,
+  ));
+  let mut parser = map(parser, |(description, temp, details)| Forecast {
+    day: day_night,
+    day_of_week,
+    celsius: temp.clone(),
+    fahrenheit: temp.into(),
+    description,
+    details,
+    timeline: vec![],
+    pop: None,
+    normal: None,
+    almanac: None,
+  });
+  parser(input)
+}
+
+/// French observations use a decimal comma, "-3,4".
+fn parse_signed_number(input: &str) -> IResult<&str, f32> {
+  let fraction_parse = recognize(tuple((digit1, char(','), digit1)));
+  let num_parse = delimited(
+    space0,
+    tuple((opt(char('-')), alt((fraction_parse, digit1)))),
+    space0,
+  );
+  let mut parser = map_res(num_parse, |(neg, n): (Option<_>, &str)| {
+    n.replace(',', ".")
+      .parse::<f32>()
+      .map(|num| if neg.is_some() { -num } else { num })
+  });
+  parser(input)
+}
+
+/// Parses the current forecast.
+pub fn parse_current_forecast(input: &str) -> IResult<&str, CurrentForecast> {
+  preceded(tag("Conditions actuelles: "), |input| {
+    parse_current_fields(input, parse_signed_number)
+  })(input)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_parse_number() {
+    let (_, n) = parse_number("moins 5.").unwrap();
+    assert_eq!(n, -5.);
+
+    let (_, n) = parse_number("zéro.").unwrap();
+    assert_eq!(n, 0.);
+
+    let (_, n) = parse_number("plus 3.").unwrap();
+    assert_eq!(n, 3.);
+  }
+
+  #[test]
+  fn test_parse_day_night() {
+    let (_, day) = parse_day_night(" soir et nuit: Neige.").unwrap();
+    assert_eq!(day, DayNight::Night);
+
+    let (_, day) = parse_day_night(" soir: Neige.").unwrap();
+    assert_eq!(day, DayNight::Night);
+
+    let (_, day) = parse_day_night(": Neige.").unwrap();
+    assert_eq!(day, DayNight::Day);
+  }
+
+  #[test]
+  fn parse_full() {
+    let test = "Lundi: Ensoleillé. Maximum moins 5.";
+    let (_, forecast) = parse_forecast(test).unwrap();
+
+    assert!(matches!(
+      forecast,
+      Forecast {
+        celsius: Temperature::High(n, _),
+        description,
+        day: DayNight::Day,
+        day_of_week: DayOfWeek::Monday,
+        ..
+      } if n == -5. && description == "Ensoleillé."
+    ));
+
+    let test = "lundi soir: Nuageux. Minimum zéro.";
+    let (_, forecast) = parse_forecast(test).unwrap();
+
+    assert!(matches!(
+      forecast,
+      Forecast {
+        celsius: Temperature::Low(n, _),
+        description,
+        day: DayNight::Night,
+        day_of_week: DayOfWeek::Monday,
+        ..
+      } if n == 0. && description == "Nuageux."
+    ));
+
+    let test =
+      "Samedi soir et nuit: Neige. Températures à la hausse pour atteindre zéro en matinée.";
+    let (_, forecast) = parse_forecast(test).unwrap();
+
+    assert!(matches!(
+      forecast,
+      Forecast {
+        celsius: Temperature::Rising(n, Some(TimeQualifier::ByMorning), _),
+        description,
+        day: DayNight::Night,
+        day_of_week: DayOfWeek::Saturday,
+        ..
+      } if n == 0. && description == "Neige."
+    ));
+
+    let test =
+      "Mardi: Pluie ou neige. Températures à la baisse pour atteindre moins 3 en après-midi. PdP 70%";
+    let (_, forecast) = parse_forecast(test).unwrap();
+
+    assert!(matches!(
+      forecast,
+      Forecast {
+        celsius: Temperature::Falling(n, Some(TimeQualifier::InTheAfternoon), _),
+        description,
+        day: DayNight::Day,
+        day_of_week: DayOfWeek::Tuesday,
+        ..
+      } if n == -3. && description == "Pluie ou neige."
+    ));
+
+    let test =
+      "Lundi soir et nuit: Neige. Températures à la hausse pour atteindre zéro tard ce soir.";
+    let (rest, forecast) = parse_forecast(test).unwrap();
+    assert_eq!(rest, ".");
+    assert_eq!(
+      forecast.celsius.qualifier(),
+      Some(TimeQualifier::Other("tard ce soir".into()))
+    );
+  }
+
+  #[test]
+  fn test_parse_details() {
+    let test = "Mardi: Ensoleillé. Maximum 15 sauf 20 à l'intérieur des terres.";
+    let (_, forecast) = parse_forecast(test).unwrap();
+    assert_eq!(
+      forecast.details,
+      vec![TemperatureDetail::Except {
+        celsius: 20.,
+        area: "à l'intérieur des terres".into()
+      }]
+    );
+
+    let test = "Mardi soir et nuit: Dégagé. Minimum moins 5 avec températures à la hausse pour atteindre plus 2 en matinée.";
+    let (rest, forecast) = parse_forecast(test).unwrap();
+    assert_eq!(rest, ".");
+    assert_eq!(
+      forecast.details,
+      vec![TemperatureDetail::Rising {
+        celsius: 2.,
+        time: Some(TimeQualifier::ByMorning)
+      }]
+    );
+
+    let test = "Jeudi: Nuageux. Maximum plus 3 près de la côte.";
+    let (_, forecast) = parse_forecast(test).unwrap();
+    assert_eq!(
+      forecast.details,
+      vec![TemperatureDetail::Area("près de la côte".into())]
+    );
+  }
+
+  #[test]
+  fn test_parse_current() {
+    let test = "Conditions actuelles: Neige légère, -3,4°C";
+
+    let (_, result) = parse_current_forecast(test).unwrap();
+
+    assert!(
+      matches!(result, CurrentForecast { celsius: Some(Temperature::Current(n, _)), description: Some(description), .. } if n == -3.4 && description == "Neige légère")
+    );
+
+    let (_, result) = parse_current_forecast("Conditions actuelles: Non observé").unwrap();
+    assert_eq!(result, CurrentForecast::default());
+  }
+}
@@ -0,0 +1,243 @@
+//! Parsers for the wind and wave sentences of marine forecasts, in either
+//! language, e.g. "Wind southwest 15 to 25 knots increasing to 20 to 30 this
+//! afternoon." or "Vagues de 1 à 2 mètres.".
+use crate::marine::{MarineWind, WaveHeight};
+use nom::{
+  branch::alt,
+  bytes::complete::tag,
+  character::complete::{digit1, one_of, space0, space1},
+  combinator::{map, map_res, opt, recognize},
+  sequence::{pair, preceded, terminated, tuple},
+  IResult,
+};
+
+/// Direction words, longest first so "northwest" wins over "north".
+const DIRECTIONS: [(&str, &str); 16] = [
+  ("northwest", "NW"),
+  ("northeast", "NE"),
+  ("southwest", "SW"),
+  ("southeast", "SE"),
+  ("north", "N"),
+  ("south", "S"),
+  ("east", "E"),
+  ("west", "W"),
+  ("nord-ouest", "NW"),
+  ("nord-est", "NE"),
+  ("sud-ouest", "SW"),
+  ("sud-est", "SE"),
+  ("nord", "N"),
+  ("sud", "S"),
+  ("l'est", "E"),
+  ("l'ouest", "W"),
+];
+
+/// Every suffix of a sentence starting at a word.
+fn words(sentence: &str) -> impl Iterator<Item = (usize, &str)> {
+  sentence
+    .char_indices()
+    .filter(|(index, c)| !c.is_whitespace() && (*index == 0 || sentence[..*index].ends_with(' ')))
+    .map(|(index, _)| (index, &sentence[index..]))
+}
+
+/// Compass direction of a direction word starting `word`.
+fn direction(word: &str) -> Option<&'static str> {
+  DIRECTIONS.iter().find_map(|(phrase, direction)| {
+    let rest = word.strip_prefix(phrase)?;
+    match rest.chars().next() {
+      Some(c) if c.is_alphabetic() || c == '-' => None,
+      _ => Some(*direction),
+    }
+  })
+}
+
+/// A plain number such as "15", "4.5" or the French "4,5", unlike `float`
+/// which also takes words like "inf".
+fn number(input: &str) -> IResult<&str, f32> {
+  map_res(
+    recognize(pair(digit1, opt(pair(one_of(".,"), digit1)))),
+    |number: &str| number.replace(',', ".").parse::<f32>(),
+  )(input)
+}
+
+/// "15 to 25" or "15", also "de 15 à 25".
+fn range(input: &str) -> IResult<&str, (f32, f32)> {
+  map(
+    tuple((
+      number,
+      opt(preceded(
+        tuple((space1, alt((tag("to"), tag("à"))), space1)),
+        number,
+      )),
+    )),
+    |(low, high)| (low, high.unwrap_or(low)),
+  )(input)
+}
+
+/// "gusting to 30" or "rafales à 30".
+fn gust(input: &str) -> IResult<&str, f32> {
+  preceded(
+    tuple((alt((tag("gusting to"), tag("rafales à"))), space1)),
+    number,
+  )(input)
+}
+
+/// "metre or less" or "mètre ou moins", after a height.
+fn or_less(input: &str) -> IResult<&str, &str> {
+  preceded(
+    tuple((
+      space0,
+      alt((tag("metres"), tag("metre"), tag("mètres"), tag("mètre"))),
+      space1,
+    )),
+    alt((tag("or less"), tag("ou moins"))),
+  )(input)
+}
+
+fn starts_with_any<'a>(sentence: &'a str, keywords: &[&str]) -> Option<&'a str> {
+  keywords.iter().find_map(|keyword| {
+    sentence
+      .strip_prefix(keyword)
+      .filter(|rest| rest.starts_with(' '))
+  })
+}
+
+/// Winds of a sentence starting with "Wind" or "Vents", in knots. Later
+/// speeds keep the last direction given, and a gust applies to the speed
+/// before it. "Wind light" has no speed.
+pub fn parse_marine_wind(sentence: &str) -> Vec<MarineWind> {
+  let sentence = sentence.trim().to_lowercase();
+  let mut winds: Vec<MarineWind> = vec![];
+  if starts_with_any(&sentence, &["wind", "winds", "vent", "vents"]).is_none() {
+    return winds;
+  }
+
+  let mut direction_now = None;
+  let mut consumed = 0;
+  for (index, word) in words(&sentence) {
+    if index < consumed {
+      continue;
+    }
+    if let Some(direction) = direction(word) {
+      direction_now = Some(direction.to_string());
+    } else if let Ok((rest, gust)) = gust(word) {
+      if let Some(wind) = winds.last_mut() {
+        wind.gust = Some(gust as u32);
+      }
+      consumed = sentence.len() - rest.len();
+    } else if let Ok((rest, (low, high))) = terminated(range, opt(tag(" knots")))(word) {
+      winds.push(MarineWind {
+        direction: direction_now.clone(),
+        low: low as u32,
+        high: high as u32,
+        gust: None,
+      });
+      consumed = sentence.len() - rest.len();
+    }
+  }
+  winds
+}
+
+/// Wave heights of a sentence starting with "Waves" or "Vagues", in metres.
+/// "1 metre or less" is from 0 to 1.
+pub fn parse_waves(sentence: &str) -> Vec<WaveHeight> {
+  let sentence = sentence.trim().to_lowercase();
+  let mut waves = vec![];
+  if starts_with_any(&sentence, &["waves", "vagues"]).is_none() {
+    return waves;
+  }
+
+  let mut consumed = 0;
+  for (index, word) in words(&sentence) {
+    if index < consumed {
+      continue;
+    }
+    if let Ok((rest, (low, high))) = range(word) {
+      let (rest, low) = match or_less(rest) {
+        Ok((rest, _)) => (rest, 0.),
+        Err(_) => (rest, low),
+      };
+      waves.push(WaveHeight { low, high });
+      consumed = sentence.len() - rest.len();
+    }
+  }
+  waves
+}
+
+/// Visibility of a sentence starting with "Visibility", e.g. "fair in
+/// showers".
+pub fn parse_visibility(sentence: &str) -> Option<String> {
+  let sentence = sentence.trim().trim_end_matches('.');
+  let lower = sentence.to_lowercase();
+  let rest = starts_with_any(&lower, &["visibility", "visibilité"])?;
+  Some(sentence[sentence.len() - rest.len()..].trim().to_string())
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn wind(direction: &str, low: u32, high: u32, gust: Option<u32>) -> MarineWind {
+    MarineWind {
+      direction: Some(direction.into()),
+      low,
+      high,
+      gust,
+    }
+  }
+
+  #[test]
+  fn test_wind() {
+    assert_eq!(
+      parse_marine_wind("Wind southwest 15 to 25 knots increasing to 20 to 30 this afternoon."),
+      vec![wind("SW", 15, 25, None), wind("SW", 20, 30, None)]
+    );
+    assert_eq!(
+      parse_marine_wind("Wind veering to northwest 15 to 20 knots gusting to 30 this evening."),
+      vec![wind("NW", 15, 20, Some(30))]
+    );
+    assert_eq!(
+      parse_marine_wind(
+        "Vents du sud-ouest de 15 à 25 noeuds augmentant à 20 à 30 cet après-midi."
+      ),
+      vec![wind("SW", 15, 25, None), wind("SW", 20, 30, None)]
+    );
+    assert_eq!(
+      parse_marine_wind("Vents de l'est de 10 noeuds avec rafales à 20."),
+      vec![wind("E", 10, 10, Some(20))]
+    );
+    assert!(parse_marine_wind("Wind light.").is_empty());
+    assert!(parse_marine_wind("Showers ending this evening.").is_empty());
+  }
+
+  #[test]
+  fn test_waves() {
+    assert_eq!(
+      parse_waves("Waves 1 to 2 metres building to 2 to 3 this afternoon."),
+      vec![
+        WaveHeight { low: 1., high: 2. },
+        WaveHeight { low: 2., high: 3. }
+      ]
+    );
+    assert_eq!(
+      parse_waves("Waves subsiding to 1 metre or less Wednesday morning."),
+      vec![WaveHeight { low: 0., high: 1. }]
+    );
+    assert_eq!(
+      parse_waves("Vagues de 3 à 4,5 mètres."),
+      vec![WaveHeight { low: 3., high: 4.5 }]
+    );
+  }
+
+  #[test]
+  fn test_visibility() {
+    assert_eq!(
+      parse_visibility("Visibility fair in showers."),
+      Some("fair in showers".into())
+    );
+    assert_eq!(
+      parse_visibility("Visibilité passable dans les averses."),
+      Some("passable dans les averses".into())
+    );
+    assert_eq!(parse_visibility("Fog patches."), None);
+  }
+}
@@ -0,0 +1,474 @@
+//! Parsers for METAR and TAF reports, e.g.
+//! "CYOW 071900Z 03015G25KT 1 1/2SM -SN BKN012 M03/M05 A2992".
+//!
+//! Reports are sequences of space separated groups. Each group is parsed on
+//! its own, and groups that aren't understood, such as runway visual range,
+//! are skipped.
+use crate::{
+  metar::{
+    Change, CloudLayer, Conditions, Convective, Cover, Intensity, Metar, PresentWeather,
+    ReportParseError, ReportTime, Taf, TafGroup, Validity,
+  },
+  provider::compass,
+  types::Wind,
+};
+use nom::{
+  branch::alt,
+  bytes::complete::{tag, take_while_m_n},
+  character::complete::{char, digit1},
+  combinator::{all_consuming, map, map_res, opt, value, verify},
+  multi::many0,
+  sequence::{preceded, separated_pair, terminated, tuple},
+  IResult,
+};
+
+const KNOT: f32 = 1.852;
+const STATUTE_MILE: f32 = 1.609_344;
+const INCH_OF_MERCURY: f32 = 3.386_39;
+
+/// Exactly `n` digits.
+fn digits<'a>(n: usize) -> impl FnMut(&'a str) -> IResult<&'a str, u32> {
+  map_res(
+    take_while_m_n(n, n, |c: char| c.is_ascii_digit()),
+    str::parse,
+  )
+}
+
+fn round(n: f32) -> f32 {
+  (n * 10.).round() / 10.
+}
+
+/// ICAO station identifier, e.g. CYOW.
+fn parse_station(input: &str) -> IResult<&str, &str> {
+  take_while_m_n(4, 4, |c: char| c.is_ascii_uppercase() || c.is_ascii_digit())(input)
+}
+
+/// Day and time, e.g. `071900Z`.
+fn parse_time(input: &str) -> IResult<&str, ReportTime> {
+  map(
+    terminated(tuple((digits(2), digits(2), digits(2))), char('Z')),
+    |(day, hour, minute)| ReportTime {
+      day: day as u8,
+      hour: hour as u8,
+      minute: minute as u8,
+    },
+  )(input)
+}
+
+/// Day and hour, e.g. `0718`.
+fn parse_day_hour(input: &str) -> IResult<&str, ReportTime> {
+  map(tuple((digits(2), digits(2))), |(day, hour)| ReportTime {
+    day: day as u8,
+    hour: hour as u8,
+    minute: 0,
+  })(input)
+}
+
+/// Forecast period, e.g. `0718/0818`.
+fn parse_validity(input: &str) -> IResult<&str, Validity> {
+  map(
+    separated_pair(parse_day_hour, char('/'), parse_day_hour),
+    |(from, to)| Validity { from, to },
+  )(input)
+}
+
+/// Wind, e.g. `03015G25KT`, `VRB03KT` or `24008MPS`.
+fn parse_wind(input: &str) -> IResult<&str, Wind> {
+  let direction = alt((
+    map(tag("VRB"), |_| None),
+    map(digits(3), |degrees| Some(degrees as f32)),
+  ));
+  let speed = || {
+    map_res(
+      take_while_m_n(2, 3, |c: char| c.is_ascii_digit()),
+      str::parse::<f32>,
+    )
+  };
+  let unit = alt((
+    value(KNOT, tag("KT")),
+    value(3.6, tag("MPS")),
+    value(1., tag("KMH")),
+  ));
+  map(
+    tuple((direction, speed(), opt(preceded(char('G'), speed())), unit)),
+    |(degrees, speed, gust, unit)| Wind {
+      direction: degrees
+        .filter(|_| speed > 0.)
+        .map(|degrees| compass(degrees).into()),
+      speed: (speed * unit).round(),
+      gust: gust.map(|gust| (gust * unit).round()),
+    },
+  )(input)
+}
+
+/// Whole or fractional statute miles, e.g. `6`, `1/2`.
+fn parse_miles(input: &str) -> IResult<&str, f32> {
+  alt((
+    map(
+      separated_pair(digits(1), char('/'), digits(1)),
+      |(numerator, denominator)| numerator as f32 / denominator.max(1) as f32,
+    ),
+    map_res(digit1, str::parse::<f32>),
+  ))(input)
+}
+
+/// Visibility in km, from `P6SM`, `1/2SM`, `M1/4SM`, `9999` or `CAVOK`.
+fn parse_visibility(input: &str) -> IResult<&str, f32> {
+  alt((
+    map(
+      preceded(
+        opt(alt((char('P'), char('M')))),
+        terminated(parse_miles, tag("SM")),
+      ),
+      |miles| round(miles * STATUTE_MILE),
+    ),
+    map(tag("9999"), |_| 10.),
+    map(digits(4), |metres| metres as f32 / 1000.),
+  ))(input)
+}
+
+const DESCRIPTORS: [&str; 8] = ["MI", "PR", "BC", "DR", "BL", "SH", "TS", "FZ"];
+const PHENOMENA: [&str; 22] = [
+  "DZ", "RA", "SN", "SG", "IC", "PL", "GR", "GS", "UP", "BR", "FG", "FU", "VA", "DU", "SA", "HZ",
+  "PY", "PO", "SQ", "FC", "SS", "DS",
+];
+
+fn parse_code<'a>(
+  codes: &'static [&'static str],
+) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+  verify(
+    take_while_m_n(2, 2, |c: char| c.is_ascii_uppercase()),
+    move |code: &str| codes.contains(&code),
+  )
+}
+
+/// Present weather, e.g. `-SN`, `+TSRA` or `VCSH`.
+fn parse_weather(input: &str) -> IResult<&str, PresentWeather> {
+  let intensity = alt((
+    value(Intensity::Light, char('-')),
+    value(Intensity::Heavy, char('+')),
+    value(Intensity::Vicinity, tag("VC")),
+  ));
+  map(
+    verify(
+      tuple((
+        opt(intensity),
+        opt(parse_code(&DESCRIPTORS)),
+        many0(parse_code(&PHENOMENA)),
+      )),
+      |(_, descriptor, phenomena): &(_, Option<&str>, Vec<&str>)| {
+        !phenomena.is_empty() || descriptor.is_some()
+      },
+    ),
+    |(intensity, descriptor, phenomena)| PresentWeather {
+      intensity: intensity.unwrap_or(Intensity::Moderate),
+      descriptor: descriptor.map(Into::into),
+      phenomena: phenomena.into_iter().map(Into::into).collect(),
+    },
+  )(input)
+}
+
+/// Cloud layer, e.g. `BKN012` or `FEW020CB`.
+fn parse_cloud(input: &str) -> IResult<&str, CloudLayer> {
+  let cover = alt((
+    value(Cover::Few, tag("FEW")),
+    value(Cover::Scattered, tag("SCT")),
+    value(Cover::Broken, tag("BKN")),
+    value(Cover::Overcast, tag("OVC")),
+    value(Cover::VerticalVisibility, tag("VV")),
+  ));
+  let height = alt((
+    map(digits(3), |hundreds| Some(hundreds * 100)),
+    map(tag("///"), |_| None),
+  ));
+  let convective = alt((
+    value(Convective::Cumulonimbus, tag("CB")),
+    value(Convective::ToweringCumulus, tag("TCU")),
+  ));
+  map(
+    tuple((cover, height, opt(convective))),
+    |(cover, height, convective)| CloudLayer {
+      cover,
+      height,
+      convective,
+    },
+  )(input)
+}
+
+/// Whole degrees, `M` for minus, e.g. `M03`.
+fn parse_degrees(input: &str) -> IResult<&str, f32> {
+  map(tuple((opt(char('M')), digits(2))), |(minus, n)| {
+    if minus.is_some() {
+      -(n as f32)
+    } else {
+      n as f32
+    }
+  })(input)
+}
+
+/// Temperature and dewpoint, e.g. `M03/M05`. The dewpoint may be missing.
+fn parse_temperatures(input: &str) -> IResult<&str, (f32, Option<f32>)> {
+  separated_pair(parse_degrees, char('/'), opt(parse_degrees))(input)
+}
+
+/// Altimeter setting in kPa, from `A2992` (inches of mercury) or `Q1013` (hPa).
+fn parse_altimeter(input: &str) -> IResult<&str, f32> {
+  alt((
+    map(preceded(char('A'), digits(4)), |n| {
+      round(n as f32 / 100. * INCH_OF_MERCURY)
+    }),
+    map(preceded(char('Q'), digits(4)), |n| n as f32 / 10.),
+  ))(input)
+}
+
+/// Temperature and dewpoint in tenths from a `T10331052` remark.
+fn parse_precise_temperatures(input: &str) -> IResult<&str, (f32, f32)> {
+  let tenths = || {
+    map(
+      tuple((alt((char('0'), char('1'))), digits(3))),
+      |(sign, n)| {
+        let n = n as f32 / 10.;
+        if sign == '1' {
+          -n
+        } else {
+          n
+        }
+      },
+    )
+  };
+  preceded(char('T'), tuple((tenths(), tenths())))(input)
+}
+
+fn full<'a, T>(parser: impl FnMut(&'a str) -> IResult<&'a str, T>, token: &'a str) -> Option<T> {
+  all_consuming(parser)(token).ok().map(|(_, value)| value)
+}
+
+/// Add a wind, visibility, weather or cloud group to `conditions`. Returns
+/// the number of tokens used, as visibility can be written `1 1/2SM`.
+fn parse_condition(conditions: &mut Conditions, token: &str, next: Option<&str>) -> usize {
+  if let Some(wind) = full(parse_wind, token) {
+    conditions.wind = Some(wind);
+  } else if token == "CAVOK" {
+    conditions.visibility = Some(10.);
+    conditions.clouds.clear();
+  } else if let (Some(whole), Some(fraction)) = (
+    full(digits(1), token),
+    next.and_then(|next| full(parse_visibility, next)),
+  ) {
+    conditions.visibility = Some(round(whole as f32 * STATUTE_MILE + fraction));
+    return 2;
+  } else if let Some(visibility) = full(parse_visibility, token) {
+    conditions.visibility = Some(visibility);
+  } else if let Some(weather) = full(parse_weather, token) {
+    conditions.weather.push(weather);
+  } else if let Some(cloud) = full(parse_cloud, token) {
+    conditions.clouds.push(cloud);
+  } else if matches!(token, "SKC" | "CLR" | "NSC" | "NCD") {
+    conditions.clouds.clear();
+  } else if token == "NSW" {
+    conditions.weather.clear();
+  } else {
+    return 0;
+  }
+  1
+}
+
+fn error(report: &str, problem: &str) -> ReportParseError {
+  ReportParseError(format!("{problem} in \"{report}\""))
+}
+
+/// Tokens of a report, without the `=` ending it.
+fn tokens(input: &str) -> Vec<&str> {
+  input
+    .trim()
+    .trim_end_matches('=')
+    .split_whitespace()
+    .collect()
+}
+
+/// Parse a METAR or SPECI report.
+pub fn parse_metar(input: &str) -> Result<Metar, ReportParseError> {
+  let tokens = tokens(input);
+  let mut tokens = tokens
+    .iter()
+    .copied()
+    .skip_while(|token| matches!(*token, "METAR" | "SPECI"))
+    .peekable();
+
+  let station = tokens
+    .next()
+    .and_then(|token| full(parse_station, token))
+    .ok_or_else(|| error(input, "missing station"))?;
+  let time = tokens
+    .next()
+    .and_then(|token| full(parse_time, token))
+    .ok_or_else(|| error(input, "missing time"))?;
+
+  let mut metar = Metar {
+    station: station.into(),
+    time,
+    conditions: Conditions::default(),
+    temperature: None,
+    dewpoint: None,
+    pressure: None,
+    remarks: None,
+  };
+  while let Some(token) = tokens.next() {
+    match token {
+      "RMK" => {
+        let remarks = tokens.by_ref().collect::<Vec<_>>();
+        if let Some((temperature, dewpoint)) = remarks
+          .iter()
+          .find_map(|token| full(parse_precise_temperatures, token))
+        {
+          metar.temperature = Some(temperature);
+          metar.dewpoint = Some(dewpoint);
+        }
+        metar.remarks = Some(remarks.join(" "));
+      }
+      // A trend forecast follows.
+      "BECMG" | "TEMPO" | "NOSIG" => break,
+      _ => {
+        if let Some((temperature, dewpoint)) = full(parse_temperatures, token) {
+          metar.temperature = Some(temperature);
+          metar.dewpoint = dewpoint;
+        } else if let Some(pressure) = full(parse_altimeter, token) {
+          metar.pressure = Some(pressure);
+        } else if parse_condition(&mut metar.conditions, token, tokens.peek().copied()) == 2 {
+          tokens.next();
+        }
+      }
+    }
+  }
+  Ok(metar)
+}
+
+/// Start of a TAF change group, with the number of tokens it takes.
+fn parse_change(token: &str, next: Option<&str>) -> Option<(Change, usize)> {
+  if let Some(time) = full(
+    preceded(tag("FM"), tuple((digits(2), digits(2), digits(2)))),
+    token,
+  ) {
+    let (day, hour, minute) = time;
+    return Some((
+      Change::From(ReportTime {
+        day: day as u8,
+        hour: hour as u8,
+        minute: minute as u8,
+      }),
+      1,
+    ));
+  }
+  match token {
+    "BECMG" => Some((Change::Becoming, 1)),
+    "TEMPO" => Some((Change::Temporary, 1)),
+    _ => {
+      let percent = full(preceded(tag("PROB"), digits(2)), token)?;
+      let tokens = if next == Some("TEMPO") { 2 } else { 1 };
+      Some((Change::Probability(percent as u8), tokens))
+    }
+  }
+}
+
+/// Parse a TAF, with its change groups.
+pub fn parse_taf(input: &str) -> Result<Taf, ReportParseError> {
+  let tokens = tokens(input);
+  let mut tokens = tokens
+    .iter()
+    .copied()
+    .skip_while(|token| matches!(*token, "TAF" | "AMD" | "COR"))
+    .peekable();
+
+  let station = tokens
+    .next()
+    .and_then(|token| full(parse_station, token))
+    .ok_or_else(|| error(input, "missing station"))?;
+  let issued = tokens
+    .next()
+    .and_then(|token| full(parse_time, token))
+    .ok_or_else(|| error(input, "missing issue time"))?;
+  let valid = tokens
+    .next()
+    .and_then(|token| full(parse_validity, token))
+    .ok_or_else(|| error(input, "missing validity"))?;
+
+  let mut taf = Taf {
+    station: station.into(),
+    issued,
+    valid,
+    groups: vec![TafGroup {
+      change: Change::Initial,
+      period: None,
+      conditions: Conditions::default(),
+    }],
+    remarks: None,
+  };
+  while let Some(token) = tokens.next() {
+    if token == "RMK" {
+      taf.remarks = Some(tokens.by_ref().collect::<Vec<_>>().join(" "));
+      break;
+    }
+    if let Some((change, used)) = parse_change(token, tokens.peek().copied()) {
+      for _ in 1..used {
+        tokens.next();
+      }
+      let period = match change {
+        Change::From(_) => None,
+        _ => tokens.next_if(|token| full(parse_validity, token).is_some()),
+      };
+      taf.groups.push(TafGroup {
+        change,
+        period: period.and_then(|period| full(parse_validity, period)),
+        conditions: Conditions::default(),
+      });
+      continue;
+    }
+    let group = taf.groups.last_mut().expect("initial group");
+    if parse_condition(&mut group.conditions, token, tokens.peek().copied()) == 2 {
+      tokens.next();
+    }
+  }
+  Ok(taf)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_groups() {
+    assert_eq!(
+      full(parse_wind, "00000KT"),
+      Some(Wind {
+        direction: None,
+        speed: 0.,
+        gust: None,
+      })
+    );
+    assert_eq!(full(parse_visibility, "1/2SM"), Some(0.8));
+    assert_eq!(full(parse_visibility, "0800"), Some(0.8));
+    assert_eq!(
+      full(parse_weather, "FZDZ"),
+      Some(PresentWeather {
+        intensity: Intensity::Moderate,
+        descriptor: Some("FZ".into()),
+        phenomena: vec!["DZ".into()],
+      })
+    );
+    assert_eq!(full(parse_weather, "RMK"), None);
+    assert_eq!(full(parse_weather, "-"), None);
+    assert_eq!(full(parse_temperatures, "M03/"), Some((-3., None)));
+    assert_eq!(
+      full(parse_precise_temperatures, "T00251052"),
+      Some((2.5, -5.2))
+    );
+    assert_eq!(
+      full(parse_cloud, "OVC///"),
+      Some(CloudLayer {
+        cover: Cover::Overcast,
+        height: None,
+        convective: None,
+      })
+    );
+  }
+}
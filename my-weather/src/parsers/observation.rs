@@ -0,0 +1,195 @@
+//! Parser for the observation block in the summary of a current conditions
+//! entry, in either language.
+use crate::types::{Observation, PressureTendency, Temperature, Wind};
+use nom::{
+  branch::alt,
+  bytes::complete::tag_no_case,
+  character::complete::{alpha1, space0, space1},
+  combinator::{map, opt},
+  number::complete::float,
+  sequence::{preceded, tuple},
+  IResult,
+};
+use std::marker::PhantomData;
+
+/// Leading number of a value, accepting a decimal comma.
+fn parse_value(value: &str) -> Option<f32> {
+  let value = value.replace(',', ".");
+  let number = |input| -> IResult<&str, f32> { preceded(space0, float)(input) };
+  number(value.as_str()).ok().map(|(_, n)| n)
+}
+
+fn parse_tendency(value: &str) -> Option<PressureTendency> {
+  let value = value.to_lowercase();
+  if value.contains("rising") || value.contains("hausse") {
+    Some(PressureTendency::Rising)
+  } else if value.contains("falling") || value.contains("baisse") {
+    Some(PressureTendency::Falling)
+  } else if value.contains("steady") || value.contains("stable") {
+    Some(PressureTendency::Steady)
+  } else {
+    None
+  }
+}
+
+fn parse_gust(input: &str) -> IResult<&str, f32> {
+  preceded(
+    tuple((
+      space1,
+      alt((tag_no_case("gust"), tag_no_case("rafales"))),
+      space1,
+    )),
+    float,
+  )(input)
+}
+
+/// Parse a wind such as "NW 30 gust 45 km/h", "calm" or "calme".
+fn parse_wind(input: &str) -> IResult<&str, Wind> {
+  alt((
+    map(alt((tag_no_case("calme"), tag_no_case("calm"))), |_| Wind {
+      direction: None,
+      speed: 0.,
+      gust: None,
+    }),
+    map(
+      tuple((alpha1, space1, float, opt(parse_gust))),
+      |(direction, _, speed, gust)| Wind {
+        direction: Some(direction.into()),
+        speed,
+        gust,
+      },
+    ),
+  ))(input)
+}
+
+/// Parse the labelled values of an observation summary. Values that were not
+/// observed are left as `None`, and `None` is returned when nothing was found.
+pub fn parse_observation(summary: &str) -> Option<Observation> {
+  let mut observation = Observation::default();
+  let temperature = |value: &str| parse_value(value).map(|n| Temperature::Current(n, PhantomData));
+
+  for line in summary.split("<br/>") {
+    let Some((label, value)) = line.trim().trim_start_matches("<b>").split_once(":</b>") else {
+      continue;
+    };
+    let value = value
+      .replace("&deg;", "")
+      .replace("&nbsp;", " ")
+      .trim()
+      .to_string();
+    if value.is_empty() {
+      continue;
+    }
+
+    match label.trim() {
+      "Observed at" | "Enregistrées à" => observation.observed_at = Some(value),
+      "Condition" => {
+        let lower = value.to_lowercase();
+        if !(lower.starts_with("not ") || lower.starts_with("non ")) {
+          observation.condition = Some(value);
+        }
+      }
+      "Temperature" | "Température" => observation.temperature = temperature(&value),
+      "Pressure / Tendency" | "Pression / Tendance" | "Pressure" | "Pression" => {
+        observation.pressure = parse_value(&value);
+        observation.tendency = parse_tendency(&value);
+      }
+      "Visibility" | "Visibilité" => observation.visibility = parse_value(&value),
+      "Humidity" | "Humidité" => observation.humidity = parse_value(&value),
+      "Wind Chill" | "Refroidissement éolien" => observation.wind_chill = parse_value(&value),
+      "Humidex" => observation.humidex = parse_value(&value),
+      "Heat Index" | "Indice de chaleur" => observation.heat_index = parse_value(&value),
+      "Dewpoint" | "Point de rosée" => observation.dewpoint = temperature(&value),
+      "Wind" | "Vent" => {
+        observation.wind = parse_wind(&value.replace(',', ".")).ok().map(|(_, w)| w)
+      }
+      "Air Quality Health Index" | "Cote air santé" => observation.aqhi = parse_value(&value),
+      _ => (),
+    }
+  }
+
+  (observation != Observation::default()).then_some(observation)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  const ENGLISH: &str = "<b>Observed at:</b> Ottawa Macdonald-Cartier Int'l Airport 3:00 PM EST Saturday 07 January 2023 <br/>
+<b>Condition:</b> Light Snow <br/>
+<b>Temperature:</b> -3.4&deg;C <br/>
+<b>Pressure / Tendency:</b> 101.6 kPa falling<br/>
+<b>Visibility:</b> 4.8&nbsp;km<br/>
+<b>Humidity:</b> 88 %<br/>
+<b>Wind Chill:</b> -9 <br/>
+<b>Dewpoint:</b> -5.2&deg;C <br/>
+<b>Wind:</b> ENE 17 km/h<br/>
+<b>Air Quality Health Index:</b> 3 <br/>";
+
+  #[test]
+  fn test_parse_observation() {
+    let observation = parse_observation(ENGLISH).unwrap();
+    assert_eq!(
+      observation,
+      Observation {
+        observed_at: Some(
+          "Ottawa Macdonald-Cartier Int'l Airport 3:00 PM EST Saturday 07 January 2023".into()
+        ),
+        condition: Some("Light Snow".into()),
+        temperature: Some(Temperature::Current(-3.4, PhantomData)),
+        pressure: Some(101.6),
+        tendency: Some(PressureTendency::Falling),
+        visibility: Some(4.8),
+        humidity: Some(88.),
+        wind_chill: Some(-9.),
+        humidex: None,
+        dewpoint: Some(Temperature::Current(-5.2, PhantomData)),
+        wind: Some(Wind {
+          direction: Some("ENE".into()),
+          speed: 17.,
+          gust: None,
+        }),
+        aqhi: Some(3.),
+        ceiling: None,
+        heat_index: None,
+        frost_point: None,
+      }
+    );
+  }
+
+  #[test]
+  fn test_parse_french_observation() {
+    let summary = "<b>Température:</b> -3,4&deg;C <br/>
+<b>Pression / Tendance:</b> 101,6 kPa à la baisse<br/>
+<b>Vent:</b> NO 30 rafales 45 km/h<br/>";
+    let observation = parse_observation(summary).unwrap();
+    assert_eq!(
+      observation.temperature,
+      Some(Temperature::Current(-3.4, PhantomData))
+    );
+    assert_eq!(observation.pressure, Some(101.6));
+    assert_eq!(observation.tendency, Some(PressureTendency::Falling));
+    assert_eq!(
+      observation.wind,
+      Some(Wind {
+        direction: Some("NO".into()),
+        speed: 30.,
+        gust: Some(45.),
+      })
+    );
+  }
+
+  #[test]
+  fn test_partial_observation() {
+    let summary = "<b>Observed at:</b> Gatineau Airport 3:00 PM EST Saturday 07 January 2023 <br/>
+<b>Condition:</b> Not observed <br/>
+<b>Temperature:</b> Not observed <br/>
+<b>Wind:</b> calm<br/>";
+    let observation = parse_observation(summary).unwrap();
+    assert_eq!(observation.condition, None);
+    assert_eq!(observation.temperature, None);
+    assert_eq!(observation.wind.map(|wind| wind.speed), Some(0.));
+
+    assert_eq!(parse_observation("No observation available."), None);
+  }
+}
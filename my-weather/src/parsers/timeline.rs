@@ -0,0 +1,202 @@
+//! Parser for the sequence of conditions in an English forecast description,
+//! e.g. "Rain changing to snow in the afternoon".
+use super::parse_time_qualifier;
+use crate::types::{TimeQualifier, TimelineSegment};
+use nom::{
+  branch::alt,
+  bytes::complete::tag,
+  character::complete::space0,
+  combinator::{map, value},
+  error::{Error, ErrorKind},
+  multi::many0,
+  sequence::preceded,
+  IResult,
+};
+
+/// Words joining two conditions or a condition and its timing.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Connector {
+  ChangingTo,
+  Becoming,
+  Ending,
+  Beginning,
+  Then,
+}
+
+#[derive(Debug, Clone)]
+enum Token<'a> {
+  Condition(&'a str),
+  Time(TimeQualifier),
+  Connector(Connector),
+}
+
+fn parse_connector(input: &str) -> IResult<&str, Connector> {
+  alt((
+    value(Connector::ChangingTo, tag("changing to")),
+    value(Connector::Becoming, tag("becoming")),
+    value(Connector::Ending, tag("ending")),
+    value(Connector::Beginning, tag("beginning")),
+    value(Connector::Then, tag("then")),
+  ))(input)
+}
+
+/// A condition runs until the next connector or time qualifier.
+fn parse_condition(input: &str) -> IResult<&str, &str> {
+  let end = input
+    .match_indices(' ')
+    .map(|(i, _)| i)
+    .find(|&i| {
+      let rest = &input[i + 1..];
+      parse_connector(rest).is_ok() || parse_time_qualifier(rest).is_ok()
+    })
+    .unwrap_or(input.len());
+  let (condition, rest) = input.split_at(end);
+  if condition.trim().is_empty() {
+    Err(nom::Err::Error(Error::new(input, ErrorKind::TakeTill1)))
+  } else {
+    Ok((rest, condition.trim()))
+  }
+}
+
+fn parse_token(input: &str) -> IResult<&str, Token<'_>> {
+  preceded(
+    space0,
+    alt((
+      map(parse_connector, Token::Connector),
+      map(parse_time_qualifier, Token::Time),
+      map(parse_condition, Token::Condition),
+    )),
+  )(input)
+}
+
+/// Parse the first sentence of a forecast description into an ordered list
+/// of conditions with the time each one starts or ends.
+pub fn parse_timeline(input: &str) -> IResult<&str, Vec<TimelineSegment>> {
+  let sentence_end = input.find('.').unwrap_or(input.len());
+  let (sentence, rest) = input.split_at(sentence_end);
+  let (_, tokens) = many0(parse_token)(sentence)?;
+
+  let mut segments: Vec<TimelineSegment> = vec![];
+  // Time a following "then" condition starts at.
+  let mut carry = None;
+  let mut connector = None;
+
+  for (index, token) in tokens.iter().enumerate() {
+    match token {
+      Token::Condition(condition) => {
+        segments.push(TimelineSegment {
+          condition: (*condition).into(),
+          start: carry.take(),
+          end: None,
+        });
+      }
+      Token::Connector(c) => connector = Some(*c),
+      Token::Time(time) => {
+        let followed_by_then = matches!(
+          tokens.get(index + 1),
+          Some(Token::Connector(Connector::Then))
+        );
+        match (connector.take(), segments.as_mut_slice()) {
+          (Some(Connector::ChangingTo | Connector::Becoming), [.., previous, current]) => {
+            previous.end = Some(time.clone());
+            current.start = Some(time.clone());
+          }
+          (Some(Connector::Ending), [.., last]) => {
+            last.end = Some(time.clone());
+            carry = Some(time.clone());
+          }
+          (Some(Connector::Beginning), [.., last]) => last.start = Some(time.clone()),
+          (_, [.., last]) if followed_by_then => {
+            last.end = Some(time.clone());
+            carry = Some(time.clone());
+          }
+          (_, [.., last]) => last.start = Some(time.clone()),
+          _ => (),
+        }
+      }
+    }
+  }
+
+  Ok((rest, segments))
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn segment(
+    condition: &str,
+    start: Option<TimeQualifier>,
+    end: Option<TimeQualifier>,
+  ) -> TimelineSegment {
+    TimelineSegment {
+      condition: condition.into(),
+      start,
+      end,
+    }
+  }
+
+  #[test]
+  fn test_changing_to() {
+    let (_, timeline) = parse_timeline("Rain changing to snow in the afternoon.").unwrap();
+    assert_eq!(
+      timeline,
+      vec![
+        segment("Rain", None, Some(TimeQualifier::InTheAfternoon)),
+        segment("snow", Some(TimeQualifier::InTheAfternoon), None),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_ending_then() {
+    let (rest, timeline) =
+      parse_timeline("Periods of snow ending overnight then cloudy. Amount 2 cm.").unwrap();
+    assert_eq!(rest, ". Amount 2 cm.");
+    assert_eq!(
+      timeline,
+      vec![
+        segment("Periods of snow", None, Some(TimeQualifier::Overnight)),
+        segment("cloudy", Some(TimeQualifier::Overnight), None),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_single_condition() {
+    let (_, timeline) = parse_timeline("A mix of sun and cloud.").unwrap();
+    assert_eq!(
+      timeline,
+      vec![segment("A mix of sun and cloud", None, None)]
+    );
+
+    let (_, timeline) = parse_timeline("Snow beginning this evening.").unwrap();
+    assert_eq!(
+      timeline,
+      vec![segment("Snow", Some(TimeQualifier::ThisEvening), None)]
+    );
+  }
+
+  #[test]
+  fn test_sequence() {
+    let (_, timeline) =
+      parse_timeline("Cloudy becoming sunny near noon then clearing after midnight.").unwrap();
+    assert_eq!(
+      timeline,
+      vec![
+        segment("Cloudy", None, Some(TimeQualifier::NearNoon)),
+        segment("sunny", Some(TimeQualifier::NearNoon), None),
+        segment("clearing", Some(TimeQualifier::AfterMidnight), None),
+      ]
+    );
+
+    let (_, timeline) = parse_timeline("Rain in the morning then cloudy.").unwrap();
+    assert_eq!(
+      timeline,
+      vec![
+        segment("Rain", None, Some(TimeQualifier::InTheMorning)),
+        segment("cloudy", Some(TimeQualifier::InTheMorning), None),
+      ]
+    );
+  }
+}
@@ -0,0 +1,216 @@
+//! Parser for warning and watch entry titles, in either language, e.g.
+//! "SNOWFALL WARNING IN EFFECT, Gatineau" or
+//! "AVERTISSEMENT DE NEIGE EN VIGUEUR, Gatineau".
+use crate::types::{WarningKind, WarningLevel, WarningStatus, WeatherWarning};
+
+/// Kind phrases, longest first so "winter storm" wins over "storm". Phrases
+/// are matched on whole words, so "gel" doesn't match "dégel".
+const KINDS: [(&str, WarningKind); 46] = [
+  ("special weather", WarningKind::SpecialWeather),
+  ("météorologique spécial", WarningKind::SpecialWeather),
+  ("freezing drizzle", WarningKind::FreezingRain),
+  ("freezing rain", WarningKind::FreezingRain),
+  ("pluie verglaçante", WarningKind::FreezingRain),
+  ("bruine verglaçante", WarningKind::FreezingRain),
+  ("winter storm", WarningKind::WinterStorm),
+  ("tempête hivernale", WarningKind::WinterStorm),
+  ("snow squall", WarningKind::SnowSquall),
+  ("bourrasques de neige", WarningKind::SnowSquall),
+  ("extreme cold", WarningKind::ExtremeCold),
+  ("froid extrême", WarningKind::ExtremeCold),
+  ("thunderstorm", WarningKind::Thunderstorm),
+  ("orages", WarningKind::Thunderstorm),
+  ("hurricane force wind", WarningKind::HurricaneForceWind),
+  ("vents de force ouragan", WarningKind::HurricaneForceWind),
+  ("freezing spray", WarningKind::FreezingSpray),
+  ("embruns verglaçants", WarningKind::FreezingSpray),
+  ("strong wind", WarningKind::StrongWind),
+  ("vents forts", WarningKind::StrongWind),
+  ("coup de vent", WarningKind::Gale),
+  ("gale", WarningKind::Gale),
+  ("storm", WarningKind::Storm),
+  ("tempête", WarningKind::Storm),
+  ("squall", WarningKind::Squall),
+  ("grains", WarningKind::Squall),
+  ("waterspout", WarningKind::Waterspout),
+  ("trombes marines", WarningKind::Waterspout),
+  ("blizzard", WarningKind::Blizzard),
+  ("snowfall", WarningKind::Snowfall),
+  ("neige", WarningKind::Snowfall),
+  ("rainfall", WarningKind::Rainfall),
+  ("pluie", WarningKind::Rainfall),
+  ("tornado", WarningKind::Tornado),
+  ("tornade", WarningKind::Tornado),
+  ("heat", WarningKind::Heat),
+  ("chaleur", WarningKind::Heat),
+  ("wind", WarningKind::Wind),
+  ("vents", WarningKind::Wind),
+  ("vent", WarningKind::Wind),
+  ("fog", WarningKind::Fog),
+  ("brouillard", WarningKind::Fog),
+  ("frost", WarningKind::Frost),
+  ("gel", WarningKind::Frost),
+  ("air quality", WarningKind::AirQuality),
+  ("qualité de l'air", WarningKind::AirQuality),
+];
+
+const LEVELS: [(&str, WarningLevel); 8] = [
+  ("warning", WarningLevel::Warning),
+  ("avertissement", WarningLevel::Warning),
+  ("watch", WarningLevel::Watch),
+  ("veille", WarningLevel::Watch),
+  ("advisory", WarningLevel::Advisory),
+  ("avis", WarningLevel::Advisory),
+  ("statement", WarningLevel::Statement),
+  ("bulletin", WarningLevel::Statement),
+];
+
+/// Words of a headline or phrase, without punctuation. Apostrophes split
+/// words, as in "d'orages".
+fn words(text: &str) -> Vec<&str> {
+  text
+    .split(|c: char| !c.is_alphanumeric())
+    .filter(|word| !word.is_empty())
+    .collect()
+}
+
+/// Whether the words of `phrase` appear in `words` in sequence.
+fn contains_phrase(words: &[&str], phrase: &str) -> bool {
+  let phrase = self::words(phrase);
+  words.windows(phrase.len()).any(|window| window == phrase)
+}
+
+/// Parse a warning title. Titles without a known level, such as "No watches
+/// or warnings in effect", are not warnings.
+pub fn parse_warning(title: &str) -> Option<WeatherWarning> {
+  let (headline, area) = match title.rsplit_once(',') {
+    Some((headline, area)) => (headline, Some(area.trim().to_string())),
+    None => (title, None),
+  };
+  let headline = headline.trim().to_lowercase();
+  if headline.starts_with("no ") || headline.starts_with("aucun") {
+    return None;
+  }
+
+  let words = words(&headline);
+
+  let level = LEVELS
+    .iter()
+    .find(|(phrase, _)| contains_phrase(&words, phrase))
+    .map(|(_, level)| *level)?;
+  let status = if contains_phrase(&words, "ended") || contains_phrase(&words, "terminé") {
+    WarningStatus::Ended
+  } else {
+    WarningStatus::InEffect
+  };
+  let kind = KINDS
+    .iter()
+    .find(|(phrase, _)| contains_phrase(&words, phrase))
+    .map(|(_, kind)| kind.clone())
+    .unwrap_or_else(|| {
+      WarningKind::Other(
+        headline
+          .trim_end_matches(" in effect")
+          .trim_end_matches(" en vigueur")
+          .trim_end_matches(" ended")
+          .into(),
+      )
+    });
+
+  Some(WeatherWarning {
+    kind,
+    level,
+    status,
+    area,
+  })
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_parse_warning() {
+    assert_eq!(
+      parse_warning("SNOWFALL WARNING IN EFFECT, Gatineau"),
+      Some(WeatherWarning {
+        kind: WarningKind::Snowfall,
+        level: WarningLevel::Warning,
+        status: WarningStatus::InEffect,
+        area: Some("Gatineau".into()),
+      })
+    );
+    assert_eq!(
+      parse_warning("AVERTISSEMENT DE PLUIE VERGLAÇANTE EN VIGUEUR, Gatineau"),
+      Some(WeatherWarning {
+        kind: WarningKind::FreezingRain,
+        level: WarningLevel::Warning,
+        status: WarningStatus::InEffect,
+        area: Some("Gatineau".into()),
+      })
+    );
+    assert_eq!(
+      parse_warning("SPECIAL WEATHER STATEMENT ENDED, Ottawa").map(|w| (w.kind, w.level, w.status)),
+      Some((
+        WarningKind::SpecialWeather,
+        WarningLevel::Statement,
+        WarningStatus::Ended
+      ))
+    );
+    assert_eq!(
+      parse_warning("Winter storm watch in effect").map(|w| (w.kind, w.level, w.area)),
+      Some((WarningKind::WinterStorm, WarningLevel::Watch, None))
+    );
+    assert_eq!(
+      parse_warning("Strong wind warning in effect, Western Lake Ontario").map(|w| w.kind),
+      Some(WarningKind::StrongWind)
+    );
+    assert_eq!(
+      parse_warning("AVERTISSEMENT DE COUP DE VENT EN VIGUEUR").map(|w| w.kind),
+      Some(WarningKind::Gale)
+    );
+    assert_eq!(
+      parse_warning("Thunderstorm watch in effect").map(|w| w.kind),
+      Some(WarningKind::Thunderstorm)
+    );
+    assert_eq!(
+      parse_warning("AVERTISSEMENT DE VENTS EN VIGUEUR, Gatineau").map(|w| w.kind),
+      Some(WarningKind::Wind)
+    );
+    assert_eq!(
+      parse_warning("BULLETIN SPÉCIAL SUR LA QUALITÉ DE L'AIR EN VIGUEUR").map(|w| w.kind),
+      Some(WarningKind::AirQuality)
+    );
+    assert_eq!(
+      parse_warning("No watches or warnings in effect, Gatineau"),
+      None
+    );
+    assert_eq!(
+      parse_warning("Aucune veille ou alerte en vigueur, Gatineau"),
+      None
+    );
+  }
+
+  #[test]
+  fn test_whole_words() {
+    // Short French phrases don't match within other words.
+    for (title, other) in [
+      (
+        "AVERTISSEMENT DE DÉGEL EN VIGUEUR",
+        "avertissement de dégel",
+      ),
+      ("AVIS DE PRÉVENTION EN VIGUEUR", "avis de prévention"),
+      // Nor do short English and French kinds.
+      ("Nightingale warning in effect", "nightingale warning"),
+      ("Brainstorm warning in effect", "brainstorm warning"),
+      ("Squally weather watch in effect", "squally weather watch"),
+      ("AVIS POUR GRAINSVILLE EN VIGUEUR", "avis pour grainsville"),
+    ] {
+      assert_eq!(
+        parse_warning(title).map(|w| w.kind),
+        Some(WarningKind::Other(other.into())),
+        "{title}"
+      );
+    }
+  }
+}
@@ -0,0 +1,447 @@
+//! Forecast sources other than the Environment Canada city feeds, and the
+//! airport reports of [`AviationWeather`].
+//!
+//! Every provider returns a [`ForeCast`] whose entries are titled the way the
+//! city feed titles them, so the same parsers, alerts, diffs and archive work
+//! whatever the source. The city of a [`Location`] is a feed code for
+//! Environment Canada and `latitude,longitude` for the other providers.
+#[cfg(feature = "chrono")]
+use crate::dates::day_of_week;
+use crate::{
+  l10n::{format_number, Localize},
+  types::{DayNight, DayOfWeek, Observation},
+  ApiError, Category, Entry, Feed, ForeCast, Language, Location, Term, WeatherClient,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+#[cfg(feature = "async")]
+use std::future::Future;
+
+mod aviation_weather;
+mod nws;
+mod open_meteo;
+
+pub use aviation_weather::AviationWeather;
+pub use nws::Nws;
+pub use open_meteo::OpenMeteo;
+
+#[cfg(feature = "async")]
+type HttpClient = reqwest::Client;
+#[cfg(not(feature = "async"))]
+type HttpClient = reqwest::blocking::Client;
+
+/// Some services, such as api.weather.gov, refuse requests without one.
+static USER_AGENT: &str = concat!("my-weather/", env!("CARGO_PKG_VERSION"));
+
+/// A source of forecasts.
+pub trait WeatherProvider {
+  /// Gets the weather forecast for one location.
+  #[cfg(feature = "async")]
+  fn get_weather(
+    &self,
+    location: &Location,
+  ) -> impl Future<Output = Result<ForeCast, ApiError>> + Send;
+
+  #[cfg(not(feature = "async"))]
+  fn get_weather(&self, location: &Location) -> Result<ForeCast, ApiError>;
+}
+
+impl WeatherProvider for WeatherClient {
+  #[cfg(feature = "async")]
+  async fn get_weather(&self, location: &Location) -> Result<ForeCast, ApiError> {
+    WeatherClient::get_weather(self, location).await
+  }
+
+  #[cfg(not(feature = "async"))]
+  fn get_weather(&self, location: &Location) -> Result<ForeCast, ApiError> {
+    WeatherClient::get_weather(self, location)
+  }
+}
+
+/// A point given as `latitude,longitude`, e.g. `45.48,-75.65`.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Coordinates {
+  pub latitude: f64,
+  pub longitude: f64,
+}
+
+impl std::str::FromStr for Coordinates {
+  type Err = ApiError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let invalid = || ApiError::Coordinates(s.into());
+    let (latitude, longitude) = s.split_once(',').ok_or_else(invalid)?;
+    let latitude = latitude.trim().parse::<f64>().map_err(|_| invalid())?;
+    let longitude = longitude.trim().parse::<f64>().map_err(|_| invalid())?;
+    if !(-90. ..=90.).contains(&latitude) || !(-180. ..=180.).contains(&longitude) {
+      return Err(invalid());
+    }
+    Ok(Self {
+      latitude,
+      longitude,
+    })
+  }
+}
+
+impl std::fmt::Display for Coordinates {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{:.4},{:.4}", self.latitude, self.longitude)
+  }
+}
+
+#[cfg(feature = "async")]
+async fn get_text(client: &HttpClient, url: &str) -> Result<String, ApiError> {
+  Ok(
+    client
+      .get(url)
+      .header(reqwest::header::USER_AGENT, USER_AGENT)
+      .send()
+      .await?
+      .error_for_status()?
+      .text()
+      .await?,
+  )
+}
+
+#[cfg(not(feature = "async"))]
+fn get_text(client: &HttpClient, url: &str) -> Result<String, ApiError> {
+  Ok(
+    client
+      .get(url)
+      .header(reqwest::header::USER_AGENT, USER_AGENT)
+      .send()?
+      .error_for_status()?
+      .text()?,
+  )
+}
+
+#[cfg(feature = "async")]
+async fn get_json<T: DeserializeOwned>(client: &HttpClient, url: &str) -> Result<T, ApiError> {
+  Ok(serde_json::from_str(&get_text(client, url).await?)?)
+}
+
+#[cfg(not(feature = "async"))]
+fn get_json<T: DeserializeOwned>(client: &HttpClient, url: &str) -> Result<T, ApiError> {
+  Ok(serde_json::from_str(&get_text(client, url)?)?)
+}
+
+const WEEK: [DayOfWeek; 7] = [
+  DayOfWeek::Monday,
+  DayOfWeek::Tuesday,
+  DayOfWeek::Wednesday,
+  DayOfWeek::Thursday,
+  DayOfWeek::Friday,
+  DayOfWeek::Saturday,
+  DayOfWeek::Sunday,
+];
+
+/// Day of the week of a date or time starting with `yyyy-mm-dd`.
+#[cfg(not(feature = "chrono"))]
+fn day_of_week(date: &str) -> Option<DayOfWeek> {
+  let mut parts = date.get(..10)?.split('-').map(str::parse::<i64>);
+  let (year, month, day) = (
+    parts.next()?.ok()?,
+    parts.next()?.ok()?,
+    parts.next()?.ok()?,
+  );
+  if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+    return None;
+  }
+  // Days since 1970-01-01, a Thursday.
+  let (year, month) = if month <= 2 {
+    (year - 1, month + 9)
+  } else {
+    (year, month - 3)
+  };
+  let era = year.div_euclid(400);
+  let year_of_era = year - era * 400;
+  let day_of_year = (153 * month + 2) / 5 + day - 1;
+  let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+  let days = era * 146_097 + day_of_era - 719_468;
+  Some(WEEK[(days + 3).rem_euclid(7) as usize])
+}
+
+fn previous_day(day: DayOfWeek) -> DayOfWeek {
+  let index = WEEK.iter().position(|d| *d == day).unwrap_or_default();
+  WEEK[(index + 6) % 7]
+}
+
+/// Temperature in words, as the city feed writes it: "minus 5", "zero", "12".
+fn temperature_words(celsius: f32, language: Language) -> String {
+  let celsius = celsius.round() as i32;
+  match (language, celsius) {
+    (Language::English, 0) => "zero".into(),
+    (Language::French, 0) => "zéro".into(),
+    (Language::English, n) if n < 0 => format!("minus {}", -n),
+    (Language::French, n) if n < 0 => format!("moins {}", -n),
+    (_, n) => n.to_string(),
+  }
+}
+
+/// Forecast entry for a period, e.g. "Saturday night: Snow. Low minus 5.",
+/// with a high for the day and a low for the night. An empty description is
+/// left out.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn forecast_entry(
+  language: Language,
+  day_of_week: DayOfWeek,
+  day: DayNight,
+  description: &str,
+  celsius: f32,
+  pop: Option<u8>,
+  summary: Option<String>,
+  updated: Option<String>,
+) -> Entry {
+  let mut period = day_of_week.localize(language);
+  if let Some(first) = period.get(..1) {
+    period = first.to_uppercase() + &period[1..];
+  }
+  let (period, temperature, pop_label) = match (language, day) {
+    (Language::English, DayNight::Day) => (period, "High", "POP"),
+    (Language::English, DayNight::Night) => (format!("{period} night"), "Low", "POP"),
+    (Language::French, DayNight::Day) => (period, "Maximum", "PdP"),
+    (Language::French, DayNight::Night) => (format!("{period} soir et nuit"), "Minimum", "PdP"),
+  };
+  let description = description.trim().trim_end_matches('.');
+  let mut forecast = format!("{temperature} {}.", temperature_words(celsius, language));
+  if !description.is_empty() {
+    forecast = format!("{description}. {forecast}");
+  }
+  if let Some(pop) = pop.filter(|pop| *pop > 0) {
+    forecast.push_str(&format!(" {pop_label} {pop}%"));
+  }
+
+  Entry {
+    title: format!("{period}: {forecast}"),
+    category: Category {
+      term: Term::ForeCast,
+    },
+    summary: summary.unwrap_or(forecast),
+    updated,
+  }
+}
+
+/// Sixteen point compass direction of a wind blowing from `degrees`.
+pub(crate) fn compass(degrees: f32) -> &'static str {
+  const POINTS: [&str; 16] = [
+    "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW", "NW",
+    "NNW",
+  ];
+  POINTS[((degrees.rem_euclid(360.) / 22.5).round() as usize) % 16]
+}
+
+/// Current conditions entry, with the observation written out as the labelled
+/// summary of the city feed.
+fn current_entry(language: Language, observation: &Observation, updated: Option<String>) -> Entry {
+  let number = |n: f32| format_number((n * 10.).round() / 10., language);
+  let labels = match language {
+    Language::English => [
+      "Current Conditions",
+      "Observed at",
+      "Condition",
+      "Temperature",
+      "Pressure",
+      "Visibility",
+      "Humidity",
+      "Wind Chill",
+      "Humidex",
+      "Dewpoint",
+      "Wind",
+      "gust",
+      "calm",
+      "Heat Index",
+    ],
+    Language::French => [
+      "Conditions actuelles",
+      "Enregistrées à",
+      "Condition",
+      "Température",
+      "Pression",
+      "Visibilité",
+      "Humidité",
+      "Refroidissement éolien",
+      "Humidex",
+      "Point de rosée",
+      "Vent",
+      "rafales",
+      "calme",
+      "Indice de chaleur",
+    ],
+  };
+
+  let celsius = observation.temperature.as_ref().map(|t| number(t.value()));
+  let title = match (&observation.condition, &celsius) {
+    (Some(condition), Some(celsius)) => format!("{}: {condition}, {celsius}°C", labels[0]),
+    (Some(condition), None) => format!("{}: {condition}", labels[0]),
+    (None, Some(celsius)) => format!("{}: {celsius}°C", labels[0]),
+    (None, None) => labels[0].into(),
+  };
+
+  let wind = observation.wind.as_ref().map(|wind| {
+    if wind.speed < 1. {
+      return labels[12].into();
+    }
+    let mut text = wind
+      .direction
+      .as_ref()
+      .map(|direction| format!("{direction} {}", number(wind.speed)))
+      .unwrap_or_else(|| number(wind.speed));
+    if let Some(gust) = wind.gust {
+      text.push_str(&format!(" {} {}", labels[11], number(gust)));
+    }
+    text + " km/h"
+  });
+  let values = [
+    (labels[1], observation.observed_at.clone()),
+    (labels[2], observation.condition.clone()),
+    (labels[3], celsius.map(|celsius| format!("{celsius}&deg;C"))),
+    (
+      labels[4],
+      observation.pressure.map(|p| format!("{} kPa", number(p))),
+    ),
+    (
+      labels[5],
+      observation.visibility.map(|v| format!("{} km", number(v))),
+    ),
+    (
+      labels[6],
+      observation.humidity.map(|h| format!("{} %", number(h))),
+    ),
+    (labels[7], observation.wind_chill.map(number)),
+    (labels[8], observation.humidex.map(number)),
+    (labels[13], observation.heat_index.map(number)),
+    (
+      labels[9],
+      observation
+        .dewpoint
+        .as_ref()
+        .map(|t| format!("{}&deg;C", number(t.value()))),
+    ),
+    (labels[10], wind),
+  ];
+  let summary = values
+    .into_iter()
+    .filter_map(|(label, value)| Some(format!("<b>{label}:</b> {} <br/>", value?)))
+    .collect::<Vec<_>>()
+    .join("\n");
+
+  Entry {
+    title,
+    category: Category {
+      term: Term::Current,
+    },
+    summary,
+    updated,
+  }
+}
+
+pub(crate) fn feed(updated: Option<String>, entries: Vec<Entry>) -> ForeCast {
+  ForeCast(Feed {
+    updated,
+    entries,
+    warnings: vec![],
+  })
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::types::{Celsius, Forecast, Temperature, Wind};
+  use std::marker::PhantomData;
+
+  #[test]
+  fn test_coordinates() {
+    let coordinates = "45.48, -75.65".parse::<Coordinates>().unwrap();
+    assert_eq!(coordinates.latitude, 45.48);
+    assert_eq!(coordinates.longitude, -75.65);
+    assert_eq!(coordinates.to_string(), "45.4800,-75.6500");
+    assert!(matches!(
+      "qc-58".parse::<Coordinates>(),
+      Err(ApiError::Coordinates(_))
+    ));
+    assert!("95,10".parse::<Coordinates>().is_err());
+  }
+
+  #[test]
+  fn test_day_of_week() {
+    assert_eq!(day_of_week("2023-01-07"), Some(DayOfWeek::Saturday));
+    assert_eq!(
+      day_of_week("2024-02-29T18:00:00-05:00"),
+      Some(DayOfWeek::Thursday)
+    );
+    assert_eq!(day_of_week("1970-01-01"), Some(DayOfWeek::Thursday));
+    assert_eq!(day_of_week("2023-13-01"), None);
+    assert_eq!(previous_day(DayOfWeek::Monday), DayOfWeek::Sunday);
+  }
+
+  #[test]
+  fn test_entries_parse() {
+    let entry = forecast_entry(
+      Language::English,
+      DayOfWeek::Saturday,
+      DayNight::Night,
+      "Snow",
+      -4.6,
+      Some(60),
+      None,
+      None,
+    );
+    assert_eq!(entry.title, "Saturday night: Snow. Low minus 5. POP 60%");
+    let forecast = entry.title.parse::<Forecast>().unwrap();
+    assert_eq!(forecast.celsius, Temperature::Low(-5., PhantomData));
+    assert_eq!(forecast.pop, Some(60));
+
+    let entry = forecast_entry(
+      Language::French,
+      DayOfWeek::Sunday,
+      DayNight::Day,
+      "Nuageux",
+      0.2,
+      None,
+      None,
+      None,
+    );
+    assert_eq!(entry.title, "Dimanche: Nuageux. Maximum zéro.");
+    assert!(entry.title.parse::<Forecast>().is_ok());
+
+    let observation = Observation {
+      condition: Some("Light Snow".into()),
+      temperature: Some(Temperature::<Celsius>::Current(-3.44, PhantomData)),
+      pressure: Some(101.2),
+      wind: Some(Wind {
+        direction: Some(compass(40.).into()),
+        speed: 17.,
+        gust: Some(30.),
+      }),
+      ..Default::default()
+    };
+    for language in [Language::English, Language::French] {
+      let entry = current_entry(language, &observation, None);
+      let current = crate::types::to_forecast(std::iter::once(entry));
+      let [crate::types::ForecastEntry::Current(current)] = &current[..] else {
+        panic!("no current conditions in {language:?}");
+      };
+      assert_eq!(
+        current.current.celsius,
+        Some(Temperature::Current(-3.4, PhantomData))
+      );
+      let parsed = current.current.observation.as_ref().unwrap();
+      assert_eq!(parsed.pressure, Some(101.2));
+      assert_eq!(
+        parsed.wind.as_ref().unwrap().direction.as_deref(),
+        Some("NE")
+      );
+      assert_eq!(parsed.wind.as_ref().unwrap().gust, Some(30.));
+    }
+
+    let calm = Observation {
+      wind: Some(Wind {
+        direction: None,
+        speed: 0.,
+        gust: None,
+      }),
+      ..Default::default()
+    };
+    let entry = current_entry(Language::French, &calm, None);
+    assert!(entry.summary.contains("<b>Vent:</b> calme <br/>"));
+  }
+}
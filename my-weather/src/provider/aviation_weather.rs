@@ -0,0 +1,146 @@
+//! Airport reports from the Aviation Weather Center,
+//! <https://aviationweather.gov/data/api/>.
+use super::{get_text, HttpClient};
+use crate::{
+  metar::{Metar, ReportParseError, Taf},
+  ApiError,
+};
+
+static BASE_URL: &str = "https://aviationweather.gov/api/data";
+
+/// Latest METAR and TAF of an airport, by ICAO identifier.
+#[derive(Debug, Clone)]
+pub struct AviationWeather {
+  client: HttpClient,
+  base_url: String,
+}
+
+impl Default for AviationWeather {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// The first report of a response, which is empty for an unknown station.
+fn first_report(body: &str, station: &str) -> Result<String, ApiError> {
+  let mut lines = body.lines().skip_while(|line| line.trim().is_empty());
+  let first = lines
+    .next()
+    .ok_or_else(|| ReportParseError(format!("no report for {station}")))?;
+  // TAF groups continue on indented lines.
+  Ok(
+    std::iter::once(first)
+      .chain(lines.take_while(|line| line.starts_with(char::is_whitespace)))
+      .collect::<Vec<_>>()
+      .join("\n"),
+  )
+}
+
+impl AviationWeather {
+  pub fn new() -> Self {
+    Self {
+      client: Default::default(),
+      base_url: BASE_URL.into(),
+    }
+  }
+
+  /// Fetch reports from another server, e.g. a mirror.
+  pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+    self.base_url = base_url.into();
+    self
+  }
+
+  fn url(&self, report: &str, station: &str) -> String {
+    format!(
+      "{}/{report}?ids={station}&format=raw",
+      self.base_url.trim_end_matches('/')
+    )
+  }
+
+  #[cfg(feature = "async")]
+  pub async fn get_metar(&self, station: &str) -> Result<Metar, ApiError> {
+    let body = get_text(&self.client, &self.url("metar", station)).await?;
+    Ok(first_report(&body, station)?.parse()?)
+  }
+
+  #[cfg(not(feature = "async"))]
+  pub fn get_metar(&self, station: &str) -> Result<Metar, ApiError> {
+    let body = get_text(&self.client, &self.url("metar", station))?;
+    Ok(first_report(&body, station)?.parse()?)
+  }
+
+  #[cfg(feature = "async")]
+  pub async fn get_taf(&self, station: &str) -> Result<Taf, ApiError> {
+    let body = get_text(&self.client, &self.url("taf", station)).await?;
+    Ok(first_report(&body, station)?.parse()?)
+  }
+
+  #[cfg(not(feature = "async"))]
+  pub fn get_taf(&self, station: &str) -> Result<Taf, ApiError> {
+    let body = get_text(&self.client, &self.url("taf", station))?;
+    Ok(first_report(&body, station)?.parse()?)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use mockito::Matcher;
+
+  fn mocks(server: &mut mockito::Server) -> Vec<mockito::Mock> {
+    let query = |station: &str| {
+      Matcher::AllOf(vec![
+        Matcher::UrlEncoded("ids".into(), station.into()),
+        Matcher::UrlEncoded("format".into(), "raw".into()),
+      ])
+    };
+    vec![
+      server
+        .mock("GET", "/metar")
+        .match_query(query("CYOW"))
+        .with_body(include_str!("../../fixtures/metar_cyow.txt"))
+        .create(),
+      server
+        .mock("GET", "/taf")
+        .match_query(query("CYOW"))
+        .with_body(include_str!("../../fixtures/taf_cyow.txt"))
+        .create(),
+      server
+        .mock("GET", "/metar")
+        .match_query(query("XXXX"))
+        .with_body("")
+        .create(),
+    ]
+  }
+
+  #[cfg(feature = "async")]
+  #[tokio::test]
+  async fn test_reports() {
+    let mut server = mockito::Server::new_async().await;
+    let _mocks = mocks(&mut server);
+    let client = AviationWeather::new().with_base_url(server.url());
+
+    let metar = client.get_metar("CYOW").await.unwrap();
+    assert_eq!(metar.temperature, Some(-3.3));
+    let taf = client.get_taf("CYOW").await.unwrap();
+    assert_eq!(taf.groups.len(), 4);
+    assert!(matches!(
+      client.get_metar("XXXX").await,
+      Err(ApiError::Report(_))
+    ));
+  }
+
+  #[cfg(not(feature = "async"))]
+  #[test]
+  fn test_reports() {
+    let mut server = mockito::Server::new();
+    let _mocks = mocks(&mut server);
+    let client = AviationWeather::new().with_base_url(server.url());
+
+    let metar = client.get_metar("CYOW").unwrap();
+    assert_eq!(metar.temperature, Some(-3.3));
+    let taf = client.get_taf("CYOW").unwrap();
+    assert_eq!(taf.groups.len(), 4);
+    assert!(matches!(client.get_metar("XXXX"), Err(ApiError::Report(_))));
+  }
+}
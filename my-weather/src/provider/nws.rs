@@ -0,0 +1,466 @@
+//! US National Weather Service forecasts, <https://www.weather.gov/documentation/services-web-api>.
+//!
+//! A point is looked up first, which links to its gridded forecast and the
+//! stations observing near it. Forecasts are only published in English.
+use super::{
+  compass, current_entry, day_of_week, feed, forecast_entry, get_json, previous_day, Coordinates,
+  HttpClient, WeatherProvider,
+};
+use crate::{
+  types::{DayNight, Observation, Temperature, Wind},
+  ApiError, Entry, ForeCast, Language, Location,
+};
+use serde::Deserialize;
+use std::marker::PhantomData;
+
+static BASE_URL: &str = "https://api.weather.gov";
+
+#[derive(Debug, Deserialize)]
+struct Point {
+  properties: PointProperties,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PointProperties {
+  forecast: String,
+  observation_stations: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Forecast {
+  properties: ForecastProperties,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ForecastProperties {
+  update_time: Option<String>,
+  periods: Vec<Period>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Period {
+  start_time: String,
+  is_daytime: bool,
+  temperature: f32,
+  temperature_unit: String,
+  #[serde(default)]
+  probability_of_precipitation: Value,
+  short_forecast: String,
+  detailed_forecast: String,
+}
+
+/// A measurement, `null` when it wasn't taken.
+#[derive(Debug, Default, Deserialize)]
+struct Value {
+  value: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Stations {
+  features: Vec<Station>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Station {
+  properties: StationProperties,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StationProperties {
+  station_identifier: String,
+  name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LatestObservation {
+  properties: ObservationProperties,
+}
+
+/// Latest observation of a station, in SI units: °C, km/h, Pa and m.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ObservationProperties {
+  timestamp: String,
+  text_description: Option<String>,
+  #[serde(default)]
+  temperature: Value,
+  #[serde(default)]
+  dewpoint: Value,
+  #[serde(default)]
+  wind_direction: Value,
+  #[serde(default)]
+  wind_speed: Value,
+  #[serde(default)]
+  wind_gust: Value,
+  #[serde(default)]
+  barometric_pressure: Value,
+  #[serde(default)]
+  visibility: Value,
+  #[serde(default)]
+  relative_humidity: Value,
+  #[serde(default)]
+  wind_chill: Value,
+  #[serde(default)]
+  heat_index: Value,
+}
+
+fn current(station: &StationProperties, observation: ObservationProperties) -> Entry {
+  let wind = observation.wind_speed.value.map(|speed| Wind {
+    direction: observation
+      .wind_direction
+      .value
+      .map(|degrees| compass(degrees).into()),
+    speed,
+    gust: observation.wind_gust.value,
+  });
+  let observation_at = format!("{} {}", station.name, observation.timestamp);
+  let parsed = Observation {
+    observed_at: Some(observation_at),
+    condition: observation
+      .text_description
+      .filter(|text| !text.trim().is_empty()),
+    temperature: observation
+      .temperature
+      .value
+      .map(|t| Temperature::Current(t, PhantomData)),
+    pressure: observation.barometric_pressure.value.map(|pa| pa / 1000.),
+    visibility: observation.visibility.value.map(|m| m / 1000.),
+    humidity: observation.relative_humidity.value,
+    wind_chill: observation.wind_chill.value,
+    heat_index: observation.heat_index.value,
+    dewpoint: observation
+      .dewpoint
+      .value
+      .map(|t| Temperature::Current(t, PhantomData)),
+    wind,
+    ..Default::default()
+  };
+  current_entry(Language::English, &parsed, Some(observation.timestamp))
+}
+
+fn period(period: Period, updated: &Option<String>) -> Option<Entry> {
+  let mut day_of_week = day_of_week(&period.start_time)?;
+  let day = if period.is_daytime {
+    DayNight::Day
+  } else {
+    // "Overnight" periods start after midnight, but belong to the night
+    // before.
+    let hour = period.start_time.get(11..13)?.parse::<u8>().ok()?;
+    if hour < 12 {
+      day_of_week = previous_day(day_of_week);
+    }
+    DayNight::Night
+  };
+  let celsius = match period.temperature_unit.as_str() {
+    "F" => (period.temperature - 32.) * 5. / 9.,
+    _ => period.temperature,
+  };
+  Some(forecast_entry(
+    Language::English,
+    day_of_week,
+    day,
+    &period.short_forecast,
+    celsius,
+    period
+      .probability_of_precipitation
+      .value
+      .map(|pop| pop as u8),
+    Some(period.detailed_forecast),
+    updated.clone(),
+  ))
+}
+
+fn to_forecast(forecast: Forecast, current: Option<Entry>) -> ForeCast {
+  let updated = forecast.properties.update_time;
+  let entries = current
+    .into_iter()
+    .chain(
+      forecast
+        .properties
+        .periods
+        .into_iter()
+        .filter_map(|p| period(p, &updated)),
+    )
+    .collect();
+  feed(updated, entries)
+}
+
+/// Forecasts from the US National Weather Service, in English whatever the
+/// language of the location.
+#[derive(Debug, Clone)]
+pub struct Nws {
+  client: HttpClient,
+  base_url: String,
+}
+
+impl Default for Nws {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Nws {
+  pub fn new() -> Self {
+    Self {
+      client: Default::default(),
+      base_url: BASE_URL.into(),
+    }
+  }
+
+  /// Look points up on another server, e.g. a mirror.
+  pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+    self.base_url = base_url.into();
+    self
+  }
+
+  fn point_url(&self, coordinates: &Coordinates) -> String {
+    format!(
+      "{}/points/{coordinates}",
+      self.base_url.trim_end_matches('/')
+    )
+  }
+
+  fn latest_url(&self, station: &StationProperties) -> String {
+    format!(
+      "{}/stations/{}/observations/latest",
+      self.base_url.trim_end_matches('/'),
+      station.station_identifier
+    )
+  }
+}
+
+impl Nws {
+  /// Current conditions at the first station observing near `point`.
+  #[cfg(feature = "async")]
+  async fn latest(&self, point: &Point) -> Result<Option<Entry>, ApiError> {
+    let Some(url) = &point.properties.observation_stations else {
+      return Ok(None);
+    };
+    let stations: Stations = get_json(&self.client, url).await?;
+    let Some(station) = stations.features.first() else {
+      return Ok(None);
+    };
+    let latest: LatestObservation =
+      get_json(&self.client, &self.latest_url(&station.properties)).await?;
+    Ok(Some(current(&station.properties, latest.properties)))
+  }
+
+  /// Current conditions at the first station observing near `point`.
+  #[cfg(not(feature = "async"))]
+  fn latest(&self, point: &Point) -> Result<Option<Entry>, ApiError> {
+    let Some(url) = &point.properties.observation_stations else {
+      return Ok(None);
+    };
+    let stations: Stations = get_json(&self.client, url)?;
+    let Some(station) = stations.features.first() else {
+      return Ok(None);
+    };
+    let latest: LatestObservation = get_json(&self.client, &self.latest_url(&station.properties))?;
+    Ok(Some(current(&station.properties, latest.properties)))
+  }
+}
+
+impl WeatherProvider for Nws {
+  #[cfg(feature = "async")]
+  async fn get_weather(&self, location: &Location) -> Result<ForeCast, ApiError> {
+    let point: Point = get_json(&self.client, &self.point_url(&location.city.parse()?)).await?;
+    let forecast = get_json(&self.client, &point.properties.forecast).await?;
+    // Stations often have no recent observation, which only leaves the
+    // current conditions out.
+    let entry = self.latest(&point).await.ok().flatten();
+    Ok(to_forecast(forecast, entry))
+  }
+
+  #[cfg(not(feature = "async"))]
+  fn get_weather(&self, location: &Location) -> Result<ForeCast, ApiError> {
+    let point: Point = get_json(&self.client, &self.point_url(&location.city.parse()?))?;
+    let forecast = get_json(&self.client, &point.properties.forecast)?;
+    // Stations often have no recent observation, which only leaves the
+    // current conditions out.
+    let entry = self.latest(&point).ok().flatten();
+    Ok(to_forecast(forecast, entry))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::types::{to_forecast, DayOfWeek, ForecastEntry};
+
+  /// Mock the service, with the links in the recorded responses pointing to
+  /// the mock server. The station has no observation without `latest`.
+  fn mocks(server: &mut mockito::Server, latest: Option<&str>) -> Vec<mockito::Mock> {
+    let url = server.url();
+    let body = |response: &str| response.replace("https://api.weather.gov", &url);
+    vec![
+      server
+        .mock("GET", "/points/42.3600,-71.0600")
+        .match_header("user-agent", mockito::Matcher::Regex("my-weather".into()))
+        .with_body(body(include_str!("../../fixtures/nws_point.json")))
+        .create(),
+      server
+        .mock("GET", "/gridpoints/BOX/71,90/forecast")
+        .with_body(body(include_str!("../../fixtures/nws_forecast.json")))
+        .create(),
+      server
+        .mock("GET", "/gridpoints/BOX/71,90/stations")
+        .with_body(body(include_str!("../../fixtures/nws_stations.json")))
+        .create(),
+      match latest {
+        Some(latest) => server
+          .mock("GET", "/stations/KBOS/observations/latest")
+          .with_body(body(latest)),
+        None => server
+          .mock("GET", "/stations/KBOS/observations/latest")
+          .with_status(404)
+          .with_body(r#"{"status": 404, "title": "Not Found"}"#),
+      }
+      .create(),
+    ]
+  }
+
+  fn assert_forecast(forecast: ForeCast) {
+    assert_eq!(forecast.updated(), Some("2023-01-07T19:42:07+00:00"));
+    let titles = forecast
+      .clone()
+      .entries()
+      .map(|entry| entry.title)
+      .collect::<Vec<_>>();
+    assert_eq!(
+      titles,
+      [
+        "Current Conditions: Light Snow, -1.1°C",
+        "Saturday night: Chance Snow Showers. Low minus 6. POP 40%",
+        "Sunday: Mostly Sunny. High 1.",
+        "Sunday night: Partly Cloudy. Low minus 8.",
+        "Monday: Sunny. High 2.",
+      ]
+    );
+
+    let entries = to_forecast(forecast.entries());
+    let ForecastEntry::Current(current) = &entries[0] else {
+      panic!("no current conditions");
+    };
+    let observation = current.current.observation.as_ref().unwrap();
+    assert_eq!(observation.pressure, Some(101.6));
+    assert_eq!(observation.humidity, Some(88.));
+    assert_eq!(
+      observation
+        .wind
+        .as_ref()
+        .and_then(|w| w.direction.as_deref()),
+      Some("NNE")
+    );
+
+    let ForecastEntry::Future { night, .. } = &entries[1] else {
+      panic!("no forecast");
+    };
+    let night = night.as_ref().unwrap();
+    assert_eq!(night.forecast.day_of_week, DayOfWeek::Saturday);
+    assert!(night.entry.summary.starts_with("A chance of snow showers"));
+  }
+
+  #[cfg(feature = "async")]
+  #[tokio::test]
+  async fn test_get_weather() {
+    let mut server = mockito::Server::new_async().await;
+    let _mocks = mocks(
+      &mut server,
+      Some(include_str!("../../fixtures/nws_observation.json")),
+    );
+    let location = Location::new("42.36,-71.06", Language::English);
+
+    let forecast = Nws::new()
+      .with_base_url(server.url())
+      .get_weather(&location)
+      .await
+      .unwrap();
+    assert_forecast(forecast);
+  }
+
+  #[cfg(not(feature = "async"))]
+  #[test]
+  fn test_get_weather() {
+    let mut server = mockito::Server::new();
+    let _mocks = mocks(
+      &mut server,
+      Some(include_str!("../../fixtures/nws_observation.json")),
+    );
+    let location = Location::new("42.36,-71.06", Language::English);
+
+    let forecast = Nws::new()
+      .with_base_url(server.url())
+      .get_weather(&location)
+      .unwrap();
+    assert_forecast(forecast);
+  }
+
+  #[test]
+  fn test_heat_index() {
+    let station = StationProperties {
+      station_identifier: "KBOS".into(),
+      name: "Boston, Logan International Airport".into(),
+    };
+    let observation: LatestObservation = serde_json::from_str(
+      &include_str!("../../fixtures/nws_observation.json").replace(
+        r#""value": null, "qualityControl": "V"}"#,
+        r#""value": 32.2}"#,
+      ),
+    )
+    .unwrap();
+    let entries = to_forecast(std::iter::once(current(&station, observation.properties)));
+    let ForecastEntry::Current(current) = &entries[0] else {
+      panic!("no current conditions");
+    };
+    let observation = current.current.observation.as_ref().unwrap();
+    assert_eq!(observation.heat_index, Some(32.2));
+    assert_eq!(observation.humidex, None);
+  }
+
+  fn assert_no_current(forecast: ForeCast) {
+    let titles = forecast
+      .entries()
+      .map(|entry| entry.title)
+      .collect::<Vec<_>>();
+    assert_eq!(titles.len(), 4);
+    assert_eq!(
+      titles[0],
+      "Saturday night: Chance Snow Showers. Low minus 6. POP 40%"
+    );
+  }
+
+  #[cfg(feature = "async")]
+  #[tokio::test]
+  async fn test_no_observation() {
+    let location = Location::new("42.36,-71.06", Language::English);
+    for latest in [None, Some(r#"{"properties": {"timestamp": null}}"#)] {
+      let mut server = mockito::Server::new_async().await;
+      let _mocks = mocks(&mut server, latest);
+      let forecast = Nws::new()
+        .with_base_url(server.url())
+        .get_weather(&location)
+        .await
+        .unwrap();
+      assert_no_current(forecast);
+    }
+  }
+
+  #[cfg(not(feature = "async"))]
+  #[test]
+  fn test_no_observation() {
+    let location = Location::new("42.36,-71.06", Language::English);
+    for latest in [None, Some(r#"{"properties": {"timestamp": null}}"#)] {
+      let mut server = mockito::Server::new();
+      let _mocks = mocks(&mut server, latest);
+      let forecast = Nws::new()
+        .with_base_url(server.url())
+        .get_weather(&location)
+        .unwrap();
+      assert_no_current(forecast);
+    }
+  }
+}
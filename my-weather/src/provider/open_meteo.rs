@@ -0,0 +1,353 @@
+//! Open-Meteo forecasts, <https://open-meteo.com/en/docs>.
+use super::{
+  compass, current_entry, day_of_week, feed, forecast_entry, get_json, Coordinates, HttpClient,
+  WeatherProvider,
+};
+use crate::{
+  types::{DayNight, Observation, Temperature, Wind},
+  ApiError, ForeCast, Language, Location,
+};
+use serde::Deserialize;
+use std::marker::PhantomData;
+
+static BASE_URL: &str = "https://api.open-meteo.com/v1/forecast";
+
+const CURRENT: &str = "temperature_2m,relative_humidity_2m,dew_point_2m,weather_code,pressure_msl,\
+visibility,wind_speed_10m,wind_direction_10m,wind_gusts_10m";
+const DAILY: &str =
+  "weather_code,temperature_2m_max,temperature_2m_min,precipitation_probability_max";
+
+#[derive(Debug, Deserialize)]
+struct Response {
+  #[serde(default)]
+  utc_offset_seconds: i32,
+  current: Option<Current>,
+  daily: Option<Daily>,
+}
+
+/// Current values. Times are local, without an offset.
+#[derive(Debug, Deserialize)]
+struct Current {
+  time: String,
+  temperature_2m: Option<f32>,
+  relative_humidity_2m: Option<f32>,
+  dew_point_2m: Option<f32>,
+  weather_code: Option<u8>,
+  /// hPa.
+  pressure_msl: Option<f32>,
+  /// Metres.
+  visibility: Option<f32>,
+  wind_speed_10m: Option<f32>,
+  wind_direction_10m: Option<f32>,
+  wind_gusts_10m: Option<f32>,
+}
+
+/// Daily values, one array element per local date.
+#[derive(Debug, Deserialize)]
+struct Daily {
+  time: Vec<String>,
+  weather_code: Vec<Option<u8>>,
+  temperature_2m_max: Vec<Option<f32>>,
+  temperature_2m_min: Vec<Option<f32>>,
+  #[serde(default)]
+  precipitation_probability_max: Vec<Option<u8>>,
+}
+
+/// Description of a WMO weather interpretation code.
+fn description(code: u8, language: Language) -> &'static str {
+  let (english, french) = match code {
+    0 => ("Clear", "Dégagé"),
+    1 => ("Mainly clear", "Généralement dégagé"),
+    2 => ("Partly cloudy", "Partiellement nuageux"),
+    3 => ("Cloudy", "Nuageux"),
+    45 | 48 => ("Fog", "Brouillard"),
+    51 | 53 | 55 => ("Drizzle", "Bruine"),
+    56 | 57 => ("Freezing drizzle", "Bruine verglaçante"),
+    61 => ("Light rain", "Pluie faible"),
+    63 => ("Rain", "Pluie"),
+    65 => ("Heavy rain", "Pluie forte"),
+    66 | 67 => ("Freezing rain", "Pluie verglaçante"),
+    71 => ("Light snow", "Neige légère"),
+    73 => ("Snow", "Neige"),
+    75 => ("Heavy snow", "Neige forte"),
+    77 => ("Snow grains", "Neige en grains"),
+    80..=82 => ("Showers", "Averses"),
+    85 | 86 => ("Flurries", "Averses de neige"),
+    95 => ("Thunderstorm", "Orages"),
+    96 | 99 => ("Thunderstorm with hail", "Orages avec grêle"),
+    _ => ("Not available", "Non disponible"),
+  };
+  match language {
+    Language::English => english,
+    Language::French => french,
+  }
+}
+
+/// Local time with its offset, in RFC 3339 format.
+fn local_time(time: &str, offset_seconds: i32) -> String {
+  let seconds = if time.len() == 16 { ":00" } else { "" };
+  let sign = if offset_seconds < 0 { '-' } else { '+' };
+  let offset = offset_seconds.unsigned_abs() / 60;
+  format!("{time}{seconds}{sign}{:02}:{:02}", offset / 60, offset % 60)
+}
+
+fn to_forecast(response: Response, language: Language) -> ForeCast {
+  let updated = response
+    .current
+    .as_ref()
+    .map(|current| local_time(&current.time, response.utc_offset_seconds));
+  let mut entries = vec![];
+
+  if let Some(current) = &response.current {
+    let observation = Observation {
+      condition: current
+        .weather_code
+        .map(|code| description(code, language).into()),
+      temperature: current
+        .temperature_2m
+        .map(|t| Temperature::Current(t, PhantomData)),
+      pressure: current.pressure_msl.map(|hpa| hpa / 10.),
+      visibility: current.visibility.map(|m| m / 1000.),
+      humidity: current.relative_humidity_2m,
+      dewpoint: current
+        .dew_point_2m
+        .map(|t| Temperature::Current(t, PhantomData)),
+      wind: current.wind_speed_10m.map(|speed| Wind {
+        direction: current
+          .wind_direction_10m
+          .map(|degrees| compass(degrees).into()),
+        speed,
+        gust: current.wind_gusts_10m.filter(|gust| *gust > speed),
+      }),
+      ..Default::default()
+    };
+    entries.push(current_entry(language, &observation, updated.clone()));
+  }
+
+  if let Some(daily) = &response.daily {
+    for (index, date) in daily.time.iter().enumerate() {
+      let Some(day_of_week) = day_of_week(date) else {
+        continue;
+      };
+      let text = daily
+        .weather_code
+        .get(index)
+        .copied()
+        .flatten()
+        .map_or("", |code| description(code, language));
+      let pop = daily
+        .precipitation_probability_max
+        .get(index)
+        .copied()
+        .flatten();
+      if let Some(high) = daily.temperature_2m_max.get(index).copied().flatten() {
+        entries.push(forecast_entry(
+          language,
+          day_of_week,
+          DayNight::Day,
+          text,
+          high,
+          pop,
+          None,
+          updated.clone(),
+        ));
+      }
+      // The night low is the next morning's minimum. The daily weather code
+      // is the day's, so the night has no description.
+      if let Some(Some(low)) = daily.temperature_2m_min.get(index + 1) {
+        entries.push(forecast_entry(
+          language,
+          day_of_week,
+          DayNight::Night,
+          "",
+          *low,
+          None,
+          None,
+          updated.clone(),
+        ));
+      }
+    }
+  }
+
+  feed(updated, entries)
+}
+
+/// Forecasts from Open-Meteo, in either language.
+#[derive(Debug, Clone)]
+pub struct OpenMeteo {
+  client: HttpClient,
+  base_url: String,
+}
+
+impl Default for OpenMeteo {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl OpenMeteo {
+  pub fn new() -> Self {
+    Self {
+      client: Default::default(),
+      base_url: BASE_URL.into(),
+    }
+  }
+
+  /// Use another forecast endpoint, e.g. a self-hosted instance.
+  pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+    self.base_url = base_url.into();
+    self
+  }
+
+  fn url(&self, coordinates: &Coordinates) -> String {
+    format!(
+      "{}?latitude={}&longitude={}&current={CURRENT}&daily={DAILY}&timezone=auto",
+      self.base_url, coordinates.latitude, coordinates.longitude
+    )
+  }
+}
+
+impl WeatherProvider for OpenMeteo {
+  #[cfg(feature = "async")]
+  async fn get_weather(&self, location: &Location) -> Result<ForeCast, ApiError> {
+    let url = self.url(&location.city.parse()?);
+    Ok(to_forecast(
+      get_json(&self.client, &url).await?,
+      location.language,
+    ))
+  }
+
+  #[cfg(not(feature = "async"))]
+  fn get_weather(&self, location: &Location) -> Result<ForeCast, ApiError> {
+    let url = self.url(&location.city.parse()?);
+    Ok(to_forecast(
+      get_json(&self.client, &url)?,
+      location.language,
+    ))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::types::{to_forecast, ForecastEntry};
+  use mockito::Matcher;
+
+  fn mock(server: &mut mockito::Server) -> mockito::Mock {
+    server
+      .mock("GET", "/v1/forecast")
+      .match_query(Matcher::UrlEncoded("latitude".into(), "45.48".into()))
+      .with_body(include_str!("../../fixtures/open_meteo.json"))
+      .create()
+  }
+
+  fn assert_forecast(forecast: ForeCast) {
+    assert_eq!(forecast.updated(), Some("2023-01-07T15:00:00-05:00"));
+    let titles = forecast
+      .clone()
+      .entries()
+      .map(|entry| entry.title)
+      .collect::<Vec<_>>();
+    assert_eq!(
+      &titles[..4],
+      [
+        "Current Conditions: Light snow, -3.4°C",
+        "Saturday: Snow. High minus 2. POP 80%",
+        "Saturday night: Low minus 6.",
+        "Sunday: Cloudy. High minus 3. POP 20%",
+      ]
+    );
+
+    let entries = to_forecast(forecast.entries());
+    let ForecastEntry::Current(current) = &entries[0] else {
+      panic!("no current conditions");
+    };
+    let observation = current.current.observation.as_ref().unwrap();
+    assert_eq!(observation.pressure, Some(101.2));
+    assert_eq!(observation.visibility, Some(8.));
+    assert_eq!(
+      observation.wind,
+      Some(Wind {
+        direction: Some("NE".into()),
+        speed: 17.,
+        gust: Some(31.),
+      })
+    );
+    assert_eq!(
+      entries
+        .iter()
+        .filter(|entry| matches!(entry, ForecastEntry::Future { .. }))
+        .count(),
+      3
+    );
+  }
+
+  #[test]
+  fn test_short_arrays() {
+    // Arrays shorter than the dates leave the missing values out.
+    let response = serde_json::from_str(
+      r#"{"daily": {
+        "time": ["2023-01-07", "2023-01-08", "2023-01-09"],
+        "weather_code": [73, 3],
+        "temperature_2m_max": [-2.1],
+        "temperature_2m_min": [-8.0, -9.0]
+      }}"#,
+    )
+    .unwrap();
+    let titles = super::to_forecast(response, Language::English)
+      .entries()
+      .map(|entry| entry.title)
+      .collect::<Vec<_>>();
+    assert_eq!(
+      titles,
+      [
+        "Saturday: Snow. High minus 2.",
+        "Saturday night: Low minus 9."
+      ]
+    );
+  }
+
+  #[test]
+  fn test_local_time() {
+    assert_eq!(
+      local_time("2023-01-07T15:00", -18000),
+      "2023-01-07T15:00:00-05:00"
+    );
+    assert_eq!(
+      local_time("2023-01-07T15:00", 19800),
+      "2023-01-07T15:00:00+05:30"
+    );
+  }
+
+  #[cfg(feature = "async")]
+  #[tokio::test]
+  async fn test_get_weather() {
+    let mut server = mockito::Server::new_async().await;
+    let _mock = mock(&mut server);
+    let provider = OpenMeteo::new().with_base_url(format!("{}/v1/forecast", server.url()));
+
+    let location = Location::new("45.48,-75.65", Language::English);
+    assert_forecast(provider.get_weather(&location).await.unwrap());
+    let location = Location::new("qc-58", Language::English);
+    assert!(matches!(
+      provider.get_weather(&location).await,
+      Err(ApiError::Coordinates(_))
+    ));
+  }
+
+  #[cfg(not(feature = "async"))]
+  #[test]
+  fn test_get_weather() {
+    let mut server = mockito::Server::new();
+    let _mock = mock(&mut server);
+    let provider = OpenMeteo::new().with_base_url(format!("{}/v1/forecast", server.url()));
+
+    let location = Location::new("45.48,-75.65", Language::English);
+    assert_forecast(provider.get_weather(&location).unwrap());
+    let location = Location::new("qc-58", Language::English);
+    assert!(matches!(
+      provider.get_weather(&location),
+      Err(ApiError::Coordinates(_))
+    ));
+  }
+}
@@ -0,0 +1,199 @@
+//! Streaming feed decoder built on quick-xml.
+//!
+//! Reads the feed one event at a time without building an intermediate
+//! document, and borrows entry text from the input unless it has to be
+//! unescaped.
+use crate::{
+  check_entry, ApiError, Category, DecodeWarning, EntryRef, Feed, ForeCast, PartialEntry,
+};
+use quick_xml::{
+  events::{BytesStart, Event},
+  Reader,
+};
+use std::borrow::Cow;
+
+/// Decoded feed borrowing from the input.
+#[derive(Debug)]
+pub struct FeedRef<'a> {
+  pub updated: Option<Cow<'a, str>>,
+  pub entries: Vec<EntryRef<'a>>,
+  pub warnings: Vec<DecodeWarning>,
+}
+
+impl From<FeedRef<'_>> for ForeCast {
+  fn from(feed: FeedRef<'_>) -> Self {
+    ForeCast(Feed {
+      updated: feed.updated.map(Cow::into_owned),
+      entries: feed.entries.into_iter().map(EntryRef::into_owned).collect(),
+      warnings: feed.warnings,
+    })
+  }
+}
+
+/// Entry child element whose text is being read.
+#[derive(Copy, Clone)]
+enum Field {
+  Title,
+  Summary,
+  Updated,
+}
+
+impl<'a> PartialEntry<'a> {
+  fn field(&mut self, field: Field) -> &mut Option<Cow<'a, str>> {
+    match field {
+      Field::Title => &mut self.title,
+      Field::Summary => &mut self.summary,
+      Field::Updated => &mut self.updated,
+    }
+  }
+}
+
+/// Append text to a field, only allocating when it arrives in pieces.
+fn append<'a>(field: &mut Option<Cow<'a, str>>, text: Cow<'a, str>) {
+  match field {
+    Some(existing) => existing.to_mut().push_str(&text),
+    None => *field = Some(text),
+  }
+}
+
+fn utf8(bytes: Cow<'_, [u8]>) -> Result<Cow<'_, str>, ApiError> {
+  let text = match bytes {
+    Cow::Borrowed(bytes) => Cow::Borrowed(
+      std::str::from_utf8(bytes).map_err(|e| quick_xml::Error::NonDecodable(Some(e)))?,
+    ),
+    Cow::Owned(bytes) => Cow::Owned(
+      String::from_utf8(bytes).map_err(|e| quick_xml::Error::NonDecodable(Some(e.utf8_error())))?,
+    ),
+  };
+  Ok(text)
+}
+
+fn category(element: &BytesStart) -> Result<Option<Category>, ApiError> {
+  let term = element.try_get_attribute("term")?;
+  Ok(
+    term
+      .map(|term| term.unescape_value())
+      .transpose()?
+      .map(|term| Category {
+        term: term.as_ref().into(),
+      }),
+  )
+}
+
+/// Decode a feed, validating entries the same way as the serde decoder.
+pub fn decode(input: &str) -> Result<FeedRef<'_>, ApiError> {
+  let mut reader = Reader::from_str(input);
+  reader.trim_text(true);
+
+  let mut updated = None;
+  let mut entries = vec![];
+  let mut warnings = vec![];
+  let mut entry: Option<PartialEntry> = None;
+  let mut field = None;
+  let mut index = 0;
+
+  loop {
+    match reader.read_event()? {
+      Event::Start(element) => match (element.local_name().as_ref(), entry.as_mut()) {
+        (b"entry", None) => entry = Some(PartialEntry::default()),
+        (b"title", Some(_)) => field = Some(Field::Title),
+        (b"summary", Some(_)) => field = Some(Field::Summary),
+        (b"updated", _) => field = Some(Field::Updated),
+        (b"category", Some(entry)) => entry.category = category(&element)?,
+        _ => field = None,
+      },
+      Event::Empty(element) => {
+        if let (b"category", Some(entry)) = (element.local_name().as_ref(), entry.as_mut()) {
+          entry.category = category(&element)?;
+        }
+      }
+      Event::Text(text) => match (field, entry.as_mut()) {
+        (Some(field), Some(entry)) => append(entry.field(field), text.unescape()?),
+        (Some(Field::Updated), None) => append(&mut updated, text.unescape()?),
+        _ => (),
+      },
+      Event::CData(text) => {
+        if let (Some(field), Some(entry)) = (field, entry.as_mut()) {
+          append(entry.field(field), utf8(text.into_inner())?);
+        }
+      }
+      Event::End(element) => {
+        field = None;
+        if element.local_name().as_ref() == b"entry" {
+          if let Some(entry) = entry.take() {
+            entries.extend(check_entry(index, entry, &mut warnings));
+            index += 1;
+          }
+        }
+      }
+      Event::Eof => break,
+      _ => (),
+    }
+  }
+
+  Ok(FeedRef {
+    updated,
+    entries,
+    warnings,
+  })
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_matches_serde_decoder() {
+    for input in [
+      include_str!("../fixtures/qc-58_e.xml"),
+      include_str!("../fixtures/qc-58_f.xml"),
+    ] {
+      let streamed = decode(input).unwrap();
+      let parsed = input.parse::<ForeCast>().unwrap();
+
+      assert_eq!(streamed.entries.len(), parsed.0.entries.len());
+      for (streamed, parsed) in streamed.entries.iter().zip(&parsed.0.entries) {
+        assert_eq!(streamed.title, parsed.title);
+        assert_eq!(streamed.category.term, parsed.category.term);
+        assert_eq!(streamed.summary, parsed.summary);
+        assert_eq!(streamed.updated.as_deref(), parsed.updated.as_deref());
+      }
+      assert_eq!(streamed.updated.as_deref(), parsed.updated());
+      assert_eq!(streamed.warnings, parsed.warnings());
+    }
+  }
+
+  #[test]
+  fn test_borrows_input() {
+    let feed = decode(include_str!("../fixtures/qc-58_e.xml")).unwrap();
+    let forecast = feed
+      .entries
+      .iter()
+      .find(|entry| entry.title.starts_with("Sunday:"))
+      .unwrap();
+    assert!(matches!(forecast.title, Cow::Borrowed(_)));
+    assert!(matches!(forecast.summary, Cow::Borrowed(_)));
+  }
+
+  #[test]
+  fn test_missing_elements() {
+    let feed = decode(
+      r#"<feed><title>Feed</title>
+<entry><title>Air Quality</title><category term="Air Quality"/><summary>Low</summary></entry>
+<entry><category term="Weather Forecasts"/></entry>
+</feed>"#,
+    )
+    .unwrap();
+    assert_eq!(feed.entries.len(), 1);
+    assert_eq!(
+      feed.warnings,
+      [
+        DecodeWarning::UnknownCategory {
+          title: "Air Quality".into(),
+          term: "Air Quality".into()
+        },
+        DecodeWarning::MissingTitle { index: 1 },
+      ]
+    );
+  }
+}
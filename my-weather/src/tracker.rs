@@ -0,0 +1,309 @@
+//! Warning lifecycle across refreshes.
+//!
+//! Each feed only lists the warnings in effect when it was issued. A
+//! [`WarningTracker`] remembers what it saw last time, per location, so that
+//! a warning can be reported as new, continued, updated or ended. The state
+//! is saved as TOML between runs.
+use crate::{
+  types::{ForecastEntry, WarningKind, WarningStatus, WeatherWarning},
+  Location,
+};
+use serde::{Deserialize, Serialize};
+use std::{io::ErrorKind, path::Path};
+use thiserror::Error;
+
+/// Tracker state read or write errors.
+#[derive(Error, Debug)]
+pub enum TrackerError {
+  #[error("Warning state io error {0}")]
+  Io(#[from] std::io::Error),
+  #[error("Invalid warning state {0}")]
+  Decode(#[from] toml::de::Error),
+  #[error("Warning state encoding error {0}")]
+  Encode(#[from] toml::ser::Error),
+}
+
+/// How a warning changed since the previous feed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WarningChange {
+  New,
+  Continued,
+  /// Same hazard and area with a new title or summary, e.g. a watch upgraded
+  /// to a warning.
+  Updated,
+  /// Either listed as ended, or no longer in the feed.
+  Ended,
+}
+
+/// Identifies a warning across refreshes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "content")]
+enum WarningKey {
+  /// A hazard in an area, whatever its level.
+  Hazard {
+    kind: WarningKind,
+    area: Option<String>,
+  },
+  /// A title that couldn't be parsed.
+  Title(String),
+}
+
+impl WarningKey {
+  fn new(title: &str, warning: Option<&WeatherWarning>) -> Self {
+    match warning {
+      Some(warning) => Self::Hazard {
+        kind: warning.kind.clone(),
+        area: warning.area.clone(),
+      },
+      None => Self::Title(title.into()),
+    }
+  }
+}
+
+/// A warning as last seen.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct TrackedWarning {
+  location: Location,
+  key: WarningKey,
+  title: String,
+  summary: String,
+  /// Ended warnings are kept while the feed still lists them, so they are
+  /// only reported once.
+  ended: bool,
+}
+
+/// A warning that changed, or continued, since the previous feed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WarningEvent {
+  pub location: Location,
+  pub change: WarningChange,
+  pub title: String,
+  pub summary: String,
+  pub warning: Option<WeatherWarning>,
+}
+
+impl WarningEvent {
+  /// Whether the event is worth a notification.
+  pub fn is_notable(&self) -> bool {
+    self.change != WarningChange::Continued
+  }
+}
+
+impl std::fmt::Display for WarningEvent {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let change = match self.change {
+      WarningChange::New => "New",
+      WarningChange::Continued => "Continued",
+      WarningChange::Updated => "Updated",
+      WarningChange::Ended => "Ended",
+    };
+    write!(f, "{change}: {}", self.title)
+  }
+}
+
+/// Warnings seen in the previous feed of each location.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WarningTracker {
+  #[serde(rename = "warning", default)]
+  warnings: Vec<TrackedWarning>,
+}
+
+impl WarningTracker {
+  /// State saved at `path`, or an empty tracker when there is none yet.
+  pub fn load(path: impl AsRef<Path>) -> Result<Self, TrackerError> {
+    match std::fs::read_to_string(path) {
+      Ok(state) => Ok(toml::from_str(&state)?),
+      Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+      Err(e) => Err(e.into()),
+    }
+  }
+
+  pub fn save(&self, path: impl AsRef<Path>) -> Result<(), TrackerError> {
+    std::fs::write(path, toml::to_string(self)?)?;
+    Ok(())
+  }
+
+  /// Compare the warnings in a new [`to_forecast`](crate::types::to_forecast)
+  /// output for `location` with the previous one, and remember them for next
+  /// time. Every warning in the feed gets an event, followed by one for each
+  /// warning that is no longer listed.
+  pub fn update(&mut self, location: &Location, entries: &[ForecastEntry]) -> Vec<WarningEvent> {
+    let (mut previous, others): (Vec<_>, Vec<_>) = std::mem::take(&mut self.warnings)
+      .into_iter()
+      .partition(|tracked| tracked.location == *location);
+    self.warnings = others;
+    let mut events = vec![];
+
+    for entry in entries {
+      let ForecastEntry::Warning(entry) = entry else {
+        continue;
+      };
+      let warning = entry.title.parse::<WeatherWarning>().ok();
+      let key = WarningKey::new(&entry.title, warning.as_ref());
+      let ended = warning
+        .as_ref()
+        .map(|warning| warning.status == WarningStatus::Ended)
+        .unwrap_or(false);
+      // The same warning listed twice in one feed is only reported once.
+      if self
+        .warnings
+        .iter()
+        .any(|tracked| tracked.location == *location && tracked.key == key)
+      {
+        continue;
+      }
+
+      let last = previous
+        .iter()
+        .position(|tracked| tracked.key == key)
+        .map(|index| previous.remove(index));
+      let change = match last {
+        Some(last) if last.ended && ended => None,
+        // A warning back in effect after it ended is new again.
+        Some(last) if last.ended => Some(WarningChange::New),
+        Some(_) if ended => Some(WarningChange::Ended),
+        Some(last) if last.title == entry.title && last.summary == entry.summary => {
+          Some(WarningChange::Continued)
+        }
+        Some(_) => Some(WarningChange::Updated),
+        None if ended => Some(WarningChange::Ended),
+        None => Some(WarningChange::New),
+      };
+
+      if let Some(change) = change {
+        events.push(WarningEvent {
+          location: location.clone(),
+          change,
+          title: entry.title.clone(),
+          summary: entry.summary.clone(),
+          warning: warning.clone(),
+        });
+      }
+      self.warnings.push(TrackedWarning {
+        location: location.clone(),
+        key,
+        title: entry.title.clone(),
+        summary: entry.summary.clone(),
+        ended,
+      });
+    }
+
+    // Whatever is left was dropped from the feed.
+    events.extend(
+      previous
+        .into_iter()
+        .filter(|tracked| !tracked.ended)
+        .map(|tracked| WarningEvent {
+          location: tracked.location,
+          change: WarningChange::Ended,
+          warning: tracked.title.parse().ok(),
+          title: tracked.title,
+          summary: tracked.summary,
+        }),
+    );
+
+    events
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::{types::feed_forecast, Language};
+
+  const NONE: &str = "No watches or warnings in effect, Gatineau";
+
+  fn forecast(warning: &str) -> Vec<ForecastEntry> {
+    feed_forecast(&include_str!("../fixtures/qc-58_e.xml").replace(NONE, warning))
+  }
+
+  fn changes(events: &[WarningEvent]) -> Vec<WarningChange> {
+    events.iter().map(|event| event.change).collect()
+  }
+
+  #[test]
+  fn test_lifecycle() {
+    let location = Location::new("qc-58", Language::English);
+    let mut tracker = WarningTracker::default();
+
+    assert!(tracker.update(&location, &forecast(NONE)).is_empty());
+
+    let watch = forecast("WINTER STORM WATCH IN EFFECT, Gatineau");
+    assert_eq!(
+      changes(&tracker.update(&location, &watch)),
+      [WarningChange::New]
+    );
+    let events = tracker.update(&location, &watch);
+    assert_eq!(changes(&events), [WarningChange::Continued]);
+    assert!(!events[0].is_notable());
+
+    let warning = forecast("WINTER STORM WARNING IN EFFECT, Gatineau");
+    assert_eq!(
+      changes(&tracker.update(&location, &warning)),
+      [WarningChange::Updated]
+    );
+
+    let ended = forecast("WINTER STORM WARNING ENDED, Gatineau");
+    assert_eq!(
+      changes(&tracker.update(&location, &ended)),
+      [WarningChange::Ended]
+    );
+    assert!(tracker.update(&location, &ended).is_empty());
+    assert!(tracker.update(&location, &forecast(NONE)).is_empty());
+  }
+
+  #[test]
+  fn test_back_in_effect() {
+    let location = Location::new("qc-58", Language::English);
+    let mut tracker = WarningTracker::default();
+
+    let warning = forecast("SNOWFALL WARNING IN EFFECT, Gatineau");
+    let ended = forecast("SNOWFALL WARNING ENDED, Gatineau");
+    assert_eq!(
+      changes(&tracker.update(&location, &warning)),
+      [WarningChange::New]
+    );
+    assert_eq!(
+      changes(&tracker.update(&location, &ended)),
+      [WarningChange::Ended]
+    );
+    assert_eq!(
+      changes(&tracker.update(&location, &warning)),
+      [WarningChange::New]
+    );
+    assert_eq!(
+      changes(&tracker.update(&location, &warning)),
+      [WarningChange::Continued]
+    );
+    assert_eq!(
+      changes(&tracker.update(&location, &ended)),
+      [WarningChange::Ended]
+    );
+  }
+
+  #[test]
+  fn test_dropped_and_saved() {
+    let gatineau = Location::new("qc-58", Language::English);
+    let ottawa = Location::new("on-118", Language::English);
+    let mut tracker = WarningTracker::default();
+
+    tracker.update(&gatineau, &forecast("SNOWFALL WARNING IN EFFECT, Gatineau"));
+    tracker.update(&ottawa, &forecast("FOG ADVISORY IN EFFECT, Ottawa"));
+
+    let path = std::env::temp_dir().join(format!("my-weather-tracker-{}.toml", std::process::id()));
+    tracker.save(&path).unwrap();
+    let mut tracker = WarningTracker::load(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    let events = tracker.update(&gatineau, &forecast(NONE));
+    assert_eq!(changes(&events), [WarningChange::Ended]);
+    assert_eq!(events[0].title, "SNOWFALL WARNING IN EFFECT, Gatineau");
+    // Other locations are left alone.
+    assert_eq!(
+      changes(&tracker.update(&ottawa, &forecast("FOG ADVISORY IN EFFECT, Ottawa"))),
+      [WarningChange::Continued]
+    );
+
+    assert!(WarningTracker::load(&path).unwrap().warnings.is_empty());
+  }
+}
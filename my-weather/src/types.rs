@@ -1,13 +1,18 @@
-use crate::{Entry, Term};
-use serde::{Deserialize, Serialize, Serializer};
-use std::{collections::HashMap, marker::PhantomData, ops::Not};
+use crate::{l10n::Localize, meteorology, Entry, Language, Term};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, marker::PhantomData};
+use thiserror::Error;
 
-use crate::parsers::{parse_current_forecast, parse_forecast};
+use crate::parsers::{
+  parse_current_forecast, parse_forecast, parse_observation, parse_timeline, parse_title_language,
+  parse_warning,
+};
 
 /// Wrapper type for weather entry elements allowing
 /// classifying and grouping entries.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(tag = "type", content = "content")]
+#[allow(clippy::large_enum_variant)]
 pub enum ForecastEntry {
   Warning(Entry),
   Current(CurrentForecastWithEntry),
@@ -21,12 +26,18 @@ pub enum ForecastEntry {
 impl ForecastEntry {
   /// Convert html to pango doc.
   pub fn summary(&self) -> String {
+    self.localized_summary(Language::English)
+  }
+
+  /// Convert html to pango doc with day and night labels in the given language.
+  pub fn localized_summary(&self, language: Language) -> String {
     let remap_html = |input: &str| {
       input
         .replace("&deg;", "°")
         .replace("<br/>", "")
         .replace(". ", ".\n")
         .replace("minus ", "-")
+        .replace("moins ", "-")
         .replace("plus ", "")
     };
 
@@ -36,16 +47,26 @@ impl ForecastEntry {
       Self::Future { day, night, .. } => match (day, night) {
         (Some(d), Some(n)) => {
           format!(
-            "<b>Day:</b>\n{}\n\n<b>Night:</b>\n{}",
+            "<b>{}:</b>\n{}\n\n<b>{}:</b>\n{}",
+            DayNight::Day.localize(language),
             remap_html(&d.entry.summary),
+            DayNight::Night.localize(language),
             remap_html(&n.entry.summary)
           )
         }
         (Some(d), None) => {
-          format!("<b>Day:</b>\n{}", remap_html(&d.entry.summary),)
+          format!(
+            "<b>{}:</b>\n{}",
+            DayNight::Day.localize(language),
+            remap_html(&d.entry.summary),
+          )
         }
         (None, Some(n)) => {
-          format!("<b>Night:</b>\n{}", remap_html(&n.entry.summary))
+          format!(
+            "<b>{}:</b>\n{}",
+            DayNight::Night.localize(language),
+            remap_html(&n.entry.summary)
+          )
         }
         (None, None) => String::new(),
       },
@@ -53,21 +74,15 @@ impl ForecastEntry {
   }
 }
 
-#[derive(Hash, PartialEq, Eq, Copy, Clone)]
-enum Day {
-  Monday,
-  Tuesday,
-  Wednesday,
-  Thursday,
-  Friday,
-  Saturday,
-  Sunday,
-}
-
-fn parse_future_forecast(entry: &Entry) -> Option<(Day, Forecast)> {
-  let day_key = Day::try_from(entry).ok()?;
-  let forecast_entry = entry.title.as_str().parse::<Forecast>().ok()?;
-  Some((day_key, forecast_entry))
+fn parse_future_forecast(entry: &Entry) -> Option<(DayOfWeek, Forecast)> {
+  let mut forecast_entry = entry.title.as_str().parse::<Forecast>().ok()?;
+  // The timeline grammar only knows English summaries.
+  if parse_title_language(&entry.title) == Some(Language::English) {
+    if let Ok((_, timeline)) = parse_timeline(&entry.summary) {
+      forecast_entry.timeline = timeline;
+    }
+  }
+  Some((forecast_entry.day_of_week, forecast_entry))
 }
 
 /// Convert an iteration of weather Entry items into a Vec of ForeCastEntry, grouping
@@ -79,14 +94,19 @@ pub fn to_forecast(entries: impl Iterator<Item = Entry>) -> Vec<ForecastEntry> {
   for (index, entry) in entries.enumerate() {
     match entry.category.term {
       Term::Current => {
-        result.extend(
-          entry
-            .title
-            .as_str()
-            .parse::<CurrentForecast>()
-            .ok()
-            .map(|current| ForecastEntry::Current(CurrentForecastWithEntry { current, entry })),
-        );
+        // Keep whatever was reported, even when the title can't be parsed.
+        let mut current = entry
+          .title
+          .as_str()
+          .parse::<CurrentForecast>()
+          .unwrap_or_default();
+        if let Some(observation) = parse_observation(&entry.summary) {
+          current = current.with_observation(observation);
+        }
+        result.push(ForecastEntry::Current(CurrentForecastWithEntry {
+          current,
+          entry,
+        }));
       }
       Term::Warnings => result.push(ForecastEntry::Warning(entry)),
       Term::ForeCast => {
@@ -116,6 +136,8 @@ pub fn to_forecast(entries: impl Iterator<Item = Entry>) -> Vec<ForecastEntry> {
           }
         }
       }
+      // Categories without a display are dropped here but kept in the feed.
+      Term::Missing | Term::Other(_) => (),
     }
   }
 
@@ -132,92 +154,177 @@ pub fn to_forecast(entries: impl Iterator<Item = Entry>) -> Vec<ForecastEntry> {
   result
 }
 
-/// The day could not be parsed.
-struct NotAForecastDay;
-
-impl TryFrom<&Entry> for Day {
-  type Error = NotAForecastDay;
-
-  fn try_from(value: &Entry) -> Result<Self, Self::Error> {
-    match value.category.term {
-      Term::ForeCast => match &value.title {
-        s if s.starts_with("Monday") => Ok(Day::Monday),
-        s if s.starts_with("Tuesday") => Ok(Day::Tuesday),
-        s if s.starts_with("Wednesday") => Ok(Day::Wednesday),
-        s if s.starts_with("Thursday") => Ok(Day::Thursday),
-        s if s.starts_with("Friday") => Ok(Day::Friday),
-        s if s.starts_with("Saturday") => Ok(Day::Saturday),
-        s if s.starts_with("Sunday") => Ok(Day::Sunday),
-        _ => Err(NotAForecastDay),
-      },
-      _ => Err(NotAForecastDay),
-    }
-  }
-}
-
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Celsius {}
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Fahrenheit {}
 
-#[derive(Debug, PartialEq, Copy, Clone, Serialize, Deserialize)]
-#[serde(tag = "type", content = "content")]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(
+  from = "TemperatureRepr",
+  into = "TemperatureRepr",
+  bound(serialize = "Unit: Clone", deserialize = "")
+)]
 pub enum Temperature<Unit> {
-  #[serde(serialize_with = "serialize_temperature")]
   High(f32, PhantomData<Unit>),
-  #[serde(serialize_with = "serialize_temperature")]
   Low(f32, PhantomData<Unit>),
-  #[serde(serialize_with = "serialize_temperature")]
   Current(f32, PhantomData<Unit>),
+  /// "Temperature rising to minus 2 by morning."
+  Rising(f32, Option<TimeQualifier>, PhantomData<Unit>),
+  /// "Temperature falling to minus 3 in the afternoon."
+  Falling(f32, Option<TimeQualifier>, PhantomData<Unit>),
+  /// "Temperature steady near minus 1."
+  Steady(f32, PhantomData<Unit>),
 }
 
-fn serialize_temperature<S, Unit>(
-  temperature: &f32,
-  _p: &PhantomData<Unit>,
-  serializer: S,
-) -> Result<S::Ok, S::Error>
-where
-  S: Serializer,
-{
-  serializer.serialize_f32(*temperature)
+impl<Unit> Temperature<Unit> {
+  /// The temperature value regardless of kind.
+  pub fn value(&self) -> f32 {
+    match self {
+      Self::High(n, _)
+      | Self::Low(n, _)
+      | Self::Current(n, _)
+      | Self::Rising(n, ..)
+      | Self::Falling(n, ..)
+      | Self::Steady(n, _) => *n,
+    }
+  }
+
+  /// When a temperature trend is expected to reach its value.
+  pub fn qualifier(&self) -> Option<TimeQualifier> {
+    match self {
+      Self::Rising(_, qualifier, _) | Self::Falling(_, qualifier, _) => qualifier.clone(),
+      _ => None,
+    }
+  }
+
+  /// Same kind of temperature with a different value.
+  fn with_value<U>(&self, n: f32) -> Temperature<U> {
+    match self {
+      Self::High(..) => Temperature::High(n, PhantomData),
+      Self::Low(..) => Temperature::Low(n, PhantomData),
+      Self::Current(..) => Temperature::Current(n, PhantomData),
+      Self::Rising(_, qualifier, _) => Temperature::Rising(n, qualifier.clone(), PhantomData),
+      Self::Falling(_, qualifier, _) => Temperature::Falling(n, qualifier.clone(), PhantomData),
+      Self::Steady(..) => Temperature::Steady(n, PhantomData),
+    }
+  }
+}
+
+/// Serialized form of a temperature. The value is always a plain number in
+/// `content` so clients can read it without knowing the kind.
+#[derive(Serialize, Deserialize)]
+struct TemperatureRepr {
+  #[serde(rename = "type")]
+  kind: TemperatureKind,
+  content: f32,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  qualifier: Option<TimeQualifier>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum TemperatureKind {
+  High,
+  Low,
+  Current,
+  Rising,
+  Falling,
+  Steady,
 }
 
-impl PartialEq for Temperature<Celsius> {
-  fn eq(&self, other: &Self) -> bool {
-    self == other
+impl<Unit> From<Temperature<Unit>> for TemperatureRepr {
+  fn from(value: Temperature<Unit>) -> Self {
+    let kind = match value {
+      Temperature::High(..) => TemperatureKind::High,
+      Temperature::Low(..) => TemperatureKind::Low,
+      Temperature::Current(..) => TemperatureKind::Current,
+      Temperature::Rising(..) => TemperatureKind::Rising,
+      Temperature::Falling(..) => TemperatureKind::Falling,
+      Temperature::Steady(..) => TemperatureKind::Steady,
+    };
+    Self {
+      kind,
+      content: value.value(),
+      qualifier: value.qualifier(),
+    }
+  }
+}
+
+impl<Unit> From<TemperatureRepr> for Temperature<Unit> {
+  fn from(value: TemperatureRepr) -> Self {
+    let n = value.content;
+    match value.kind {
+      TemperatureKind::High => Self::High(n, PhantomData),
+      TemperatureKind::Low => Self::Low(n, PhantomData),
+      TemperatureKind::Current => Self::Current(n, PhantomData),
+      TemperatureKind::Rising => Self::Rising(n, value.qualifier, PhantomData),
+      TemperatureKind::Falling => Self::Falling(n, value.qualifier, PhantomData),
+      TemperatureKind::Steady => Self::Steady(n, PhantomData),
+    }
   }
 }
 
 impl From<Temperature<Celsius>> for Temperature<Fahrenheit> {
   fn from(value: Temperature<Celsius>) -> Self {
     let convert = |n| (n * 2.) + 30.;
-    match value {
-      Temperature::High(n, _) => Temperature::<Fahrenheit>::High(convert(n), PhantomData),
-      Temperature::Low(n, _) => Temperature::<Fahrenheit>::Low(convert(n), PhantomData),
-      Temperature::Current(n, _) => Temperature::<Fahrenheit>::Current(convert(n), PhantomData),
-    }
+    value.with_value(convert(value.value()))
   }
 }
 
 impl std::fmt::Display for Temperature<Celsius> {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    let mut w = |n| write!(f, "{n}°C");
-    match self {
-      Self::High(n, _) => w(n),
-      Self::Low(n, _) => w(n),
-      Self::Current(n, _) => w(n),
-    }
+    write!(f, "{}°C", self.value())
   }
 }
 
 impl std::fmt::Display for Temperature<Fahrenheit> {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    let mut w = |n| write!(f, "{n:.0}°F");
+    write!(f, "{:.0}°F", self.value())
+  }
+}
+
+/// Time of day a temperature trend refers to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeQualifier {
+  ByMorning,
+  ByNoon,
+  ByAfternoon,
+  ByEvening,
+  ByMidnight,
+  InTheMorning,
+  InTheAfternoon,
+  InTheEvening,
+  ThisMorning,
+  ThisAfternoon,
+  ThisEvening,
+  Overnight,
+  AfterMidnight,
+  NearNoon,
+  NearMidnight,
+  /// A phrasing the grammar doesn't know, as written.
+  Other(String),
+}
+
+impl TimeQualifier {
+  pub fn as_str(&self) -> &str {
     match self {
-      Self::High(n, _) => w(n),
-      Self::Low(n, _) => w(n),
-      Self::Current(n, _) => w(n),
+      Self::ByMorning => "by morning",
+      Self::ByNoon => "by noon",
+      Self::ByAfternoon => "by afternoon",
+      Self::ByEvening => "by evening",
+      Self::ByMidnight => "by midnight",
+      Self::InTheMorning => "in the morning",
+      Self::InTheAfternoon => "in the afternoon",
+      Self::InTheEvening => "in the evening",
+      Self::ThisMorning => "this morning",
+      Self::ThisAfternoon => "this afternoon",
+      Self::ThisEvening => "this evening",
+      Self::Overnight => "overnight",
+      Self::AfterMidnight => "after midnight",
+      Self::NearNoon => "near noon",
+      Self::NearMidnight => "near midnight",
+      Self::Other(other) => other,
     }
   }
 }
@@ -235,15 +342,175 @@ pub struct Forecast {
   pub description: String,
   pub day: DayNight,
   pub day_of_week: DayOfWeek,
+  /// Secondary values and qualifiers following the main temperature.
+  #[serde(default)]
+  pub details: Vec<TemperatureDetail>,
+  /// Conditions in order of occurrence, parsed from the entry summary.
+  #[serde(default)]
+  pub timeline: Vec<TimelineSegment>,
+  /// Probability of precipitation in percent.
+  #[serde(default)]
+  pub pop: Option<u8>,
+  /// How the temperature compares with the climate normal of the forecast
+  /// date, when normals are loaded.
+  #[serde(default)]
+  pub normal: Option<Departure>,
+  /// Sun and moon of the forecast date, when the location's coordinates are
+  /// known.
+  #[serde(default)]
+  pub almanac: Option<Almanac>,
+}
+
+/// Forecast high or low against the normal of its calendar day, in celsius.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Departure {
+  /// Normal high for a day, normal low for a night.
+  pub normal: f32,
+  /// Forecast temperature minus the normal.
+  pub departure: f32,
+  /// Whether the forecast would break the record high of a day or the
+  /// record low of a night.
+  pub record: bool,
 }
 
+impl Departure {
+  /// The same departure in fahrenheit degrees, for a forecast shown in
+  /// fahrenheit.
+  pub fn fahrenheit(&self) -> Self {
+    Self {
+      normal: self.normal * 9. / 5. + 32.,
+      departure: self.departure * 9. / 5.,
+      record: self.record,
+    }
+  }
+}
+
+/// Sun and moon of a date at a location. Times are in RFC 3339 format in the
+/// location's time zone, and missing when the sun doesn't rise, set or reach
+/// 6° below the horizon that day.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Almanac {
+  /// Start of civil twilight.
+  pub dawn: Option<String>,
+  pub sunrise: Option<String>,
+  pub sunset: Option<String>,
+  /// End of civil twilight.
+  pub dusk: Option<String>,
+  /// Minutes between sunrise and sunset, 1440 under the midnight sun.
+  pub day_length: u32,
+  pub moon: MoonPhase,
+  /// Fraction of the moon lit, from 0 to 1.
+  pub illumination: f32,
+}
+
+impl std::fmt::Display for Almanac {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    // "2023-01-07T07:41:00-05:00" -> "07:41"
+    let time = |time: &Option<String>| {
+      time
+        .as_deref()
+        .and_then(|time| time.get(11..16))
+        .unwrap_or("—")
+        .to_string()
+    };
+    write!(
+      f,
+      "Sunrise {}, sunset {}, {} h {:02} min of daylight, {} ({:.0}% lit)",
+      time(&self.sunrise),
+      time(&self.sunset),
+      self.day_length / 60,
+      self.day_length % 60,
+      self.moon.as_str(),
+      self.illumination * 100.
+    )
+  }
+}
+
+/// Phase of the moon, each centered on its name, e.g. a full moon from 1/16
+/// before to 1/16 after the middle of the lunar month.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MoonPhase {
+  NewMoon,
+  WaxingCrescent,
+  FirstQuarter,
+  WaxingGibbous,
+  FullMoon,
+  WaningGibbous,
+  LastQuarter,
+  WaningCrescent,
+}
+
+impl MoonPhase {
+  /// Phase of a fraction of the lunar month since the new moon.
+  pub fn from_fraction(fraction: f64) -> Self {
+    const PHASES: [MoonPhase; 8] = [
+      MoonPhase::NewMoon,
+      MoonPhase::WaxingCrescent,
+      MoonPhase::FirstQuarter,
+      MoonPhase::WaxingGibbous,
+      MoonPhase::FullMoon,
+      MoonPhase::WaningGibbous,
+      MoonPhase::LastQuarter,
+      MoonPhase::WaningCrescent,
+    ];
+    PHASES[(fraction.rem_euclid(1.) * 8. + 0.5) as usize % 8]
+  }
+
+  pub fn as_str(&self) -> &str {
+    match self {
+      Self::NewMoon => "new moon",
+      Self::WaxingCrescent => "waxing crescent",
+      Self::FirstQuarter => "first quarter",
+      Self::WaxingGibbous => "waxing gibbous",
+      Self::FullMoon => "full moon",
+      Self::WaningGibbous => "waning gibbous",
+      Self::LastQuarter => "last quarter",
+      Self::WaningCrescent => "waning crescent",
+    }
+  }
+}
+
+impl std::fmt::Display for Departure {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str(&self.localize(Language::English))
+  }
+}
+
+/// A condition within a forecast period and when it starts or ends.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineSegment {
+  pub condition: String,
+  pub start: Option<TimeQualifier>,
+  pub end: Option<TimeQualifier>,
+}
+
+/// Secondary temperature or qualifier in a forecast title. Values are in celsius.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "content")]
+pub enum TemperatureDetail {
+  /// "High 15 except 20 inland."
+  Except { celsius: f32, area: String },
+  /// "Low minus 5 with temperature rising to plus 2 by morning."
+  Rising {
+    celsius: f32,
+    time: Option<TimeQualifier>,
+  },
+  /// "High 6 with temperature falling to zero in the afternoon."
+  Falling {
+    celsius: f32,
+    time: Option<TimeQualifier>,
+  },
+  /// "High plus 3 near the coast."
+  Area(String),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum DayNight {
   Day,
   Night,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum DayOfWeek {
   Monday,
   Tuesday,
@@ -268,14 +535,80 @@ impl DayOfWeek {
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Error)]
+#[error("invalid forecast title: {0}")]
 pub struct TitleParseError(String);
 
+/// Severity of a warning entry.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WarningLevel {
+  Warning,
+  Watch,
+  Advisory,
+  Statement,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WarningStatus {
+  InEffect,
+  Ended,
+}
+
+/// Hazard a warning is about.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WarningKind {
+  Snowfall,
+  Rainfall,
+  FreezingRain,
+  WinterStorm,
+  SnowSquall,
+  Blizzard,
+  ExtremeCold,
+  Heat,
+  Wind,
+  Fog,
+  Frost,
+  Thunderstorm,
+  Tornado,
+  AirQuality,
+  SpecialWeather,
+  /// Marine, winds of 20 to 33 knots.
+  StrongWind,
+  /// Marine, winds of 34 to 47 knots.
+  Gale,
+  /// Marine, winds of 48 to 63 knots.
+  Storm,
+  /// Marine, winds of 64 knots or more.
+  HurricaneForceWind,
+  FreezingSpray,
+  Squall,
+  Waterspout,
+  /// Lowercase headline of a kind that isn't known.
+  Other(String),
+}
+
+/// A warning or watch parsed from its entry title.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct WeatherWarning {
+  pub kind: WarningKind,
+  pub level: WarningLevel,
+  pub status: WarningStatus,
+  pub area: Option<String>,
+}
+
+impl std::str::FromStr for WeatherWarning {
+  type Err = TitleParseError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    parse_warning(s).ok_or_else(|| TitleParseError(s.into()))
+  }
+}
+
 impl std::str::FromStr for Forecast {
   type Err = TitleParseError;
 
   fn from_str(s: &str) -> Result<Self, Self::Err> {
-    parse_forecast::<Celsius>(s)
+    parse_forecast(s)
       .map_err(|e| TitleParseError(e.to_string()))
       .map(|(_, forecast)| forecast)
   }
@@ -287,14 +620,140 @@ pub struct CurrentForecastWithEntry {
   pub entry: Entry,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Current conditions. Stations do not always report every value, so each
+/// field is `None` when it was not observed.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
 pub struct CurrentForecast {
-  pub celsius: Temperature<Celsius>,
-  pub fahrenheit: Temperature<Fahrenheit>,
-  pub description: String,
+  pub celsius: Option<Temperature<Celsius>>,
+  pub fahrenheit: Option<Temperature<Fahrenheit>>,
+  pub description: Option<String>,
+  #[serde(default)]
+  pub observation: Option<Observation>,
+  /// Sun and moon of the day, when the location's coordinates are known.
+  #[serde(default)]
+  pub almanac: Option<Almanac>,
+}
+
+impl CurrentForecast {
+  /// Fill in values missing from the title with those from the observation.
+  pub(crate) fn with_observation(mut self, observation: Observation) -> Self {
+    if self.celsius.is_none() {
+      self.celsius = observation.temperature.clone();
+      self.fahrenheit = observation.temperature.clone().map(Into::into);
+    }
+    if self.description.is_none() {
+      self.description = observation.condition.clone();
+    }
+    self.observation = Some(observation.with_derived());
+    self
+  }
+}
+
+/// Observation block from the summary of a current conditions entry.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct Observation {
+  /// Station name and time of the observation.
+  pub observed_at: Option<String>,
+  pub condition: Option<String>,
+  pub temperature: Option<Temperature<Celsius>>,
+  /// Pressure in kPa.
+  pub pressure: Option<f32>,
+  pub tendency: Option<PressureTendency>,
+  /// Visibility in km.
+  pub visibility: Option<f32>,
+  /// Relative humidity in percent.
+  pub humidity: Option<f32>,
+  pub wind_chill: Option<f32>,
+  pub humidex: Option<f32>,
+  pub dewpoint: Option<Temperature<Celsius>>,
+  pub wind: Option<Wind>,
+  /// Air Quality Health Index.
+  pub aqhi: Option<f32>,
+  /// Height of the lowest broken or overcast layer in feet, from airport
+  /// reports.
+  #[serde(default)]
+  pub ceiling: Option<u32>,
+  /// Heat index in °C, computed from the temperature and humidity.
+  #[serde(default)]
+  pub heat_index: Option<f32>,
+  /// Temperature at which frost forms, computed from a dewpoint below
+  /// freezing.
+  #[serde(default)]
+  pub frost_point: Option<Temperature<Celsius>>,
+}
+
+impl Observation {
+  /// Fill in the values a station didn't report but that can be computed
+  /// from the ones it did: dewpoint or humidity from each other, then wind
+  /// chill, humidex, heat index and frost point. Feels-like values are
+  /// rounded to whole degrees like those of the feeds.
+  pub fn with_derived(mut self) -> Self {
+    if let Some(temperature) = self.temperature.clone() {
+      if self.dewpoint.is_none() {
+        self.dewpoint = self.humidity.and_then(|humidity| {
+          let dewpoint = meteorology::dewpoint(&temperature, humidity)?.value();
+          Some(Temperature::Current(
+            (dewpoint * 10.).round() / 10.,
+            PhantomData,
+          ))
+        });
+      }
+      if self.humidity.is_none() {
+        self.humidity = self
+          .dewpoint
+          .as_ref()
+          .map(|dewpoint| meteorology::relative_humidity(&temperature, dewpoint).round());
+      }
+      let round = |t: Option<Temperature<Celsius>>| t.map(|t| t.value().round());
+      if self.wind_chill.is_none() {
+        let wind = self.wind.as_ref().map(|wind| wind.speed);
+        self.wind_chill = round(wind.and_then(|wind| meteorology::wind_chill(&temperature, wind)));
+      }
+      if self.humidex.is_none() {
+        self.humidex = round(
+          self
+            .dewpoint
+            .as_ref()
+            .and_then(|dewpoint| meteorology::humidex(&temperature, dewpoint)),
+        );
+      }
+      if self.heat_index.is_none() {
+        self.heat_index = round(
+          self
+            .humidity
+            .and_then(|humidity| meteorology::heat_index(&temperature, humidity)),
+        );
+      }
+    }
+    if self.frost_point.is_none() {
+      self.frost_point = self
+        .dewpoint
+        .as_ref()
+        .and_then(meteorology::frost_point)
+        .map(|t| Temperature::Current((t.value() * 10.).round() / 10., PhantomData));
+    }
+    self
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq)]
+pub enum PressureTendency {
+  Rising,
+  Falling,
+  Steady,
 }
 
-#[derive(Debug)]
+/// Observed wind. A calm wind has no direction and a speed of 0.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Wind {
+  pub direction: Option<String>,
+  /// Speed in km/h.
+  pub speed: f32,
+  pub gust: Option<f32>,
+}
+
+#[derive(Debug, Error)]
+#[error("invalid current conditions: {0}")]
 pub struct CurrentForecastError(String);
 
 impl std::str::FromStr for CurrentForecast {
@@ -306,3 +765,9 @@ impl std::str::FromStr for CurrentForecast {
       .map(|(_, cf)| cf)
   }
 }
+
+/// Forecast of a feed document, for tests.
+#[cfg(test)]
+pub(crate) fn feed_forecast(feed: &str) -> Vec<ForecastEntry> {
+  to_forecast(feed.parse::<crate::ForeCast>().unwrap().entries())
+}
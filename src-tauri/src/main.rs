@@ -5,12 +5,15 @@
 
 use chrono::Local;
 use my_weather::{
+  alerts::{AlertEngine, AlertEvent, Rules},
+  climate::Normals,
   get_weather,
   types::{to_forecast, ForecastEntry},
+  ForeCast,
 };
 use serde::Serialize;
-use std::time::Duration;
-use tauri::{async_runtime::JoinHandle, Manager, Window};
+use std::{sync::Mutex, time::Duration};
+use tauri::{async_runtime::JoinHandle, Manager, State, Window};
 use tokio::time;
 
 #[derive(Serialize, Clone)]
@@ -21,6 +24,34 @@ struct LocalApiError(String);
 struct WeatherResponse {
   forecasts: Vec<ForecastEntry>,
   fetched: String,
+  warnings: Vec<String>,
+  /// Alert rules that fired for this forecast and not the previous one.
+  alerts: Vec<AlertEvent>,
+}
+
+/// Alert rules shared by the refresh timer and client requests.
+struct Alerts(Mutex<AlertEngine>);
+
+impl WeatherResponse {
+  fn new(forecast: ForeCast, alerts: &Alerts, normals: &Normals) -> Self {
+    let warnings = forecast
+      .warnings()
+      .iter()
+      .map(|warning| warning.to_string())
+      .collect();
+    let mut forecasts = to_forecast(forecast.entries());
+    normals.annotate(&mut forecasts, &Local);
+    WeatherResponse {
+      alerts: alerts
+        .0
+        .lock()
+        .map(|mut engine| engine.evaluate(&forecasts))
+        .unwrap_or_default(),
+      warnings,
+      forecasts,
+      fetched: format!("{}", Local::now().format("%x %r")),
+    }
+  }
 }
 
 impl std::fmt::Display for LocalApiError {
@@ -31,20 +62,28 @@ impl std::fmt::Display for LocalApiError {
 
 /// Handle the client request for a new forecast.
 #[tauri::command]
-async fn get_weather_gui() -> Result<WeatherResponse, LocalApiError> {
+async fn get_weather_gui(
+  alerts: State<'_, Alerts>,
+  normals: State<'_, Normals>,
+) -> Result<WeatherResponse, LocalApiError> {
   get_weather()
     .await
-    .map(|forecast| to_forecast(forecast.entries()))
-    .map(|forecasts| WeatherResponse {
-      forecasts,
-      fetched: format!("{}", Local::now().format("%x %r")),
-    })
+    .map(|forecast| WeatherResponse::new(forecast, &alerts, &normals))
     .map_err(|err| LocalApiError(err.to_string()))
 }
 
 fn main() {
   tauri::Builder::default()
     .setup(|app| {
+      let rules = Rules::from_env().unwrap_or_else(|err| {
+        eprintln!("{err}");
+        Rules::default()
+      });
+      app.manage(Alerts(Mutex::new(AlertEngine::new(rules))));
+      app.manage(Normals::from_env().unwrap_or_else(|err| {
+        eprintln!("{err}");
+        Normals::default()
+      }));
       start_refresh(app.get_window("main").expect("No main window"));
       Ok(())
     })
@@ -61,10 +100,12 @@ fn start_refresh(window: Window) -> JoinHandle<()> {
       interval.tick().await;
       get_weather()
         .await
-        .map(|forecast| to_forecast(forecast.entries()))
-        .map(|forecasts| WeatherResponse {
-          forecasts,
-          fetched: format!("{}", Local::now().format("%x %r")),
+        .map(|forecast| {
+          WeatherResponse::new(
+            forecast,
+            &window.state::<Alerts>(),
+            &window.state::<Normals>(),
+          )
         })
         .map_err(|err| LocalApiError(err.to_string()))
         .and_then(|forecast| {
@@ -3,7 +3,7 @@ import './App.css'
 import './components/FutureForecast/Forecast';
 import FutureForecast from './components/FutureForecast/Forecast';
 import { invoke } from '@tauri-apps/api';
-import { ForecastEntry, WeatherResponse } from "./common/types";
+import { AlertEvent, ForecastEntry, WeatherResponse } from "./common/types";
 import { WebviewWindow } from "@tauri-apps/api/window";
 
 type AppState = {
@@ -11,6 +11,8 @@ type AppState = {
     fetching: boolean;
     error?: string;
     lastRefreshed?: string;
+    warnings: string[];
+    alerts: AlertEvent[];
 }
 
 type Action = { type: "getWeather" }
@@ -34,11 +36,13 @@ function reducer(state: AppState, action: Action): AppState {
             fetching: false,
             entries: action.weather.forecasts,
             lastRefreshed: action.weather.fetched,
+            warnings: action.weather.warnings,
+            alerts: action.weather.alerts,
         }
     }
 }
 
-const INITIAL_STATE: AppState = { fetching: false, entries: [] };
+const INITIAL_STATE: AppState = { fetching: false, entries: [], warnings: [], alerts: [] };
 
 function App() {
     const [state, dispatch] = useReducer(reducer, INITIAL_STATE);
@@ -72,8 +76,29 @@ function App() {
         }
     }
 
+    function alertText(alert: AlertEvent) {
+        switch (alert.trigger.type) {
+            case "Period": {
+                const { period, forecast } = alert.trigger.content;
+                const name = period.day === "Night" ? `${period.day_of_week} night` : period.day_of_week;
+                return `${alert.rule}: ${name} ${forecast.celsius.content}°C`;
+            }
+            case "Warning":
+                return `${alert.rule}: ${alert.trigger.content.title}`;
+            case "AirQuality": {
+                const { period, index } = alert.trigger.content;
+                return `${alert.rule}: ${period} AQHI ${index}`;
+            }
+        }
+    }
+
     return (
         <div className="App">
+            {state.alerts.length > 0 &&
+                <ul className="alerts">
+                    {state.alerts.map(alert => <li>{alertText(alert)}</li>)}
+                </ul>
+            }
             {renderForeCast()}
             <button
                 className="refresh"
@@ -84,6 +109,11 @@ function App() {
             {state.lastRefreshed &&
                 <div className="status">
                     Loaded {state.lastRefreshed}
+                    {state.warnings.length > 0 &&
+                        <span title={state.warnings.join("\n")}>
+                            {` (${state.warnings.length} feed warnings)`}
+                        </span>
+                    }
                 </div>
             }
         </div>
@@ -1,6 +1,8 @@
 export type WeatherResponse = {
     forecasts: ForecastEntry[],
     fetched: string,
+    warnings: string[],
+    alerts: AlertEvent[],
 }
 
 export type Forecast = {
@@ -11,10 +13,11 @@ export type Entry = {
   title: string;
   summary: string;
   category: Category;
+  updated: string | null;
 }
 
 export interface Category {
-  term: "Weather Forecasts" | "Current Conditions";
+  term: "Weather Forecasts" | "Current Conditions" | "Warnings and Watches" | string;
 }
 
 export type ForecastEntry =
@@ -25,12 +28,37 @@ export type ForecastEntry =
 export type CurrentEntry = {
   entry: { title: string, summary: string };
   current: {
-    celsius: Temperature,
-    fahrenheit: Temperature,
-    description: string
+    celsius: Temperature | null,
+    fahrenheit: Temperature | null,
+    description: string | null,
+    observation: Observation | null,
+    almanac: Almanac | null,
   };
 }
 
+export type Observation = {
+  observed_at: string | null;
+  condition: string | null;
+  temperature: Temperature | null;
+  pressure: number | null;
+  tendency: "Rising" | "Falling" | "Steady" | null;
+  visibility: number | null;
+  humidity: number | null;
+  wind_chill: number | null;
+  humidex: number | null;
+  heat_index: number | null;
+  dewpoint: Temperature | null;
+  frost_point: Temperature | null;
+  wind: Wind | null;
+  aqhi: number | null;
+}
+
+export type Wind = {
+  direction: string | null;
+  speed: number;
+  gust: number | null;
+}
+
 export type FutureEntry = {
   day: FutureDayNight;
   night: FutureDayNight;
@@ -43,14 +71,67 @@ export type FutureDayNight = {
     day: "Day" | "Night";
     day_of_week: DayOfWeek;
     description: string;
+    details: TemperatureDetail[];
+    timeline: TimelineSegment[];
+    pop: number | null;
+    normal: Departure | null;
+    almanac: Almanac | null;
   },
   entry: Entry;
 }
 
+export type Almanac = {
+  dawn: string | null;
+  sunrise: string | null;
+  sunset: string | null;
+  dusk: string | null;
+  day_length: number;
+  moon: "NewMoon" | "WaxingCrescent" | "FirstQuarter" | "WaxingGibbous"
+    | "FullMoon" | "WaningGibbous" | "LastQuarter" | "WaningCrescent";
+  illumination: number;
+}
+
+export type Departure = {
+  normal: number;
+  departure: number;
+  record: boolean;
+}
+
+export type TimelineSegment = {
+  condition: string;
+  start: TimeQualifier | null;
+  end: TimeQualifier | null;
+}
+
+export type TemperatureDetail =
+  { type: "Except"; content: { celsius: number; area: string } }
+  | { type: "Rising"; content: { celsius: number; time?: TimeQualifier } }
+  | { type: "Falling"; content: { celsius: number; time?: TimeQualifier } }
+  | { type: "Area"; content: string };
+
 export type Temperature = {
-  type: "High" | "Low" | "Current", content: number;
+  type: "High" | "Low" | "Current" | "Rising" | "Falling" | "Steady";
+  content: number;
+  qualifier?: TimeQualifier;
 }
 
+export type TimeQualifier = "ByMorning"
+  | "ByNoon"
+  | "ByAfternoon"
+  | "ByEvening"
+  | "ByMidnight"
+  | "InTheMorning"
+  | "InTheAfternoon"
+  | "InTheEvening"
+  | "ThisMorning"
+  | "ThisAfternoon"
+  | "ThisEvening"
+  | "Overnight"
+  | "AfterMidnight"
+  | "NearNoon"
+  | "NearMidnight"
+  | { Other: string };
+
 export type DayOfWeek = "Monday"
   | "Tuesday"
   | "Wednesday"
@@ -62,4 +143,25 @@ export type DayOfWeek = "Monday"
 export type WarningEntry = {
   title: string;
   summary: string;
-}
\ No newline at end of file
+}
+export type Period = {
+  day_of_week: DayOfWeek;
+  day: "Day" | "Night";
+}
+
+export type WeatherWarning = {
+  kind: string | { Other: string };
+  level: "Warning" | "Watch" | "Advisory" | "Statement";
+  status: "InEffect" | "Ended";
+  area: string | null;
+}
+
+export type AlertEvent = {
+  rule: string;
+  trigger:
+    { type: "Period"; content: { period: Period; forecast: FutureDayNight["forecast"] } }
+    | { type: "Warning"; content: { title: string; warning: WeatherWarning } }
+    | { type: "AirQuality"; content: { period: string; index: number; risk: Risk } };
+}
+
+export type Risk = "low" | "moderate" | "high" | "very_high";
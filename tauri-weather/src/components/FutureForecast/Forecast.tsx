@@ -1,4 +1,7 @@
-import { CurrentEntry, ForecastEntry, FutureEntry, WarningEntry, } from "../../common/types";
+import {
+    CurrentEntry, Departure, ForecastEntry, FutureEntry, Temperature, TimelineSegment, TimeQualifier,
+    WarningEntry,
+} from "../../common/types";
 import Tooltip from "../Tooltip/Tooltip";
 import classes from "./Forecast.module.css";
 
@@ -45,10 +48,12 @@ function renderFuture(entry: FutureEntry, unit: Unit) {
                     <Tooltip message={entry.day.entry.summary}>
                         <div className={classes.day}>
                             <div className={classes.temperature}>
-                                {renderTemperature(entry.day.forecast.celsius.content, unit)}
+                                {renderTrend(entry.day.forecast.celsius, unit)}
                             </div>
                             <div className={classes.description}>
                                 {entry.day.forecast.description}
+                                {renderTimeline(entry.day.forecast.timeline)}
+                                {renderNormal(entry.day.forecast.normal, unit)}
                             </div>
                         </div>
                     </Tooltip>
@@ -57,10 +62,12 @@ function renderFuture(entry: FutureEntry, unit: Unit) {
                     <Tooltip message={entry.night.entry.summary}>
                         <div className={classes.night}>
                             <div className={classes.temperature}>
-                                {renderTemperature(entry.night.forecast.celsius.content, unit)}
+                                {renderTrend(entry.night.forecast.celsius, unit)}
                             </div>
                             <div className={classes.description}>
                                 {entry.night.forecast.description}
+                                {renderTimeline(entry.night.forecast.timeline)}
+                                {renderNormal(entry.night.forecast.normal, unit)}
                             </div>
                         </div>
                     </Tooltip>
@@ -78,6 +85,62 @@ function renderTemperature(t: number, unit: Unit, fractionDigits?: boolean): str
     }
 }
 
+const TREND_ARROWS: Partial<Record<Temperature["type"], string>> = {
+    Rising: "↗",
+    Falling: "↘",
+    Steady: "→",
+};
+
+/** "ByMorning" -> "by morning", other phrasings as written. */
+const describeQualifier = (qualifier: TimeQualifier) =>
+    typeof qualifier === "string"
+        ? qualifier.replace(/([A-Z])/g, " $1").trim().toLowerCase()
+        : qualifier.Other;
+
+function renderTrend(t: Temperature, unit: Unit) {
+    const arrow = TREND_ARROWS[t.type];
+    return (
+        <span title={t.qualifier && describeQualifier(t.qualifier)}>
+            {arrow && `${arrow} `}{renderTemperature(t.content, unit)}
+        </span>
+    );
+}
+
+/** Draw the conditions of a period in order when there is more than one. */
+function renderTimeline(timeline: TimelineSegment[]) {
+    if (timeline.length < 2) {
+        return null;
+    }
+    return (
+        <div className={classes.timeline}>
+            {timeline.map((segment, index) =>
+                <span key={index} className={classes.segment}>
+                    {segment.condition}
+                    {segment.start && ` (${describeQualifier(segment.start)})`}
+                </span>
+            )}
+        </div>
+    );
+}
+
+/** "6° above normal", in the unit shown. */
+function renderNormal(normal: Departure | null, unit: Unit) {
+    if (!normal) {
+        return null;
+    }
+    const degrees = Math.round(unit === "Celsius" ? normal.departure : normal.departure * 9 / 5);
+    const text = degrees === 0
+        ? "near normal"
+        : `${Math.abs(degrees)}° ${degrees > 0 ? "above" : "below"} normal`;
+    return (
+        <div className={classes.normal}>
+            {text}{normal.record && " (record)"}
+        </div>
+    );
+}
+
+const NOT_REPORTED = "Not reported";
+
 const renderCurrent = (entry: CurrentEntry, unit: Unit) => (
     <Tooltip htmlMessage={entry.entry.summary}>
         <div className={classes.futureContainer}>
@@ -85,11 +148,13 @@ const renderCurrent = (entry: CurrentEntry, unit: Unit) => (
                 <span>Now</span>
             </div>
             <div className={classes.current}>
-                <div className={classes.temperature}>
-                    {renderTemperature(entry.current.celsius.content, unit, true)}
+                <div className={entry.current.celsius ? classes.temperature : classes.unreported}>
+                    {entry.current.celsius
+                        ? renderTemperature(entry.current.celsius.content, unit, true)
+                        : "—"}
                 </div>
-                <div className={classes.description}>
-                    {entry.current.description}
+                <div className={entry.current.description ? classes.description : classes.unreported}>
+                    {entry.current.description ?? NOT_REPORTED}
                 </div>
             </div>
         </div>